@@ -2,8 +2,8 @@ use crate::error::ContractError;
 use crate::state::{read_config, store_config, Config};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::CosmosMsg;
 use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{BankMsg, CosmosMsg};
 use cosmwasm_std::{Coin, Empty};
 use cosmwasm_std::{Uint128, WasmMsg};
 use moneymarket::bucket::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
@@ -42,6 +42,11 @@ pub fn execute(
             overseer_contract,
         } => update_config(deps, info, owner, sender_contract, overseer_contract),
         ExecuteMsg::Send { denom, amount } => execute_send(deps, info, denom, amount),
+        ExecuteMsg::SendTo {
+            denom,
+            amount,
+            recipient,
+        } => execute_send_to(deps, info, denom, amount, recipient),
     }
 }
 
@@ -73,6 +78,29 @@ pub fn execute_send(
         })))
 }
 
+pub fn execute_send_to(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    amount: Uint128,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let config = read_config(deps.storage)?;
+
+    if info.sender != config.sender_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+    Ok(Response::new()
+        .add_attribute("action", "send_to")
+        .add_attribute("recipient", recipient.to_string())
+        .add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin { denom, amount }],
+        })))
+}
+
 pub fn update_config(
     deps: DepsMut,
     info: MessageInfo,
@@ -2,7 +2,7 @@ use crate::contract::{execute, instantiate, query};
 use crate::error::ContractError;
 use cosmwasm_std::from_json;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{BankMsg, Coin, CosmosMsg, SubMsg, Uint128};
 use moneymarket::bucket::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
 
 #[test]
@@ -111,3 +111,54 @@ fn send_from_bucket() {
 
     assert_eq!(res, res);
 }
+
+#[test]
+fn send_to_from_bucket() {
+    let mut deps = mock_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::UpdateConfig {
+            owner: None,
+            sender_contract: Some("sender".to_string()),
+            overseer_contract: Some("overseer".to_string()),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::SendTo {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+        recipient: "borrower".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("sender", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "borrower".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        }))]
+    );
+}
@@ -10,7 +10,7 @@ use cosmwasm_std::{
     StdResult, Uint256, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
-use moneymarket::custody::{BorrowerResponse, BorrowersResponse};
+use moneymarket::custody::{BorrowerResponse, BorrowersResponse, LiquidationMarket};
 use moneymarket::liquidation_queue::Cw20HookMsg as LiquidationCw20HookMsg;
 
 /// Deposit new collateral
@@ -150,18 +150,51 @@ pub fn unlock_collateral(
     ]))
 }
 
+/// Unlock specified amount of collateral token
+/// and send it back to the borrower
+/// Executor: overseer
+pub fn unlock_and_withdraw_collateral(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    unlock_collateral(deps.branch(), info, borrower.clone(), amount)?;
+
+    withdraw_collateral(
+        deps,
+        MessageInfo {
+            sender: borrower,
+            funds: vec![],
+        },
+        Some(amount),
+    )
+}
+
 pub fn liquidate_collateral(
     deps: DepsMut,
     info: MessageInfo,
     liquidator: Addr,
     borrower: Addr,
     amount: Uint256,
+    market: Option<LiquidationMarket>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
         return Err(ContractError::Unauthorized {});
     }
 
+    let (market_contract, liquidation_contract) = match market {
+        Some(market) => (
+            deps.api.addr_validate(&market.market_contract)?,
+            deps.api.addr_validate(&market.liquidation_contract)?,
+        ),
+        None => (
+            deps.api.addr_humanize(&config.market_contract)?,
+            deps.api.addr_humanize(&config.liquidation_contract)?,
+        ),
+    };
+
     let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
     let locked_amount = borrower_info.balance - borrower_info.spendable;
@@ -182,10 +215,7 @@ pub fn liquidate_collateral(
                 .to_string(),
             funds: vec![],
             msg: to_json_binary(&Cw20ExecuteMsg::Send {
-                contract: deps
-                    .api
-                    .addr_humanize(&config.liquidation_contract)?
-                    .to_string(),
+                contract: liquidation_contract.to_string(),
                 amount: amount.try_into()?,
                 msg: to_json_binary(&LiquidationCw20HookMsg::ExecuteBid {
                     liquidator: liquidator.to_string(),
@@ -194,9 +224,8 @@ pub fn liquidate_collateral(
                             .addr_humanize(&config.overseer_contract)?
                             .to_string(),
                     ),
-                    repay_address: Some(
-                        deps.api.addr_humanize(&config.market_contract)?.to_string(),
-                    ),
+                    repay_address: Some(market_contract.to_string()),
+                    borrower: Some(borrower.to_string()),
                 })?,
             })?,
         }))
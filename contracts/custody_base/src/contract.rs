@@ -7,7 +7,7 @@ use cosmwasm_std::{
 
 use crate::collateral::{
     deposit_collateral, liquidate_collateral, lock_collateral, query_borrower, query_borrowers,
-    unlock_collateral, withdraw_collateral,
+    unlock_and_withdraw_collateral, unlock_collateral, withdraw_collateral,
 };
 use crate::error::ContractError;
 use crate::state::{read_config, store_config, Config};
@@ -74,17 +74,26 @@ pub fn execute(
             let borrower_addr = deps.api.addr_validate(&borrower)?;
             unlock_collateral(deps, info, borrower_addr, amount)
         }
+        ExecuteMsg::UnlockAndWithdrawCollateral { borrower, amount } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            unlock_and_withdraw_collateral(deps, info, borrower_addr, amount)
+        }
         ExecuteMsg::DistributeRewards {} => Ok(Response::new()),
         ExecuteMsg::WithdrawCollateral { amount } => withdraw_collateral(deps, info, amount),
         ExecuteMsg::LiquidateCollateral {
             liquidator,
             borrower,
             amount,
+            market,
         } => {
             let liquidator_addr = deps.api.addr_validate(&liquidator)?;
             let borrower_addr = deps.api.addr_validate(&borrower)?;
-            liquidate_collateral(deps, info, liquidator_addr, borrower_addr, amount)
+            liquidate_collateral(deps, info, liquidator_addr, borrower_addr, amount, market)
         }
+        ExecuteMsg::BuyCollateral { .. }
+        | ExecuteMsg::SellCollateral { .. }
+        | ExecuteMsg::DepositBoughtCollateral { .. }
+        | ExecuteMsg::RepayWithSoldCollateral { .. } => Err(ContractError::LeverageNotSupported {}),
     }
 }
 
@@ -96,15 +105,16 @@ pub fn receive_cw20(
     let contract_addr = info.sender;
 
     match from_json(&cw20_msg.msg) {
-        Ok(Cw20HookMsg::DepositCollateral { borrower: None }) => {
+        Ok(Cw20HookMsg::DepositCollateral { borrower }) => {
             // only asset contract can execute this message
             let config: Config = read_config(deps.storage)?;
             if deps.api.addr_canonicalize(contract_addr.as_str())? != config.collateral_token {
                 return Err(ContractError::Unauthorized {});
             }
 
-            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
-            deposit_collateral(deps, cw20_sender_addr, cw20_msg.amount.into())
+            let borrower = borrower.unwrap_or(cw20_msg.sender);
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            deposit_collateral(deps, borrower_addr, cw20_msg.amount.into())
         }
         _ => Err(ContractError::MissingDepositCollateralHook {}),
     }
@@ -32,4 +32,7 @@ pub enum ContractError {
 
     #[error("Withdraw amount cannot exceed the user's spendable amount: {0}")]
     WithdrawAmountExceedsSpendable(Uint128),
+
+    #[error("Leverage is not supported by this custody contract")]
+    LeverageNotSupported {},
 }
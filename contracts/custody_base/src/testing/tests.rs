@@ -638,6 +638,7 @@ fn liquidate_collateral() {
         liquidator: "addr0001".to_string(),
         borrower: "addr0000".to_string(),
         amount: Uint256::from(100u64),
+        market: None,
     };
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
@@ -656,6 +657,7 @@ fn liquidate_collateral() {
         liquidator: "liquidator".to_string(),
         borrower: "addr0000".to_string(),
         amount: Uint256::from(10u64),
+        market: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
@@ -680,6 +682,7 @@ fn liquidate_collateral() {
                     liquidator: "liquidator".to_string(),
                     fee_address: Some("overseer".to_string()),
                     repay_address: Some("market".to_string()),
+                    borrower: Some("addr0000".to_string()),
                 })
                 .unwrap()
             })
@@ -8,10 +8,13 @@ use moneymarket::astroport_router::AssetInfo;
 
 use crate::collateral::{
     deposit_collateral, liquidate_collateral, lock_collateral, query_borrower, query_borrowers,
-    unlock_collateral, withdraw_collateral,
+    unlock_and_withdraw_collateral, unlock_collateral, withdraw_collateral,
 };
 use crate::distribution::{distribute_hook, distribute_rewards, swap_to_stable_denom};
 use crate::error::ContractError;
+use crate::leverage::{
+    buy_collateral, deposit_bought_collateral, repay_with_sold_collateral, sell_collateral,
+};
 use crate::state::{read_config, store_config, store_swap_config, Config, SwapConfig};
 
 use cw20::Cw20ReceiveMsg;
@@ -95,16 +98,51 @@ pub fn execute(
             let borrower_addr = deps.api.addr_validate(&borrower)?;
             unlock_collateral(deps, info, borrower_addr, amount)
         }
+        ExecuteMsg::UnlockAndWithdrawCollateral { borrower, amount } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            unlock_and_withdraw_collateral(deps, info, borrower_addr, amount)
+        }
         ExecuteMsg::DistributeRewards {} => distribute_rewards(deps, env, info),
         ExecuteMsg::WithdrawCollateral { amount } => withdraw_collateral(deps, info, amount),
         ExecuteMsg::LiquidateCollateral {
             liquidator,
             borrower,
             amount,
+            market,
         } => {
             let liquidator_addr = deps.api.addr_validate(&liquidator)?;
             let borrower_addr = deps.api.addr_validate(&borrower)?;
-            liquidate_collateral(deps, info, liquidator_addr, borrower_addr, amount)
+            liquidate_collateral(deps, info, liquidator_addr, borrower_addr, amount, market)
+        }
+        ExecuteMsg::BuyCollateral {
+            borrower,
+            amount,
+            minimum_receive,
+        } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            buy_collateral(deps, env, info, borrower_addr, amount, minimum_receive)
+        }
+        ExecuteMsg::SellCollateral {
+            borrower,
+            amount,
+            minimum_receive,
+        } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            sell_collateral(deps, env, info, borrower_addr, amount, minimum_receive)
+        }
+        ExecuteMsg::DepositBoughtCollateral {
+            borrower,
+            prev_balance,
+        } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            deposit_bought_collateral(deps, env, info, borrower_addr, prev_balance)
+        }
+        ExecuteMsg::RepayWithSoldCollateral {
+            borrower,
+            prev_balance,
+        } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            repay_with_sold_collateral(deps, env, info, borrower_addr, prev_balance)
         }
     }
 }
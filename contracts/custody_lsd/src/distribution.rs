@@ -117,6 +117,7 @@ pub fn swap_to_stable_denom(deps: DepsMut, env: Env) -> Result<Response, Contrac
                     amount: coin.amount,
                 },
                 config.stable_token.clone(),
+                None,
             )
         })
         .flat_map(|result| match result {
@@ -142,6 +143,7 @@ pub fn swap_to_stable_denom(deps: DepsMut, env: Env) -> Result<Response, Contrac
                     amount: asset.amount,
                 },
                 config.stable_token.clone(),
+                None,
             )
         })
         .flat_map(|result| match result {
@@ -27,6 +27,9 @@ pub enum ContractError {
     #[error("Invalid request: \"deposit collateral\" message not included in request")]
     MissingDepositCollateralHook {},
 
+    #[error("Sell amount cannot exceed locked amount: {0}")]
+    SellAmountExceedsLocked(Uint128),
+
     #[error("Unlock amount cannot exceed locked amount: {0}")]
     UnlockAmountExceedsLocked(Uint128),
 
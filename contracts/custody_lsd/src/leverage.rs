@@ -0,0 +1,204 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response, Uint256,
+    WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::astroport_router::AssetInfo;
+use moneymarket::custody::{Asset, ExecuteMsg};
+use moneymarket::market::{Cw20HookMsg as MarketCw20HookMsg, ExecuteMsg as MarketExecuteMsg};
+use moneymarket::querier::{query_all_token_types_balance, query_token_balance};
+use std::convert::TryInto;
+
+use crate::collateral::deposit_collateral;
+use crate::error::ContractError;
+use crate::state::{read_borrower_info, read_config, store_borrower_info, BorrowerInfo, Config};
+use crate::swap::create_swap_msg;
+
+/// Swap the stable sent by the market to collateral tokens,
+/// they are deposited for the borrower in `DepositBoughtCollateral`
+/// Executor: overseer
+pub fn buy_collateral(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    amount: Uint256,
+    minimum_receive: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_token = deps.api.addr_humanize(&config.collateral_token)?;
+    let prev_balance = query_token_balance(
+        deps.as_ref(),
+        collateral_token.clone(),
+        env.contract.address.clone(),
+    )?;
+
+    let mut messages = create_swap_msg(
+        deps.as_ref(),
+        env.clone(),
+        Asset {
+            asset_info: config.stable_token,
+            amount: amount.try_into()?,
+        },
+        AssetInfo::Token {
+            contract_addr: collateral_token,
+        },
+        Some(minimum_receive.try_into()?),
+    )?;
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_json_binary(&ExecuteMsg::DepositBoughtCollateral {
+            borrower: borrower.to_string(),
+            prev_balance,
+        })?,
+    }));
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "buy_collateral"),
+        attr("borrower", borrower),
+        attr("amount", amount),
+    ]))
+}
+
+/// Executor: itself
+pub fn deposit_bought_collateral(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    prev_balance: Uint256,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let cur_balance = query_token_balance(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.collateral_token)?,
+        env.contract.address,
+    )?;
+
+    deposit_collateral(deps, borrower, cur_balance - prev_balance)
+}
+
+/// Swap locked collateral tokens of the borrower to stable,
+/// the loan is repaid in `RepayWithSoldCollateral`
+/// Executor: overseer
+pub fn sell_collateral(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    amount: Uint256,
+    minimum_receive: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    let locked_amount = borrower_info.balance - borrower_info.spendable;
+    if amount > locked_amount {
+        return Err(ContractError::SellAmountExceedsLocked(
+            locked_amount.try_into()?,
+        ));
+    }
+
+    borrower_info.balance -= amount;
+    store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+
+    let prev_balance = query_all_token_types_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        config.stable_token.clone(),
+    )?;
+
+    let mut messages = create_swap_msg(
+        deps.as_ref(),
+        env.clone(),
+        Asset {
+            asset_info: AssetInfo::Token {
+                contract_addr: deps.api.addr_humanize(&config.collateral_token)?,
+            },
+            amount: amount.try_into()?,
+        },
+        config.stable_token,
+        Some(minimum_receive.try_into()?),
+    )?;
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_json_binary(&ExecuteMsg::RepayWithSoldCollateral {
+            borrower: borrower.to_string(),
+            prev_balance,
+        })?,
+    }));
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "sell_collateral"),
+        attr("borrower", borrower),
+        attr("amount", amount),
+    ]))
+}
+
+/// Executor: itself
+pub fn repay_with_sold_collateral(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    prev_balance: Uint256,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let cur_balance = query_all_token_types_balance(
+        deps.as_ref(),
+        env.contract.address,
+        config.stable_token.clone(),
+    )?;
+    let repay_amount = cur_balance - prev_balance;
+
+    let market_contract = deps.api.addr_humanize(&config.market_contract)?;
+    let repay_message = match config.stable_token {
+        AssetInfo::NativeToken { denom } => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: market_contract.to_string(),
+            funds: vec![Coin {
+                denom,
+                amount: repay_amount.try_into()?,
+            }],
+            msg: to_json_binary(&MarketExecuteMsg::RepayStable {
+                borrower: Some(borrower.to_string()),
+            })?,
+        }),
+        AssetInfo::Token { contract_addr } => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                contract: market_contract.to_string(),
+                amount: repay_amount.try_into()?,
+                msg: to_json_binary(&MarketCw20HookMsg::RepayStable {
+                    borrower: Some(borrower.to_string()),
+                })?,
+            })?,
+        }),
+    };
+
+    Ok(Response::new()
+        .add_message(repay_message)
+        .add_attributes(vec![
+            attr("action", "repay_with_sold_collateral"),
+            attr("borrower", borrower),
+            attr("repay_amount", repay_amount),
+        ]))
+}
@@ -2,6 +2,7 @@ pub mod collateral;
 pub mod contract;
 pub mod distribution;
 pub mod error;
+pub mod leverage;
 pub mod state;
 pub mod swap;
 
@@ -33,6 +33,7 @@ pub fn create_swap_msg(
     env: Env,
     asset_to_swap: Asset,
     stable_token: AssetInfo,
+    minimum_receive: Option<Uint128>,
 ) -> StdResult<Vec<CosmosMsg>> {
     let (best_price_index, best_price): (usize, Uint128) = SwapMessageType::iter()
         .map(|message_type| {
@@ -62,6 +63,7 @@ pub fn create_swap_msg(
         asset_to_swap,
         stable_token,
         best_price_marketplace,
+        minimum_receive,
     )?])
 }
 
@@ -117,6 +119,7 @@ pub fn create_swap_message_for(
     asset_to_swap: Asset,
     stable_token: AssetInfo,
     message_type: SwapMessageType,
+    minimum_receive: Option<Uint128>,
 ) -> StdResult<CosmosMsg> {
     let swap_contract_address = get_contract_address(deps, message_type)?;
 
@@ -135,7 +138,7 @@ pub fn create_swap_message_for(
                     )],
                     to: None,
                     max_spread: Some(Decimal::from_str("0.1")?),
-                    minimum_receive: None,
+                    minimum_receive,
                 })?,
             })?,
         })),
@@ -148,7 +151,7 @@ pub fn create_swap_message_for(
                 )],
                 to: None,
                 max_spread: Some(Decimal::from_str("0.1")?),
-                minimum_receive: None,
+                minimum_receive,
             },
             swap_contract_address,
             vec![Coin {
@@ -8,7 +8,7 @@ use cosmwasm_std::{
     SystemResult, Uint128, WasmQuery,
 };
 use cosmwasm_storage::to_length_prefixed;
-use cw20::TokenInfoResponse;
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 use moneymarket::astroport_router::QueryMsg as SwapQueryMsg;
 use moneymarket::astroport_router::SimulateSwapOperationsResponse;
 use std::collections::HashMap;
@@ -186,6 +186,23 @@ impl WasmMockQuerier {
                         request: msg.clone(),
                     }),
                 },
+                "bluna" => match from_json(msg).unwrap() {
+                    Cw20QueryMsg::Balance { address } => SystemResult::Ok(ContractResult::from(
+                        to_json_binary(&Cw20BalanceResponse {
+                            balance: self
+                                .token_querier
+                                .balances
+                                .get(contract_addr)
+                                .and_then(|balances| balances.get(&address))
+                                .cloned()
+                                .unwrap_or_default(),
+                        }),
+                    )),
+                    _ => SystemResult::Err(SystemError::InvalidRequest {
+                        error: "not covered".to_string(),
+                        request: msg.clone(),
+                    }),
+                },
                 _ => match from_json(msg).unwrap() {
                     RewardContractQueryMsg::AccruedRewards { address: _ } => SystemResult::Ok(
                         ContractResult::from(to_json_binary(&BLunaAccruedRewardsResponse {
@@ -22,6 +22,7 @@ use moneymarket::custody::{
     QueryMsg,
 };
 use moneymarket::liquidation_queue::Cw20HookMsg as LiquidationCw20HookMsg;
+use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 
 #[test]
 fn proper_initialization() {
@@ -874,7 +875,8 @@ fn swap_to_stable_denom() {
                     },
                     AssetInfo::NativeToken {
                         denom: "uusd".to_string(),
-                    }
+                    },
+                    None,
                 )
                 .unwrap()[0]
                     .clone()
@@ -892,6 +894,7 @@ fn swap_to_stable_denom() {
                     AssetInfo::NativeToken {
                         denom: "uusd".to_string(),
                     },
+                    None,
                 )
                 .unwrap()[0]
                     .clone(),
@@ -966,6 +969,7 @@ fn liquidate_collateral() {
         liquidator: "addr0001".to_string(),
         borrower: "addr0000".to_string(),
         amount: Uint256::from(100u64),
+        market: None,
     };
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
@@ -984,6 +988,7 @@ fn liquidate_collateral() {
         liquidator: "liquidator".to_string(),
         borrower: "addr0000".to_string(),
         amount: Uint256::from(10u64),
+        market: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
@@ -1008,6 +1013,7 @@ fn liquidate_collateral() {
                     liquidator: "liquidator".to_string(),
                     fee_address: Some("overseer".to_string()),
                     repay_address: Some("market".to_string()),
+                    borrower: Some("addr0000".to_string()),
                 })
                 .unwrap()
             })
@@ -1096,3 +1102,188 @@ fn proper_distribute_rewards_with_no_rewards() {
         ),]
     );
 }
+
+#[test]
+fn buy_and_sell_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = LSDInstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: "bluna".to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_token: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        basset_info: BAssetInfo {
+            name: "bluna".to_string(),
+            symbol: "bluna".to_string(),
+            decimals: 6,
+        },
+        astroport_addr: "astroport_addr".to_string(),
+        phoenix_addr: "phoenix_addr".to_string(),
+        terraswap_addr: "terraswap_addr".to_string(),
+
+        known_tokens: vec![],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_json_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
+    });
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("bluna", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(60u64),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::BuyCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(50u64),
+        minimum_receive: Uint256::from(45u64),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The stable sent by the market is swapped to collateral tokens
+    deps.querier.with_token_balances(&[(
+        &"bluna".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+    let res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap();
+    let mut messages = create_swap_msg(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            amount: Uint128::from(50u128),
+            asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+        },
+        AssetInfo::Token {
+            contract_addr: deps.api.addr_validate("bluna").unwrap(),
+        },
+        Some(Uint128::from(45u128)),
+    )
+    .unwrap();
+    let deposit_bought_collateral = ExecuteMsg::DepositBoughtCollateral {
+        borrower: "addr0000".to_string(),
+        prev_balance: Uint256::from(100u64),
+    };
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+        funds: vec![],
+        msg: to_json_binary(&deposit_bought_collateral).unwrap(),
+    }));
+    assert_eq!(
+        res.messages,
+        messages.into_iter().map(SubMsg::new).collect::<Vec<SubMsg>>()
+    );
+
+    // The bought amount is deposited for the borrower
+    deps.querier.with_token_balances(&[(
+        &"bluna".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(145u128))],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        deposit_bought_collateral,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_collateral"),
+            attr("borrower", "addr0000"),
+            attr("amount", "45"),
+        ]
+    );
+
+    // Only locked collateral can be sold
+    let msg = ExecuteMsg::SellCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(100u64),
+        minimum_receive: Uint256::from(90u64),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::SellAmountExceedsLocked(60u128.into()));
+
+    deps.querier.set_other_balances(Uint128::from(1000u128));
+    let msg = ExecuteMsg::SellCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(60u64),
+        minimum_receive: Uint256::from(54u64),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg).unwrap();
+    let mut messages = create_swap_msg(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            amount: Uint128::from(60u128),
+            asset_info: AssetInfo::Token {
+                contract_addr: deps.api.addr_validate("bluna").unwrap(),
+            },
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        Some(Uint128::from(54u128)),
+    )
+    .unwrap();
+    let repay_with_sold_collateral = ExecuteMsg::RepayWithSoldCollateral {
+        borrower: "addr0000".to_string(),
+        prev_balance: Uint256::from(1000u64),
+    };
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+        funds: vec![],
+        msg: to_json_binary(&repay_with_sold_collateral).unwrap(),
+    }));
+    assert_eq!(
+        res.messages,
+        messages.into_iter().map(SubMsg::new).collect::<Vec<SubMsg>>()
+    );
+    let borrower_info = read_borrower_info(
+        deps.as_ref().storage,
+        &deps.api.addr_canonicalize("addr0000").unwrap(),
+    );
+    assert_eq!(borrower_info.balance, Uint256::from(85u64));
+    assert_eq!(borrower_info.spendable, Uint256::from(85u64));
+
+    // The swapped stable repays the loan of the borrower
+    deps.querier.set_other_balances(Uint128::from(1055u128));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        repay_with_sold_collateral,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            funds: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(55u128),
+            }],
+            msg: to_json_binary(&MarketExecuteMsg::RepayStable {
+                borrower: Some("addr0000".to_string()),
+            })
+            .unwrap(),
+        }))]
+    );
+}
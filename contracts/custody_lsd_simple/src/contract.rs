@@ -8,7 +8,7 @@ use moneymarket::astroport_router::AssetInfo;
 
 use crate::collateral::{
     deposit_collateral, liquidate_collateral, lock_collateral, query_borrower, query_borrowers,
-    unlock_collateral, withdraw_collateral,
+    unlock_and_withdraw_collateral, unlock_collateral, withdraw_collateral,
 };
 use crate::distribution::distribute_rewards;
 use crate::error::ContractError;
@@ -91,17 +91,26 @@ pub fn execute(
             let borrower_addr = deps.api.addr_validate(&borrower)?;
             unlock_collateral(deps, info, borrower_addr, amount)
         }
+        ExecuteMsg::UnlockAndWithdrawCollateral { borrower, amount } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            unlock_and_withdraw_collateral(deps, info, borrower_addr, amount)
+        }
         ExecuteMsg::DistributeRewards {} => distribute_rewards(deps, env, info),
         ExecuteMsg::WithdrawCollateral { amount } => withdraw_collateral(deps, info, amount),
         ExecuteMsg::LiquidateCollateral {
             liquidator,
             borrower,
             amount,
+            market,
         } => {
             let liquidator_addr = deps.api.addr_validate(&liquidator)?;
             let borrower_addr = deps.api.addr_validate(&borrower)?;
-            liquidate_collateral(deps, info, liquidator_addr, borrower_addr, amount)
+            liquidate_collateral(deps, info, liquidator_addr, borrower_addr, amount, market)
         }
+        ExecuteMsg::BuyCollateral { .. }
+        | ExecuteMsg::SellCollateral { .. }
+        | ExecuteMsg::DepositBoughtCollateral { .. }
+        | ExecuteMsg::RepayWithSoldCollateral { .. } => Err(ContractError::LeverageNotSupported {}),
     }
 }
 
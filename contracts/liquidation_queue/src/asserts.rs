@@ -1,5 +1,6 @@
 use crate::state::Bid;
 use cosmwasm_std::{Decimal256, Env, StdError, StdResult, Uint256};
+use moneymarket::liquidation_queue::DutchAuction;
 
 const MAX_SLOT_CAP: u8 = 30u8;
 
@@ -70,3 +71,33 @@ pub fn assert_max_slot_premium(max_slot: u8, premium_rate_per_slot: Decimal256)
     }
     Ok(())
 }
+
+pub fn assert_premium_rates(max_slot: u8, premium_rates: &[Decimal256]) -> StdResult<()> {
+    if premium_rates.len() != max_slot as usize + 1 {
+        return Err(StdError::generic_err(
+            "Premium rates must be set for each slot up to the max slot",
+        ));
+    }
+    if premium_rates.windows(2).any(|rates| rates[0] > rates[1]) {
+        return Err(StdError::generic_err(
+            "Premium rates must not decrease with the slot",
+        ));
+    }
+    if premium_rates[max_slot as usize] >= Decimal256::one() {
+        return Err(StdError::generic_err("Max slot premium rate exceeds limit"));
+    }
+    Ok(())
+}
+
+pub fn assert_dutch_auction(dutch_auction: &DutchAuction) -> StdResult<()> {
+    if dutch_auction.max_discount >= Decimal256::one() {
+        return Err(StdError::generic_err("Max discount should be below 1"));
+    }
+    if dutch_auction.start_discount > dutch_auction.max_discount {
+        return Err(StdError::generic_err(
+            "Start discount can't be above the max discount",
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use crate::bid::{collateral_transfer_msg, route_liquidation_proceeds};
+use crate::querier::query_collateral_price;
+use crate::state::{
+    read_auction, read_auctions, read_config, read_dutch_auction, read_stable_asset, store_auction,
+    Auction, Config,
+};
+use cosmwasm_std::{
+    attr, Addr, CosmosMsg, Decimal256, Deps, DepsMut, Env, Response, StdError, StdResult, Uint128,
+    Uint256,
+};
+use moneymarket::custody::Asset;
+use moneymarket::liquidation_queue::{AuctionResponse, AuctionsResponse, DutchAuctionResponse};
+use std::convert::TryInto;
+
+/// Keepers buy the collateral of an auction lot at the oracle price less the
+/// current discount. The stable paid goes through the fee routing of the
+/// liquidation, the repay amount repays the loan of the borrower in the
+/// market and the excess is refunded
+pub fn buy_auction(
+    deps: DepsMut,
+    env: Env,
+    buyer: Addr,
+    collateral_token: String,
+    auction_idx: Uint128,
+    amount: Uint256,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let mut auction: Auction = read_auction(deps.storage, &collateral_token_raw, auction_idx)?;
+
+    let price = query_collateral_price(deps.as_ref(), &env, &config, collateral_token.clone())?;
+    let discount = auction_discount(&auction, env.block.height);
+    let auction_price = price * (Decimal256::one() - discount);
+
+    let mut collateral_amount =
+        Decimal256::from_ratio(amount, 1u128) / auction_price * Uint256::one();
+    if collateral_amount > auction.amount {
+        collateral_amount = auction.amount;
+    }
+    if collateral_amount.is_zero() {
+        return Err(StdError::generic_err("Payment is too small"));
+    }
+    let cost = collateral_amount * auction_price;
+
+    auction.amount -= collateral_amount;
+    store_auction(deps.storage, &auction)?;
+
+    let (proceeds_messages, repay_amount, bid_fee, liquidator_fee) = route_liquidation_proceeds(
+        deps.storage,
+        &config,
+        cost,
+        auction.repay_address,
+        auction.fee_address,
+        auction.liquidator,
+        Some(auction.borrower),
+    )?;
+
+    let mut messages: Vec<CosmosMsg> = vec![collateral_transfer_msg(
+        &collateral_token,
+        buyer.clone(),
+        collateral_amount,
+    )?];
+    messages.extend(proceeds_messages);
+    if amount > cost {
+        messages.push(
+            Asset {
+                amount: (amount - cost).try_into()?,
+                asset_info: read_stable_asset(deps.storage, &config)?,
+            }
+            .to_msg(buyer)?,
+        );
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "buy_auction"),
+        attr("auction_idx", auction_idx),
+        attr("collateral_token", collateral_token),
+        attr("collateral_amount", collateral_amount),
+        attr("discount", discount.to_string()),
+        attr("repay_amount", repay_amount),
+        attr("bid_fee", bid_fee),
+        attr("liquidator_fee", liquidator_fee),
+    ]))
+}
+
+/// The lot keeps the Dutch auction of its liquidation, owner updates only
+/// apply to the next lots
+fn auction_discount(auction: &Auction, block_height: u64) -> Decimal256 {
+    let dutch_auction = &auction.dutch_auction;
+    let blocks = block_height.saturating_sub(auction.start_height);
+    let discount = dutch_auction.start_discount
+        + dutch_auction.discount_per_block * Decimal256::from_ratio(blocks, 1u64);
+
+    discount.min(dutch_auction.max_discount)
+}
+
+pub fn query_dutch_auction(
+    deps: Deps,
+    collateral_token: String,
+) -> StdResult<DutchAuctionResponse> {
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+
+    Ok(DutchAuctionResponse {
+        dutch_auction: read_dutch_auction(deps.storage, &collateral_token_raw)?,
+    })
+}
+
+pub fn query_auctions(
+    deps: Deps,
+    env: Env,
+    collateral_token: String,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<AuctionsResponse> {
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+
+    let auctions: Vec<AuctionResponse> =
+        read_auctions(deps.storage, &collateral_token_raw, start_after, limit)?
+            .into_iter()
+            .map(|auction| AuctionResponse {
+                idx: auction.idx,
+                collateral_token: collateral_token.clone(),
+                borrower: auction.borrower.clone(),
+                amount: auction.amount,
+                start_height: auction.start_height,
+                discount: auction_discount(&auction, env.block.height),
+            })
+            .collect();
+
+    Ok(AuctionsResponse { auctions })
+}
@@ -1,19 +1,38 @@
 use crate::asserts::{assert_activate_status, assert_withdraw_amount};
-use crate::querier::query_collateral_whitelist_info;
+use crate::querier::{query_collateral_price, query_collateral_whitelist_info};
 use crate::state::{
-    pop_bid_idx, read_bid, read_bid_pool, read_bids_by_user, read_collateral_info, read_config,
-    read_epoch_scale_sum, read_or_create_bid_pool, read_total_bids, remove_bid, store_bid,
-    store_bid_pool, store_epoch_scale_sum, store_total_bids, Bid, BidPool, CollateralInfo, Config,
+    pop_auction_idx, pop_bid_idx, read_age_priority, read_auto_claim, read_bid,
+    read_bid_fees_collected, read_bid_pool, read_bids_by_user, read_collateral_auto_claim_bids,
+    read_collateral_info, read_config, read_dex_fallback, read_dutch_auction, read_epoch_scale_sum,
+    read_oldest_active_bid, read_or_create_bid_pool, read_stable_asset, read_swap_config,
+    read_total_bids, remove_active_bid_indexer, remove_bid, store_auction, store_auto_claim,
+    store_bid, store_bid_fees_collected, store_bid_owner, store_bid_pool, store_epoch_scale_sum,
+    store_pending_dex_liquidation, store_total_bids, take_pending_dex_liquidation, Auction, Bid,
+    BidPool, CollateralInfo, Config, DexLiquidation,
 };
+use crate::swap::create_swap_msg;
 use cosmwasm_std::{
-    attr, to_json_binary, BankMsg, CanonicalAddr, Coin, CosmosMsg, Decimal256, DepsMut, Env,
-    MessageInfo, Response, StdError, StdResult, Storage, Uint128, Uint256, WasmMsg,
+    attr, to_json_binary, Addr, Api, CanonicalAddr, Coin, CosmosMsg, Decimal256, DepsMut, Env,
+    MessageInfo, Response, StdError, StdResult, Storage, SubMsg, SubMsgResult, Uint128, Uint256,
+    WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
+use moneymarket::astroport_router::AssetInfo;
+use moneymarket::custody::Asset;
+use moneymarket::liquidation_queue::AutoClaim;
+use moneymarket::market::{Cw20HookMsg as MarketCw20HookMsg, ExecuteMsg as MarketExecuteMsg};
 use moneymarket::oracle::PriceResponse;
-use moneymarket::querier::{query_price, TimeConstraints};
+use moneymarket::querier::{query_all_token_types_balance, query_price, TimeConstraints};
 use std::convert::TryInto;
 
+pub const DEX_LIQUIDATION_REPLY_ID: u64 = 2;
+
+/// Auto-claim bids visited by a single liquidation
+const MAX_AUTO_CLAIMS: usize = 10;
+/// Bids of a slot a liquidation settles by age priority, the
+/// collateral left falls through to the next slot
+const MAX_AGE_PRIORITY_FILLS: usize = 10;
+
 /// Stable asset is submitted to create a bid record. If available bids for the collateral is under
 /// the threshold, the bid is activated. Bids are not used for liquidations until activated
 pub fn submit_bid(
@@ -23,17 +42,28 @@ pub fn submit_bid(
     collateral_token: String,
     premium_slot: u8,
 ) -> StdResult<Response> {
-    let config: Config = read_config(deps.storage)?;
-    let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
-    let collateral_info: CollateralInfo =
-        read_collateral_info(deps.storage, &collateral_token_raw)?;
-    let bidder_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let amount = stable_amount_sent(deps.storage, &info)?;
+
+    create_bid(
+        deps,
+        env,
+        info.sender,
+        collateral_token,
+        premium_slot,
+        amount,
+    )
+}
+
+/// Stable asset amount sent along with a message
+pub(crate) fn stable_amount_sent(storage: &dyn Storage, info: &MessageInfo) -> StdResult<Uint256> {
+    let config: Config = read_config(storage)?;
+    let stable_asset = read_stable_asset(storage, &config)?;
 
     let amount: Uint256 = info
         .funds
         .iter()
         .map(|item| {
-            if item.denom != config.stable_denom {
+            if !stable_asset.is_same_asset(item) {
                 Err(StdError::generic_err(format!(
                     "Invalid asset provided, only {} allowed",
                     config.stable_denom
@@ -51,16 +81,78 @@ pub fn submit_bid(
         })??
         .into();
 
+    Ok(amount)
+}
+
+/// Bids of a collateral filled by age priority are settled one by one,
+/// so they must not be smaller than its minimum bid amount
+pub(crate) fn assert_min_bid_amount(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    amount: Uint256,
+) -> StdResult<()> {
+    match read_age_priority(storage, collateral_token)? {
+        Some(min_bid_amount) if amount < min_bid_amount => Err(StdError::generic_err(format!(
+            "Bid amount is lower than the minimum bid amount ({})",
+            min_bid_amount
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Record a bid of `amount` stable asset received from the bidder
+pub fn create_bid(
+    deps: DepsMut,
+    env: Env,
+    bidder: Addr,
+    collateral_token: String,
+    premium_slot: u8,
+    amount: Uint256,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_info: CollateralInfo =
+        read_collateral_info(deps.storage, &collateral_token_raw)?;
+    let bidder_raw = deps.api.addr_canonicalize(bidder.as_str())?;
+    assert_min_bid_amount(deps.storage, &collateral_token_raw, amount)?;
+
+    let bid = place_bid(
+        deps.storage,
+        &env,
+        &config,
+        &collateral_info,
+        bidder_raw,
+        premium_slot,
+        amount,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "submit_bid"),
+        attr("bid_idx", bid.idx),
+        attr("amount", amount),
+    ]))
+}
+
+/// Create a bid, activated right away if the available bids for the collateral
+/// are under the threshold
+pub(crate) fn place_bid(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    collateral_info: &CollateralInfo,
+    bidder: CanonicalAddr,
+    premium_slot: u8,
+    amount: Uint256,
+) -> StdResult<Bid> {
     // read or create bid_pool, make sure slot is valid
-    let mut bid_pool: BidPool =
-        read_or_create_bid_pool(deps.storage, &collateral_info, premium_slot)?;
+    let mut bid_pool: BidPool = read_or_create_bid_pool(storage, collateral_info, premium_slot)?;
 
     // create bid object
-    let bid_idx: Uint128 = pop_bid_idx(deps.storage)?;
+    let bid_idx: Uint128 = pop_bid_idx(storage)?;
     let mut bid = Bid {
         idx: bid_idx,
-        bidder: bidder_raw,
-        collateral_token: collateral_token_raw.clone(),
+        bidder,
+        collateral_token: collateral_info.collateral_token.clone(),
         product_snapshot: Decimal256::one(),
         amount,
         premium_slot,
@@ -73,29 +165,34 @@ pub fn submit_bid(
 
     // if available bids is lower than bid_threshold, directly activate bid
     let available_bids: Uint256 =
-        read_total_bids(deps.storage, &collateral_token_raw).unwrap_or_default();
+        read_total_bids(storage, &collateral_info.collateral_token).unwrap_or_default();
     if available_bids < collateral_info.bid_threshold {
         // update bid and bid pool, add new share and pool indexes to bid
         process_bid_activation(&mut bid, &mut bid_pool, amount);
 
         // store bid_pool
-        store_bid_pool(deps.storage, &collateral_token_raw, premium_slot, &bid_pool)?;
+        store_bid_pool(
+            storage,
+            &collateral_info.collateral_token,
+            premium_slot,
+            &bid_pool,
+        )?;
 
         // increase total bid amount
-        store_total_bids(deps.storage, &collateral_token_raw, available_bids + amount)?;
+        store_total_bids(
+            storage,
+            &collateral_info.collateral_token,
+            available_bids + amount,
+        )?;
     } else {
         // calculate wait_end from current time
         bid.wait_end = Some(env.block.time.plus_seconds(config.waiting_period).seconds());
     };
 
     // save to storage
-    store_bid(deps.storage, bid_idx, &bid)?;
+    store_bid(storage, bid_idx, &bid)?;
 
-    Ok(Response::new().add_attributes(vec![
-        attr("action", "submit_bid"),
-        attr("bid_idx", bid_idx),
-        attr("amount", amount),
-    ]))
+    Ok(bid)
 }
 
 /// After bids are submitted, need to execute the activation after wait_period expires
@@ -208,6 +305,7 @@ pub fn retract_bid(
             remove_bid(deps.storage, bid.idx)?;
         } else {
             bid.amount -= waiting_withdraw_amount;
+            assert_min_bid_amount(deps.storage, &collateral_token_raw, bid.amount)?;
             store_bid(deps.storage, bid.idx, &bid)?;
         }
 
@@ -230,6 +328,13 @@ pub fn retract_bid(
         bid_pool.residue_bid += residue_bid;
         // check requested amount
         let withdraw_amount: Uint256 = assert_withdraw_amount(amount, withdrawable_amount)?;
+        if withdraw_amount != withdrawable_amount {
+            assert_min_bid_amount(
+                deps.storage,
+                &collateral_token_raw,
+                withdrawable_amount - withdraw_amount,
+            )?;
+        }
 
         // remove or update bid
         if withdraw_amount == withdrawable_amount && bid.pending_liquidated_collateral.is_zero() {
@@ -271,13 +376,13 @@ pub fn retract_bid(
 
     let mut messages: Vec<CosmosMsg> = vec![];
     if !withdraw_amount.is_zero() {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: vec![Coin {
-                denom: config.stable_denom,
+        messages.push(
+            Asset {
                 amount: withdraw_amount.try_into()?,
-            }],
-        }));
+                asset_info: read_stable_asset(deps.storage, &config)?,
+            }
+            .to_msg(info.sender)?,
+        );
     }
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
@@ -298,6 +403,7 @@ pub fn execute_liquidation(
     liquidator: String,
     repay_address: String,
     fee_address: String,
+    borrower: Option<String>,
     collateral_token: String,
     amount: Uint256,
 ) -> StdResult<Response> {
@@ -333,9 +439,11 @@ pub fn execute_liquidation(
         }),
     )?;
 
+    let age_priority = read_age_priority(deps.storage, &collateral_token_raw)?;
     let mut remaining_collateral_to_liquidate = amount;
     let mut repay_amount = Uint256::zero();
     let mut filled: bool = false;
+    let mut consumed_slots: Vec<u8> = vec![];
     for slot in 0..collateral_info.max_slot + 1 {
         let mut bid_pool: BidPool = match read_bid_pool(deps.storage, &collateral_token_raw, slot) {
             Ok(bid_pool) => bid_pool,
@@ -345,7 +453,12 @@ pub fn execute_liquidation(
             continue;
         };
 
-        let (pool_repay_amount, pool_liquidated_collateral) = execute_pool_liquidation(
+        let execute_pool = if age_priority.is_some() {
+            execute_pool_age_priority_liquidation
+        } else {
+            execute_pool_liquidation
+        };
+        let (pool_repay_amount, pool_liquidated_collateral) = execute_pool(
             deps.storage,
             &mut bid_pool,
             &collateral_token_raw,
@@ -358,6 +471,9 @@ pub fn execute_liquidation(
         store_bid_pool(deps.storage, &collateral_token_raw, slot, &bid_pool)?;
 
         repay_amount += pool_repay_amount;
+        if !pool_liquidated_collateral.is_zero() {
+            consumed_slots.push(slot);
+        }
 
         if filled {
             remaining_collateral_to_liquidate = Uint256::zero();
@@ -367,10 +483,73 @@ pub fn execute_liquidation(
         }
     }
 
-    if !remaining_collateral_to_liquidate.is_zero() {
-        return Err(StdError::generic_err(
-            "Not enough bids to execute this liquidation",
-        ));
+    // opted-in collateral the queue can't fill is market sold, the proceeds go
+    // through the fee routing in the reply, where a failed sale leaves the
+    // collateral to the Dutch auction
+    let mut dex_amount = Uint256::zero();
+    let mut dex_swap_msg: Option<SubMsg> = None;
+    if !remaining_collateral_to_liquidate.is_zero()
+        && read_dex_fallback(deps.storage, &collateral_token_raw)?
+    {
+        if let Some(swap_config) = read_swap_config(deps.storage)? {
+            let stable_asset = read_stable_asset(deps.storage, &config)?;
+            let minimum_receive = remaining_collateral_to_liquidate
+                * price.rate
+                * (Decimal256::one() - swap_config.max_slippage);
+
+            // a DEX unable to quote within the max slippage leaves the
+            // collateral to the Dutch auction right away
+            if let Ok(swap_msg) = create_swap_msg(
+                deps.as_ref(),
+                &swap_config,
+                Asset {
+                    amount: remaining_collateral_to_liquidate.try_into()?,
+                    asset_info: AssetInfo::Token {
+                        contract_addr: deps.api.addr_validate(&collateral_token)?,
+                    },
+                },
+                stable_asset.clone(),
+                minimum_receive.try_into()?,
+                env.contract.address.to_string(),
+            ) {
+                let prev_balance = query_all_token_types_balance(
+                    deps.as_ref(),
+                    env.contract.address.clone(),
+                    stable_asset,
+                )?;
+                store_pending_dex_liquidation(
+                    deps.storage,
+                    &DexLiquidation {
+                        collateral_token: collateral_token_raw.clone(),
+                        amount: remaining_collateral_to_liquidate,
+                        prev_balance,
+                        borrower: borrower.clone(),
+                        repay_address: repay_address.clone(),
+                        fee_address: fee_address.clone(),
+                        liquidator: liquidator.clone(),
+                    },
+                )?;
+                dex_swap_msg = Some(SubMsg::reply_always(swap_msg, DEX_LIQUIDATION_REPLY_ID));
+                dex_amount = remaining_collateral_to_liquidate;
+                remaining_collateral_to_liquidate = Uint256::zero();
+            }
+        }
+    }
+
+    // the collateral the queue can't fill is sold in a Dutch auction
+    let auction_amount = remaining_collateral_to_liquidate;
+    if !auction_amount.is_zero() {
+        create_auction_lot(
+            deps.storage,
+            deps.api,
+            env.block.height,
+            collateral_token_raw.clone(),
+            auction_amount,
+            borrower,
+            repay_address.clone(),
+            fee_address.clone(),
+            liquidator.clone(),
+        )?;
     }
 
     store_total_bids(
@@ -379,42 +558,58 @@ pub fn execute_liquidation(
         available_bids - repay_amount,
     )?;
 
-    let bid_fee = repay_amount * config.bid_fee;
-    let liquidator_fee = repay_amount * config.liquidator_fee;
-    let repay_amount = repay_amount - bid_fee - liquidator_fee;
-
     let mut messages: Vec<CosmosMsg> = vec![];
 
-    if !repay_amount.is_zero() {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: repay_address,
-            amount: vec![Coin {
-                denom: config.stable_denom.clone(),
-                amount: repay_amount.try_into()?,
-            }],
-        }));
-    }
+    // deliver the collateral of the auto-claim bids of the consumed slots, sales
+    // to stable are left to ClaimFor so that a failing swap can't revert the liquidation.
+    // The bids visited are capped across the slots, whether they pay out or not
+    let mut auto_claim_amount = Uint256::zero();
+    let mut auto_claims = 0usize;
+    for slot in consumed_slots {
+        if auto_claims == MAX_AUTO_CLAIMS {
+            break;
+        }
 
-    if !bid_fee.is_zero() {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: fee_address,
-            amount: vec![Coin {
-                denom: config.stable_denom.clone(),
-                amount: bid_fee.try_into()?,
-            }],
-        }));
-    }
-    if !liquidator_fee.is_zero() {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: liquidator,
-            amount: vec![Coin {
-                denom: config.stable_denom.clone(),
-                amount: liquidator_fee.try_into()?,
-            }],
-        }));
+        let bids = read_collateral_auto_claim_bids(
+            deps.storage,
+            &collateral_token_raw,
+            slot,
+            None,
+            MAX_AUTO_CLAIMS - auto_claims,
+        )?;
+        auto_claims += bids.len();
+
+        for bid in bids {
+            // waiting bids indexed before they were left out until activation
+            if bid.wait_end.is_some() {
+                continue;
+            }
+
+            let bidder = deps.api.addr_humanize(&bid.bidder)?;
+            let claim_amount = claim_bid(deps.storage, bid)?;
+            if !claim_amount.is_zero() {
+                messages.push(collateral_transfer_msg(
+                    &collateral_token,
+                    bidder,
+                    claim_amount,
+                )?);
+                auto_claim_amount += claim_amount;
+            }
+        }
     }
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
+    let (proceeds_messages, repay_amount, bid_fee, liquidator_fee) = route_liquidation_proceeds(
+        deps.storage,
+        &config,
+        repay_amount,
+        repay_address,
+        fee_address,
+        liquidator,
+        None,
+    )?;
+    messages.extend(proceeds_messages);
+
+    let mut attributes = vec![
         attr("action", "execute_bid"),
         attr("stable_denom", config.stable_denom),
         attr("repay_amount", repay_amount),
@@ -422,6 +617,118 @@ pub fn execute_liquidation(
         attr("liquidator_fee", liquidator_fee),
         attr("collateral_token", collateral_token),
         attr("collateral_amount", amount),
+    ];
+    if !auto_claim_amount.is_zero() {
+        attributes.push(attr("auto_claim_amount", auto_claim_amount));
+    }
+    if !dex_amount.is_zero() {
+        attributes.push(attr("dex_amount", dex_amount));
+    }
+    if !auction_amount.is_zero() {
+        attributes.push(attr("auction_amount", auction_amount));
+    }
+
+    // the sale is executed first, so that its reply only sees its own proceeds
+    Ok(Response::new()
+        .add_submessages(dex_swap_msg)
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+/// The lot stays attributed to the borrower and repays their loan as it is sold
+#[allow(clippy::too_many_arguments)]
+fn create_auction_lot(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    start_height: u64,
+    collateral_token: CanonicalAddr,
+    amount: Uint256,
+    borrower: Option<String>,
+    repay_address: String,
+    fee_address: String,
+    liquidator: String,
+) -> StdResult<()> {
+    let (dutch_auction, borrower) =
+        match (read_dutch_auction(storage, &collateral_token)?, borrower) {
+            (Some(dutch_auction), Some(borrower)) => (dutch_auction, borrower),
+            _ => {
+                return Err(StdError::generic_err(
+                    "Not enough bids to execute this liquidation",
+                ))
+            }
+        };
+
+    let auction_idx = pop_auction_idx(storage)?;
+    store_auction(
+        storage,
+        &Auction {
+            idx: auction_idx,
+            collateral_token,
+            amount,
+            start_height,
+            dutch_auction,
+            borrower: api.addr_validate(&borrower)?.to_string(),
+            repay_address,
+            fee_address,
+            liquidator,
+        },
+    )
+}
+
+/// The DEX sale of a liquidation settles in its reply. The proceeds go through
+/// the fee routing of the liquidation, a failed sale leaves the collateral
+/// to the Dutch auction
+pub fn settle_dex_liquidation(
+    deps: DepsMut,
+    env: Env,
+    result: SubMsgResult,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let dex_liquidation = take_pending_dex_liquidation(deps.storage)?;
+    let collateral_token = deps.api.addr_humanize(&dex_liquidation.collateral_token)?;
+
+    if let SubMsgResult::Err(_) = result {
+        create_auction_lot(
+            deps.storage,
+            deps.api,
+            env.block.height,
+            dex_liquidation.collateral_token,
+            dex_liquidation.amount,
+            dex_liquidation.borrower,
+            dex_liquidation.repay_address,
+            dex_liquidation.fee_address,
+            dex_liquidation.liquidator,
+        )?;
+
+        return Ok(Response::new().add_attributes(vec![
+            attr("action", "settle_dex_liquidation"),
+            attr("collateral_token", collateral_token),
+            attr("auction_amount", dex_liquidation.amount),
+        ]));
+    }
+
+    let cur_balance = query_all_token_types_balance(
+        deps.as_ref(),
+        env.contract.address,
+        read_stable_asset(deps.storage, &config)?,
+    )?;
+    let (messages, repay_amount, bid_fee, liquidator_fee) = route_liquidation_proceeds(
+        deps.storage,
+        &config,
+        cur_balance - dex_liquidation.prev_balance,
+        dex_liquidation.repay_address,
+        dex_liquidation.fee_address,
+        dex_liquidation.liquidator,
+        None,
+    )?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "settle_dex_liquidation"),
+        attr("collateral_token", collateral_token),
+        attr("dex_amount", dex_liquidation.amount),
+        attr("repay_amount", repay_amount),
+        attr("bid_fee", bid_fee),
+        attr("liquidator_fee", liquidator_fee),
     ]))
 }
 
@@ -465,72 +772,306 @@ pub fn claim_liquidations(
             continue;
         }
 
-        let mut bid_pool: BidPool =
-            read_bid_pool(deps.storage, &bid.collateral_token, bid.premium_slot)?;
+        claim_amount += claim_bid(deps.storage, bid)?;
+    }
 
-        // calculate remaining bid amount
-        let (remaining_bid, residue_bid) = calculate_remaining_bid(&bid, &bid_pool)?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !claim_amount.is_zero() {
+        messages.push(collateral_transfer_msg(
+            &collateral_token,
+            info.sender,
+            claim_amount,
+        )?);
+    }
 
-        // calculate liquidated collateral
-        let (liquidated_collateral, residue_collateral) =
-            calculate_liquidated_collateral(deps.storage, &bid)?;
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "claim_liquidations"),
+        attr("collateral_token", collateral_token),
+        attr("collateral_amount", claim_amount),
+    ]))
+}
 
-        // keep residues
-        bid_pool.residue_collateral += residue_collateral;
-        bid_pool.residue_bid += residue_bid;
+/// Bidder moves a bid to another wallet. The bid keeps its snapshots, so the
+/// collateral liquidated before the transfer is claimed by the recipient
+pub fn transfer_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bid_idx: Uint128,
+    recipient: String,
+) -> StdResult<Response> {
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let bid: Bid = read_bid(deps.storage, bid_idx)?;
 
-        // get claimable amount
-        claim_amount += bid.pending_liquidated_collateral
-            + liquidated_collateral
-            + claim_col_residue(&mut bid_pool);
+    if bid.bidder != sender_raw {
+        return Err(StdError::generic_err("unauthorized"));
+    }
 
-        // store bid_pool to update residue
-        store_bid_pool(
-            deps.storage,
-            &collateral_token_raw,
-            bid.premium_slot,
-            &bid_pool,
-        )?;
+    // vault bids are only tracked through their vault
+    let recipient = deps.api.addr_validate(&recipient)?;
+    if recipient == env.contract.address {
+        return Err(StdError::generic_err(
+            "Cannot transfer a bid to the contract",
+        ));
+    }
 
-        // check if bid has been consumed, include 1 for rounding
-        if remaining_bid <= Uint256::one() {
-            remove_bid(deps.storage, bid.idx)?;
-        } else {
-            store_bid(
-                deps.storage,
-                bid.idx,
-                &Bid {
-                    amount: remaining_bid,
-                    product_snapshot: bid_pool.product_snapshot,
-                    sum_snapshot: bid_pool.sum_snapshot,
-                    scale_snapshot: bid_pool.current_scale,
-                    pending_liquidated_collateral: Uint256::zero(),
-                    ..bid
-                },
-            )?;
+    // the recipient opts in to auto-claim on its own
+    store_auto_claim(deps.storage, &bid, None)?;
+    store_bid_owner(
+        deps.storage,
+        bid_idx,
+        deps.api.addr_canonicalize(recipient.as_str())?,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "transfer_bid"),
+        attr("bid_idx", bid_idx),
+        attr("recipient", recipient),
+    ]))
+}
+
+/// Bid owner can have the liquidated collateral of a bid delivered without
+/// claiming it, either as is or sold for the stable asset
+pub fn set_auto_claim(
+    deps: DepsMut,
+    info: MessageInfo,
+    bid_idx: Uint128,
+    auto_claim: Option<AutoClaim>,
+) -> StdResult<Response> {
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let bid: Bid = read_bid(deps.storage, bid_idx)?;
+
+    if bid.bidder != sender_raw {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+    if auto_claim == Some(AutoClaim::Stable) && read_swap_config(deps.storage)?.is_none() {
+        return Err(StdError::generic_err("Swap is not configured"));
+    }
+
+    store_auto_claim(deps.storage, &bid, auto_claim)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_auto_claim"),
+        attr("bid_idx", bid_idx),
+        attr(
+            "auto_claim",
+            match auto_claim {
+                Some(AutoClaim::Collateral) => "collateral",
+                Some(AutoClaim::Stable) => "stable",
+                None => "none",
+            },
+        ),
+    ]))
+}
+
+/// Anyone can deliver the liquidated collateral of the auto-claim bids of a
+/// bidder. Collateral of the stable auto-claim bids is sold on the DEX with
+/// the best return, within the max slippage versus the oracle price
+pub fn claim_for(
+    deps: DepsMut,
+    env: Env,
+    bidder: String,
+    collateral_token: String,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let bidder = deps.api.addr_validate(&bidder)?;
+    let bidder_raw = deps.api.addr_canonicalize(bidder.as_str())?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+
+    let mut claim_amount = Uint256::zero();
+    let mut swap_amount = Uint256::zero();
+    for bid in read_bids_by_user(deps.storage, &collateral_token_raw, &bidder_raw, None, None)? {
+        if bid.wait_end.is_some() {
+            // bid not activated
+            continue;
+        }
+
+        match read_auto_claim(deps.storage, &bid)? {
+            Some(AutoClaim::Collateral) => claim_amount += claim_bid(deps.storage, bid)?,
+            Some(AutoClaim::Stable) => swap_amount += claim_bid(deps.storage, bid)?,
+            None => continue,
         }
     }
 
     let mut messages: Vec<CosmosMsg> = vec![];
     if !claim_amount.is_zero() {
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: collateral_token.clone(),
-            funds: vec![],
-            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.to_string(),
-                amount: claim_amount.try_into()?,
-            })?,
-        }));
+        messages.push(collateral_transfer_msg(
+            &collateral_token,
+            bidder.clone(),
+            claim_amount,
+        )?);
+    }
+
+    if !swap_amount.is_zero() {
+        let swap_config = read_swap_config(deps.storage)?
+            .ok_or_else(|| StdError::generic_err("Swap is not configured"))?;
+        let price = query_collateral_price(deps.as_ref(), &env, &config, collateral_token.clone())?;
+        let minimum_receive = swap_amount * price * (Decimal256::one() - swap_config.max_slippage);
+
+        messages.push(create_swap_msg(
+            deps.as_ref(),
+            &swap_config,
+            Asset {
+                amount: swap_amount.try_into()?,
+                asset_info: AssetInfo::Token {
+                    contract_addr: deps.api.addr_validate(&collateral_token)?,
+                },
+            },
+            read_stable_asset(deps.storage, &config)?,
+            minimum_receive.try_into()?,
+            bidder.to_string(),
+        )?);
     }
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "claim_liquidations"),
+        attr("action", "claim_for"),
+        attr("bidder", bidder),
         attr("collateral_token", collateral_token),
         attr("collateral_amount", claim_amount),
+        attr("swap_amount", swap_amount),
     ]))
 }
 
-fn process_bid_activation(bid: &mut Bid, bid_pool: &mut BidPool, amount: Uint256) {
+/// Settle an active bid against its pool and return the liquidated collateral
+/// it can claim. Consumed bids are removed
+pub(crate) fn claim_bid(storage: &mut dyn Storage, bid: Bid) -> StdResult<Uint256> {
+    let mut bid_pool: BidPool = read_bid_pool(storage, &bid.collateral_token, bid.premium_slot)?;
+
+    // calculate remaining bid amount
+    let (remaining_bid, residue_bid) = calculate_remaining_bid(&bid, &bid_pool)?;
+
+    // calculate liquidated collateral
+    let (liquidated_collateral, residue_collateral) =
+        calculate_liquidated_collateral(storage, &bid)?;
+
+    // keep residues
+    bid_pool.residue_collateral += residue_collateral;
+    bid_pool.residue_bid += residue_bid;
+
+    // get claimable amount
+    let claim_amount = bid.pending_liquidated_collateral
+        + liquidated_collateral
+        + claim_col_residue(&mut bid_pool);
+
+    // store bid_pool to update residue
+    store_bid_pool(storage, &bid.collateral_token, bid.premium_slot, &bid_pool)?;
+
+    // check if bid has been consumed, include 1 for rounding
+    if remaining_bid <= Uint256::one() {
+        remove_bid(storage, bid.idx)?;
+    } else {
+        store_bid(
+            storage,
+            bid.idx,
+            &Bid {
+                amount: remaining_bid,
+                product_snapshot: bid_pool.product_snapshot,
+                sum_snapshot: bid_pool.sum_snapshot,
+                scale_snapshot: bid_pool.current_scale,
+                pending_liquidated_collateral: Uint256::zero(),
+                ..bid
+            },
+        )?;
+    }
+
+    Ok(claim_amount)
+}
+
+/// Stable paid for liquidated collateral goes to `repay_address`, less the
+/// bid fee sent to `fee_address` and the liquidator fee sent to `liquidator`.
+/// Returns the messages, the repay amount, the bid fee and the liquidator fee
+pub(crate) fn route_liquidation_proceeds(
+    storage: &mut dyn Storage,
+    config: &Config,
+    amount: Uint256,
+    repay_address: String,
+    fee_address: String,
+    liquidator: String,
+    borrower: Option<String>,
+) -> StdResult<(Vec<CosmosMsg>, Uint256, Uint256, Uint256)> {
+    let bid_fee = amount * config.bid_fee;
+    let liquidator_fee = amount * config.liquidator_fee;
+    let repay_amount = amount - bid_fee - liquidator_fee;
+
+    let stable_asset = read_stable_asset(storage, config)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !repay_amount.is_zero() {
+        let repay_asset = Asset {
+            amount: repay_amount.try_into()?,
+            asset_info: stable_asset.clone(),
+        };
+        // the overseer repays the loan of a queue liquidation from the market balance,
+        // proceeds settled after the liquidation repay the loan of the borrower
+        messages.push(match borrower {
+            Some(borrower) => market_repay_msg(repay_asset, repay_address, borrower)?,
+            None => repay_asset.to_msg(Addr::unchecked(repay_address))?,
+        });
+    }
+
+    if !bid_fee.is_zero() {
+        let bid_fees_collected = read_bid_fees_collected(storage)? + bid_fee;
+        store_bid_fees_collected(storage, &bid_fees_collected)?;
+
+        messages.push(
+            Asset {
+                amount: bid_fee.try_into()?,
+                asset_info: stable_asset.clone(),
+            }
+            .to_msg(Addr::unchecked(fee_address))?,
+        );
+    }
+    if !liquidator_fee.is_zero() {
+        messages.push(
+            Asset {
+                amount: liquidator_fee.try_into()?,
+                asset_info: stable_asset,
+            }
+            .to_msg(Addr::unchecked(liquidator))?,
+        );
+    }
+
+    Ok((messages, repay_amount, bid_fee, liquidator_fee))
+}
+
+fn market_repay_msg(asset: Asset, market: String, borrower: String) -> StdResult<CosmosMsg> {
+    match asset.asset_info {
+        AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: market,
+            funds: vec![Coin {
+                denom,
+                amount: asset.amount,
+            }],
+            msg: to_json_binary(&MarketExecuteMsg::RepayStableFromAuction { borrower })?,
+        })),
+        AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                contract: market,
+                amount: asset.amount,
+                msg: to_json_binary(&MarketCw20HookMsg::RepayStableFromAuction { borrower })?,
+            })?,
+        })),
+    }
+}
+
+pub(crate) fn collateral_transfer_msg(
+    collateral_token: &str,
+    recipient: Addr,
+    amount: Uint256,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: collateral_token.to_string(),
+        funds: vec![],
+        msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: amount.try_into()?,
+        })?,
+    }))
+}
+
+pub(crate) fn process_bid_activation(bid: &mut Bid, bid_pool: &mut BidPool, amount: Uint256) {
     bid.product_snapshot = bid_pool.product_snapshot;
     bid.sum_snapshot = bid_pool.sum_snapshot;
     bid.wait_end = None;
@@ -617,6 +1158,65 @@ fn execute_pool_liquidation(
     Ok((pool_required_stable, pool_collateral_to_liquidate))
 }
 
+/// With age priority the oldest active bids of the slot are consumed first.
+/// The bids are settled directly, so the pool snapshots are left unchanged
+fn execute_pool_age_priority_liquidation(
+    storage: &mut dyn Storage,
+    bid_pool: &mut BidPool,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+    collateral_to_liquidate: Uint256,
+    price: Decimal256,
+    filled: &mut bool,
+) -> StdResult<(Uint256, Uint256)> {
+    let premium_price = price * (Decimal256::one() - bid_pool.premium_rate);
+    let mut remaining_collateral = collateral_to_liquidate;
+    let mut pool_required_stable = Uint256::zero();
+    let mut fills = 0usize;
+    while !remaining_collateral.is_zero() && fills < MAX_AGE_PRIORITY_FILLS {
+        let mut bid: Bid = match read_oldest_active_bid(storage, collateral_token, premium_slot)? {
+            Some(bid) => bid,
+            None => break,
+        };
+        fills += 1;
+
+        // a bid too small to buy a unit of collateral is left for its owner to retract
+        let bid_collateral =
+            Decimal256::from_ratio(bid.amount, 1u128) / premium_price * Uint256::one();
+        if bid_collateral.is_zero() {
+            remove_active_bid_indexer(storage, &bid);
+            continue;
+        }
+
+        let (bid_stable, bid_liquidated_collateral) = if bid_collateral > remaining_collateral {
+            (remaining_collateral * premium_price, remaining_collateral)
+        } else {
+            (bid_collateral * premium_price, bid_collateral)
+        };
+
+        bid.amount -= bid_stable;
+        bid.pending_liquidated_collateral += bid_liquidated_collateral;
+        store_bid(storage, bid.idx, &bid)?;
+
+        // a consumed bid leaves the active index, its collateral is claimable
+        // and the stable left by the rounding is retractable
+        if bid_liquidated_collateral == bid_collateral {
+            remove_active_bid_indexer(storage, &bid);
+        }
+
+        pool_required_stable += bid_stable;
+        remaining_collateral -= bid_liquidated_collateral;
+    }
+
+    bid_pool.total_bid_amount -= pool_required_stable;
+    *filled = remaining_collateral.is_zero();
+
+    Ok((
+        pool_required_stable,
+        collateral_to_liquidate - remaining_collateral,
+    ))
+}
+
 pub(crate) fn calculate_remaining_bid(
     bid: &Bid,
     bid_pool: &BidPool,
@@ -684,7 +1284,7 @@ pub(crate) fn calculate_liquidated_collateral(
     Ok((liquidated_collateral, residue_collateral))
 }
 
-fn claim_col_residue(bid_pool: &mut BidPool) -> Uint256 {
+pub(crate) fn claim_col_residue(bid_pool: &mut BidPool) -> Uint256 {
     let claimable = bid_pool.residue_collateral * Uint256::one();
     if !claimable.is_zero() {
         bid_pool.residue_collateral -= Decimal256::from_ratio(claimable, 1u128);
@@ -3,23 +3,46 @@ use cosmwasm_std::entry_point;
 #[cfg(not(feature = "library"))]
 use moneymarket::liquidation_queue::MigrateMsg;
 
-use crate::asserts::{assert_fees, assert_max_slot, assert_max_slot_premium};
-use crate::bid::{activate_bids, claim_liquidations, execute_liquidation, retract_bid, submit_bid};
+use crate::asserts::{
+    assert_dutch_auction, assert_fees, assert_max_slot, assert_max_slot_premium,
+    assert_premium_rates,
+};
+use crate::auction::{buy_auction, query_auctions, query_dutch_auction};
+use crate::bid::{
+    activate_bids, claim_for, claim_liquidations, create_bid, execute_liquidation, retract_bid,
+    set_auto_claim, settle_dex_liquidation, stable_amount_sent, submit_bid, transfer_bid,
+    DEX_LIQUIDATION_REPLY_ID,
+};
 use crate::querier::query_collateral_whitelist_info;
 use crate::query::{
-    query_bid, query_bid_pool, query_bid_pools, query_bids_by_user, query_collateral_info,
-    query_config, query_liquidation_amount,
+    query_age_priority, query_bid, query_bid_pool, query_bid_pools, query_bids_by_user,
+    query_claimable_by_user, query_collateral_info, query_config, query_dex_fallback,
+    query_fees_collected, query_liquidation_amount, query_liquidation_depth,
 };
 use crate::state::{
-    read_collateral_info, read_config, store_collateral_info, store_config, CollateralInfo, Config,
+    clear_active_bid_indexer, read_age_priority, read_bid_pool, read_collateral_info, read_config,
+    read_stable_asset, read_swap_config, read_total_bids, store_age_priority, store_bid_pool,
+    store_collateral_info, store_config, store_dex_fallback, store_dutch_auction,
+    store_epoch_scale_sum, store_stable_asset, store_swap_config, CollateralInfo, Config,
+    SwapConfig,
+};
+
+use crate::response::MsgInstantiateContractResponse;
+use crate::vault::{
+    compound_vault, create_vault_token, deposit_vault, query_vault, query_vault_shares,
+    rebid_vault, register_vault_token, withdraw_vault, withdraw_vault_tokens, VAULT_TOKEN_REPLY_ID,
 };
 
 use cosmwasm_std::{
-    from_json, to_json_binary, Binary, Decimal256, Deps, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult, Uint256,
+    from_json, to_json_binary, Addr, Binary, Decimal256, Deps, DepsMut, Env, MessageInfo, Reply,
+    Response, StdError, StdResult, Uint128, Uint256,
 };
 use cw20::Cw20ReceiveMsg;
-use moneymarket::liquidation_queue::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+use moneymarket::astroport_router::AssetInfo;
+use moneymarket::liquidation_queue::{
+    Cw20HookMsg, DutchAuction, ExecuteMsg, InstantiateMsg, QueryMsg,
+};
+use protobuf::Message;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -35,6 +58,27 @@ pub fn instantiate(
         ));
     }
 
+    let stable_asset = match msg.stable_asset {
+        Some(AssetInfo::Token { contract_addr }) => {
+            let contract_addr = deps.api.addr_validate(contract_addr.as_str())?;
+            if contract_addr.as_str() != msg.stable_denom {
+                return Err(StdError::generic_err(
+                    "stable_denom must be the address of the stable token",
+                ));
+            }
+            AssetInfo::Token { contract_addr }
+        }
+        Some(AssetInfo::NativeToken { denom }) if denom != msg.stable_denom => {
+            return Err(StdError::generic_err(
+                "stable_denom must be the denom of the stable asset",
+            ));
+        }
+        _ => AssetInfo::NativeToken {
+            denom: msg.stable_denom.clone(),
+        },
+    };
+    store_stable_asset(deps.storage, &stable_asset)?;
+
     store_config(
         deps.storage,
         &Config {
@@ -86,6 +130,7 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             bid_threshold,
             max_slot,
             premium_rate_per_slot,
+            premium_rates,
         } => whitelist_collateral(
             deps,
             info,
@@ -93,12 +138,21 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             bid_threshold,
             max_slot,
             premium_rate_per_slot,
+            premium_rates,
         ),
         ExecuteMsg::UpdateCollateralInfo {
             collateral_token,
             bid_threshold,
             max_slot,
-        } => update_collateral_info(deps, info, collateral_token, bid_threshold, max_slot),
+            premium_rates,
+        } => update_collateral_info(
+            deps,
+            info,
+            collateral_token,
+            bid_threshold,
+            max_slot,
+            premium_rates,
+        ),
 
         ExecuteMsg::RemoveCollateral { collateral_token } => {
             remove_collateral(deps, info, collateral_token)
@@ -112,10 +166,132 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             bids_idx,
         } => activate_bids(deps, env, info, collateral_token, bids_idx),
         ExecuteMsg::RetractBid { bid_idx, amount } => retract_bid(deps, env, info, bid_idx, amount),
+        ExecuteMsg::TransferBid { bid_idx, recipient } => {
+            transfer_bid(deps, env, info, bid_idx, recipient)
+        }
         ExecuteMsg::ClaimLiquidations {
             collateral_token,
             bids_idx,
         } => claim_liquidations(deps, env, info, collateral_token, bids_idx),
+        ExecuteMsg::UpdateSwapConfig {
+            astroport_addr,
+            phoenix_addr,
+            terraswap_addr,
+            max_slippage,
+        } => update_swap_config(
+            deps,
+            info,
+            astroport_addr,
+            phoenix_addr,
+            terraswap_addr,
+            max_slippage,
+        ),
+        ExecuteMsg::UpdateDutchAuction {
+            collateral_token,
+            dutch_auction,
+        } => update_dutch_auction(deps, info, collateral_token, dutch_auction),
+        ExecuteMsg::UpdateDexFallback {
+            collateral_token,
+            enabled,
+        } => update_dex_fallback(deps, info, collateral_token, enabled),
+        ExecuteMsg::UpdateAgePriority {
+            collateral_token,
+            enabled,
+            min_bid_amount,
+        } => update_age_priority(deps, info, collateral_token, enabled, min_bid_amount),
+        ExecuteMsg::BuyAuction {
+            collateral_token,
+            auction_idx,
+        } => {
+            let amount = stable_amount_sent(deps.storage, &info)?;
+            buy_auction(
+                deps,
+                env,
+                info.sender,
+                collateral_token,
+                auction_idx,
+                amount,
+            )
+        }
+        ExecuteMsg::SetAutoClaim {
+            bid_idx,
+            auto_claim,
+        } => set_auto_claim(deps, info, bid_idx, auto_claim),
+        ExecuteMsg::ClaimFor {
+            bidder,
+            collateral_token,
+        } => claim_for(deps, env, bidder, collateral_token),
+        ExecuteMsg::DepositVault {
+            collateral_token,
+            premium_slot,
+        } => {
+            let amount = stable_amount_sent(deps.storage, &info)?;
+            deposit_vault(
+                deps,
+                env,
+                info.sender,
+                collateral_token,
+                premium_slot,
+                amount,
+            )
+        }
+        ExecuteMsg::WithdrawVault {
+            collateral_token,
+            premium_slot,
+            shares,
+        } => withdraw_vault(deps, info, collateral_token, premium_slot, shares),
+        ExecuteMsg::CompoundVault {
+            collateral_token,
+            premium_slot,
+        } => compound_vault(deps, env, collateral_token, premium_slot),
+        ExecuteMsg::RebidVault {
+            collateral_token,
+            premium_slot,
+            prev_balance,
+        } => rebid_vault(
+            deps,
+            env,
+            info,
+            collateral_token,
+            premium_slot,
+            prev_balance,
+        ),
+        ExecuteMsg::CreateVaultToken {
+            collateral_token,
+            premium_slot,
+            token_code_id,
+            name,
+            symbol,
+        } => create_vault_token(
+            deps,
+            env,
+            info,
+            collateral_token,
+            premium_slot,
+            token_code_id,
+            name,
+            symbol,
+        ),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response> {
+    match msg.id {
+        VAULT_TOKEN_REPLY_ID => {
+            // get new token's contract address
+            let res: MsgInstantiateContractResponse = Message::parse_from_bytes(
+                msg.result.unwrap().data.unwrap().as_slice(),
+            )
+            .map_err(|_| {
+                StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
+            })?;
+            let token_addr = Addr::unchecked(res.get_contract_address());
+
+            register_vault_token(deps, token_addr)
+        }
+        DEX_LIQUIDATION_REPLY_ID => settle_dex_liquidation(deps, env, msg.result),
+        _ => Err(StdError::generic_err("Invalid reply id")),
     }
 }
 
@@ -131,6 +307,7 @@ pub fn receive_cw20(
             liquidator,
             repay_address,
             fee_address,
+            borrower,
         } => {
             let collateral_token = contract_addr.to_string();
             let repay_address = repay_address.unwrap_or_else(|| cw20_msg.sender.clone());
@@ -143,11 +320,85 @@ pub fn receive_cw20(
                 liquidator,
                 repay_address,
                 fee_address,
+                borrower,
                 collateral_token,
                 cw20_msg.amount.into(),
             )
         }
+        Cw20HookMsg::SubmitBid {
+            collateral_token,
+            premium_slot,
+        } => {
+            assert_stable_token(deps.as_ref(), contract_addr)?;
+
+            let bidder = deps.api.addr_validate(&cw20_msg.sender)?;
+            create_bid(
+                deps,
+                env,
+                bidder,
+                collateral_token,
+                premium_slot,
+                cw20_msg.amount.into(),
+            )
+        }
+        Cw20HookMsg::DepositVault {
+            collateral_token,
+            premium_slot,
+        } => {
+            assert_stable_token(deps.as_ref(), contract_addr)?;
+
+            let depositor = deps.api.addr_validate(&cw20_msg.sender)?;
+            deposit_vault(
+                deps,
+                env,
+                depositor,
+                collateral_token,
+                premium_slot,
+                cw20_msg.amount.into(),
+            )
+        }
+        Cw20HookMsg::WithdrawVault {
+            collateral_token,
+            premium_slot,
+        } => {
+            let redeemer = deps.api.addr_validate(&cw20_msg.sender)?;
+            withdraw_vault_tokens(
+                deps,
+                contract_addr,
+                redeemer,
+                collateral_token,
+                premium_slot,
+                cw20_msg.amount.into(),
+            )
+        }
+        Cw20HookMsg::BuyAuction {
+            collateral_token,
+            auction_idx,
+        } => {
+            assert_stable_token(deps.as_ref(), contract_addr)?;
+
+            let buyer = deps.api.addr_validate(&cw20_msg.sender)?;
+            buy_auction(
+                deps,
+                env,
+                buyer,
+                collateral_token,
+                auction_idx,
+                cw20_msg.amount.into(),
+            )
+        }
+    }
+}
+
+/// Only the stable token contract can execute the bidding hooks
+fn assert_stable_token(deps: Deps, contract_addr: Addr) -> StdResult<()> {
+    let config: Config = read_config(deps.storage)?;
+    let stable_token = AssetInfo::Token { contract_addr };
+    if read_stable_asset(deps.storage, &config)? != stable_token {
+        return Err(StdError::generic_err("unauthorized"));
     }
+
+    Ok(())
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -228,6 +479,169 @@ pub fn update_config(
     Ok(res)
 }
 
+pub fn update_swap_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    astroport_addr: String,
+    phoenix_addr: String,
+    terraswap_addr: String,
+    max_slippage: Decimal256,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if max_slippage >= Decimal256::one() {
+        return Err(StdError::generic_err("Max slippage should be below 1"));
+    }
+
+    store_swap_config(
+        deps.storage,
+        &SwapConfig {
+            astroport_addr: deps.api.addr_validate(&astroport_addr)?,
+            phoenix_addr: deps.api.addr_validate(&phoenix_addr)?,
+            terraswap_addr: deps.api.addr_validate(&terraswap_addr)?,
+            max_slippage,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_swap_config"),
+        ("max_slippage", &max_slippage.to_string()),
+    ]))
+}
+
+pub fn update_dutch_auction(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: String,
+    dutch_auction: Option<DutchAuction>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    // check the collateral is whitelisted
+    read_collateral_info(deps.storage, &collateral_token_raw)?;
+
+    if let Some(dutch_auction) = &dutch_auction {
+        assert_dutch_auction(dutch_auction)?;
+    }
+
+    store_dutch_auction(deps.storage, &collateral_token_raw, dutch_auction)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_dutch_auction"),
+        ("collateral_token", &collateral_token),
+    ]))
+}
+
+pub fn update_dex_fallback(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: String,
+    enabled: bool,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    // check the collateral is whitelisted
+    read_collateral_info(deps.storage, &collateral_token_raw)?;
+
+    if enabled && read_swap_config(deps.storage)?.is_none() {
+        return Err(StdError::generic_err("Swap is not configured"));
+    }
+
+    store_dex_fallback(deps.storage, &collateral_token_raw, enabled)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_dex_fallback"),
+        ("collateral_token", &collateral_token),
+        ("enabled", &enabled.to_string()),
+    ]))
+}
+
+pub fn update_age_priority(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: String,
+    enabled: bool,
+    min_bid_amount: Option<Uint256>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_info: CollateralInfo =
+        read_collateral_info(deps.storage, &collateral_token_raw)?;
+
+    // the minimum bid amount bounds the bids a liquidation settles one by one
+    let min_bid_amount = match min_bid_amount {
+        Some(min_bid_amount) if enabled && !min_bid_amount.is_zero() => Some(min_bid_amount),
+        _ if enabled => {
+            return Err(StdError::generic_err(
+                "Age priority requires a non-zero minimum bid amount",
+            ))
+        }
+        _ => None,
+    };
+
+    // the bids activated pro-rata are not settled by the age priority fill, so
+    // it starts from empty pools with fresh snapshots
+    if enabled && read_age_priority(deps.storage, &collateral_token_raw)?.is_none() {
+        if !read_total_bids(deps.storage, &collateral_token_raw)
+            .unwrap_or_default()
+            .is_zero()
+        {
+            return Err(StdError::generic_err(
+                "Age priority can only be enabled without active bids",
+            ));
+        }
+
+        for slot in 0..collateral_info.max_slot + 1 {
+            // the bids activated before are settled by the reset, only the
+            // bids activated from now on are filled by age
+            clear_active_bid_indexer(deps.storage, &collateral_token_raw, slot)?;
+            if let Ok(mut bid_pool) = read_bid_pool(deps.storage, &collateral_token_raw, slot) {
+                store_epoch_scale_sum(
+                    deps.storage,
+                    &collateral_token_raw,
+                    slot,
+                    bid_pool.current_epoch,
+                    bid_pool.current_scale,
+                    bid_pool.sum_snapshot,
+                )?;
+
+                bid_pool.sum_snapshot = Decimal256::zero();
+                bid_pool.product_snapshot = Decimal256::one();
+                bid_pool.current_scale = Uint128::zero();
+                bid_pool.current_epoch += Uint128::from(1u128);
+                store_bid_pool(deps.storage, &collateral_token_raw, slot, &bid_pool)?;
+            }
+        }
+    }
+
+    store_age_priority(deps.storage, &collateral_token_raw, min_bid_amount)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_age_priority"),
+        ("collateral_token", &collateral_token),
+        ("enabled", &enabled.to_string()),
+        (
+            "min_bid_amount",
+            &min_bid_amount.unwrap_or_default().to_string(),
+        ),
+    ]))
+}
+
 pub fn whitelist_collateral(
     deps: DepsMut,
     info: MessageInfo,
@@ -235,6 +649,7 @@ pub fn whitelist_collateral(
     bid_threshold: Uint256,
     max_slot: u8,
     premium_rate_per_slot: Decimal256,
+    premium_rates: Option<Vec<Decimal256>>,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
     let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
@@ -256,7 +671,10 @@ pub fn whitelist_collateral(
 
     // assert max slot does not exceed cap and max premium rate does not exceed 1
     assert_max_slot(max_slot)?;
-    assert_max_slot_premium(max_slot, premium_rate_per_slot)?;
+    match &premium_rates {
+        Some(premium_rates) => assert_premium_rates(max_slot, premium_rates)?,
+        None => assert_max_slot_premium(max_slot, premium_rate_per_slot)?,
+    }
 
     // save collateral info
     store_collateral_info(
@@ -267,6 +685,7 @@ pub fn whitelist_collateral(
             max_slot,
             bid_threshold,
             premium_rate_per_slot,
+            premium_rates,
         },
     )?;
 
@@ -279,6 +698,7 @@ pub fn update_collateral_info(
     collateral_token: String,
     bid_threshold: Option<Uint256>,
     max_slot: Option<u8>,
+    premium_rates: Option<Vec<Decimal256>>,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
     let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
@@ -295,15 +715,40 @@ pub fn update_collateral_info(
     }
 
     if let Some(max_slot) = max_slot {
-        // assert max slot does not exceed cap and max premium rate does not exceed 1
+        // assert max slot does not exceed cap
         assert_max_slot(max_slot)?;
-        assert_max_slot_premium(max_slot, collateral_info.premium_rate_per_slot)?;
         collateral_info.max_slot = max_slot;
     }
 
+    if let Some(premium_rates) = premium_rates {
+        collateral_info.premium_rates = if premium_rates.is_empty() {
+            None
+        } else {
+            Some(premium_rates)
+        };
+    }
+
+    // assert max premium rate does not exceed 1
+    match &collateral_info.premium_rates {
+        Some(premium_rates) => assert_premium_rates(collateral_info.max_slot, premium_rates)?,
+        None => assert_max_slot_premium(
+            collateral_info.max_slot,
+            collateral_info.premium_rate_per_slot,
+        )?,
+    }
+
     // save collateral info
     store_collateral_info(deps.storage, &collateral_token_raw, &collateral_info)?;
 
+    // existing bid pools take the premium rate of their slot, the bid
+    // snapshots don't depend on it
+    for slot in 0..collateral_info.max_slot + 1 {
+        if let Ok(mut bid_pool) = read_bid_pool(deps.storage, &collateral_token_raw, slot) {
+            bid_pool.premium_rate = collateral_info.premium_rate(slot);
+            store_bid_pool(deps.storage, &collateral_token_raw, slot, &bid_pool)?;
+        }
+    }
+
     Ok(Response::new().add_attribute("action", "update_collateral_info"))
 }
 
@@ -325,7 +770,7 @@ pub fn remove_collateral(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
         QueryMsg::LiquidationAmount {
@@ -370,6 +815,53 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after,
             limit,
         )?),
+        QueryMsg::FeesCollected {} => to_json_binary(&query_fees_collected(deps)?),
+        QueryMsg::Vault {
+            collateral_token,
+            premium_slot,
+        } => to_json_binary(&query_vault(deps, collateral_token, premium_slot)?),
+        QueryMsg::VaultShares {
+            collateral_token,
+            premium_slot,
+            depositor,
+        } => to_json_binary(&query_vault_shares(
+            deps,
+            collateral_token,
+            premium_slot,
+            depositor,
+        )?),
+        QueryMsg::DutchAuction { collateral_token } => {
+            to_json_binary(&query_dutch_auction(deps, collateral_token)?)
+        }
+        QueryMsg::AgePriority { collateral_token } => {
+            to_json_binary(&query_age_priority(deps, collateral_token)?)
+        }
+        QueryMsg::DexFallback { collateral_token } => {
+            to_json_binary(&query_dex_fallback(deps, collateral_token)?)
+        }
+        QueryMsg::Auctions {
+            collateral_token,
+            start_after,
+            limit,
+        } => to_json_binary(&query_auctions(
+            deps,
+            env,
+            collateral_token,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ClaimableByUser { bidder } => {
+            to_json_binary(&query_claimable_by_user(deps, bidder)?)
+        }
+        QueryMsg::LiquidationDepth {
+            collateral_token,
+            collateral_amount,
+        } => to_json_binary(&query_liquidation_depth(
+            deps,
+            env,
+            collateral_token,
+            collateral_amount,
+        )?),
     }
 }
 
@@ -1,9 +1,13 @@
 mod asserts;
+mod auction;
 mod bid;
 pub mod contract;
 mod querier;
 mod query;
+pub mod response;
 mod state;
+mod swap;
+mod vault;
 
 #[cfg(test)]
 mod testing;
@@ -1,7 +1,12 @@
-use cosmwasm_std::{to_json_binary, QuerierWrapper, QueryRequest, StdResult, WasmQuery};
+use crate::state::Config;
+use cosmwasm_std::{
+    to_json_binary, Decimal256, Deps, Env, QuerierWrapper, QueryRequest, StdResult, WasmQuery,
+};
+use moneymarket::oracle::PriceResponse;
 use moneymarket::overseer::{
     QueryMsg as OverseerQueryMsg, WhitelistResponse, WhitelistResponseElem,
 };
+use moneymarket::querier::{query_price, TimeConstraints};
 
 pub fn query_collateral_whitelist_info(
     querier: &QuerierWrapper,
@@ -20,3 +25,25 @@ pub fn query_collateral_whitelist_info(
 
     Ok(whitelist_res.elems[0].clone())
 }
+
+/// Oracle price of the collateral in the stable denom, within the price timeframe
+pub fn query_collateral_price(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    collateral_token: String,
+) -> StdResult<Decimal256> {
+    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
+    let price: PriceResponse = query_price(
+        deps,
+        oracle_contract,
+        collateral_token,
+        config.stable_denom.clone(),
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        }),
+    )?;
+
+    Ok(price.rate)
+}
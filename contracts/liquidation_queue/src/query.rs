@@ -1,20 +1,26 @@
-use crate::bid::{calculate_liquidated_collateral, calculate_remaining_bid};
-use crate::querier::query_collateral_whitelist_info;
+use crate::bid::{calculate_liquidated_collateral, calculate_remaining_bid, claim_col_residue};
+use crate::querier::{query_collateral_price, query_collateral_whitelist_info};
 use crate::state::{
-    read_bid, read_bid_pool, read_bid_pools, read_bids_by_user, read_collateral_info, read_config,
-    read_total_bids, Bid, BidPool, CollateralInfo, Config,
+    read_age_priority, read_auto_claim, read_bid, read_bid_fees_collected, read_bid_pool,
+    read_bid_pools, read_bids_by_user, read_collateral_info, read_collateral_infos, read_config,
+    read_dex_fallback, read_stable_asset, read_total_bids, Bid, BidPool, CollateralInfo, Config,
 };
 
-use cosmwasm_std::{CanonicalAddr, Decimal256, Deps, StdResult, Uint128, Uint256};
+use cosmwasm_std::{CanonicalAddr, Decimal256, Deps, Env, StdResult, Uint128, Uint256};
 use moneymarket::liquidation_queue::{
-    BidPoolResponse, BidPoolsResponse, BidResponse, BidsResponse, CollateralInfoResponse,
-    ConfigResponse, LiquidationAmountResponse,
+    AgePriorityResponse, BidPoolResponse, BidPoolsResponse, BidResponse, BidsResponse,
+    ClaimableByUserResponse, ClaimableCollateralResponse, CollateralInfoResponse, ConfigResponse,
+    DexFallbackResponse, FeesCollectedResponse, LiquidationAmountResponse,
+    LiquidationDepthResponse, SlotDepthResponse,
 };
 use moneymarket::tokens::TokensHuman;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = read_config(deps.storage)?;
     let resp = ConfigResponse {
+        stable_asset: read_stable_asset(deps.storage, &config)?,
         owner: deps.api.addr_humanize(&config.owner)?.to_string(),
         oracle_contract: deps.api.addr_humanize(&config.oracle_contract)?.to_string(),
         stable_denom: config.stable_denom,
@@ -30,6 +36,99 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     Ok(resp)
 }
 
+pub fn query_fees_collected(deps: Deps) -> StdResult<FeesCollectedResponse> {
+    Ok(FeesCollectedResponse {
+        bid_fee: read_bid_fees_collected(deps.storage)?,
+    })
+}
+
+pub fn query_dex_fallback(deps: Deps, collateral_token: String) -> StdResult<DexFallbackResponse> {
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+
+    Ok(DexFallbackResponse {
+        enabled: read_dex_fallback(deps.storage, &collateral_token_raw)?,
+    })
+}
+
+pub fn query_age_priority(deps: Deps, collateral_token: String) -> StdResult<AgePriorityResponse> {
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+
+    let min_bid_amount = read_age_priority(deps.storage, &collateral_token_raw)?;
+
+    Ok(AgePriorityResponse {
+        enabled: min_bid_amount.is_some(),
+        min_bid_amount,
+    })
+}
+
+/// Walks the bid pools like a liquidation does, without consuming the bids.
+/// Age priority pools round per bid, so their fills can differ by a few units
+pub fn query_liquidation_depth(
+    deps: Deps,
+    env: Env,
+    collateral_token: String,
+    collateral_amount: Uint256,
+) -> StdResult<LiquidationDepthResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_info: CollateralInfo =
+        read_collateral_info(deps.storage, &collateral_token_raw)?;
+    let price = query_collateral_price(deps, &env, &config, collateral_token.clone())?;
+
+    let mut remaining_collateral = collateral_amount;
+    let mut total_bid_amount = Uint256::zero();
+    let mut slots: Vec<SlotDepthResponse> = vec![];
+    for slot in 0..collateral_info.max_slot + 1 {
+        if remaining_collateral.is_zero() {
+            break;
+        }
+        let bid_pool: BidPool = match read_bid_pool(deps.storage, &collateral_token_raw, slot) {
+            Ok(bid_pool) => bid_pool,
+            Err(_) => continue,
+        };
+        if bid_pool.total_bid_amount.is_zero() {
+            continue;
+        }
+
+        let premium_price = price * (Decimal256::one() - bid_pool.premium_rate);
+        let mut pool_collateral = remaining_collateral;
+        let mut pool_bid_amount = pool_collateral * premium_price;
+        if pool_bid_amount > bid_pool.total_bid_amount {
+            pool_bid_amount = bid_pool.total_bid_amount;
+            pool_collateral =
+                Decimal256::from_ratio(pool_bid_amount, 1u128) / premium_price * Uint256::one();
+        }
+
+        remaining_collateral -= pool_collateral;
+        total_bid_amount += pool_bid_amount;
+        slots.push(SlotDepthResponse {
+            premium_slot: slot,
+            premium_rate: bid_pool.premium_rate,
+            bid_amount: pool_bid_amount,
+            collateral_amount: pool_collateral,
+        });
+    }
+
+    let repay_amount = total_bid_amount
+        - total_bid_amount * config.bid_fee
+        - total_bid_amount * config.liquidator_fee;
+    let filled_value = (collateral_amount - remaining_collateral) * price;
+    let effective_discount = if filled_value.is_zero() {
+        Decimal256::zero()
+    } else {
+        Decimal256::one() - Decimal256::from_ratio(repay_amount, filled_value)
+    };
+
+    Ok(LiquidationDepthResponse {
+        collateral_token,
+        collateral_amount,
+        repay_amount,
+        effective_discount,
+        slots,
+        unfilled_amount: remaining_collateral,
+    })
+}
+
 /// The amount of collateral to be liquidated depends on the status of the bid pools
 /// for each collateral. To find out how much collateral should be liquidated
 /// we find the intersaction between f(x) and g(x); where x = liquidated collateral,
@@ -216,6 +315,79 @@ pub fn query_bid(deps: Deps, bid_idx: Uint128) -> StdResult<BidResponse> {
         wait_end: bid.wait_end,
         epoch_snapshot: bid.epoch_snapshot,
         scale_snapshot: bid.scale_snapshot,
+        auto_claim: read_auto_claim(deps.storage, &bid)?,
+    })
+}
+
+/// Replays the claim of every bid of the bidder, on copies of the bid pools
+/// so that the collateral residues go to the bids like on ClaimLiquidations
+pub fn query_claimable_by_user(deps: Deps, bidder: String) -> StdResult<ClaimableByUserResponse> {
+    let bidder_raw = deps.api.addr_canonicalize(&bidder)?;
+
+    let mut collaterals: Vec<ClaimableCollateralResponse> = vec![];
+    for collateral_info in read_collateral_infos(deps.storage)? {
+        let collateral_token_raw = collateral_info.collateral_token;
+        let mut bid_pools: HashMap<u8, BidPool> = HashMap::new();
+        let mut claimable = ClaimableCollateralResponse {
+            collateral_token: deps.api.addr_humanize(&collateral_token_raw)?.to_string(),
+            pending_collateral: Uint256::zero(),
+            claimable_collateral: Uint256::zero(),
+            bid_amount: Uint256::zero(),
+            waiting_bid_amount: Uint256::zero(),
+        };
+
+        let mut has_bids = false;
+        let mut start_after: Option<Uint128> = None;
+        loop {
+            let bids: Vec<Bid> = read_bids_by_user(
+                deps.storage,
+                &collateral_token_raw,
+                &bidder_raw,
+                start_after,
+                None,
+            )?;
+            let last_bid = match bids.last() {
+                Some(bid) => bid.idx,
+                None => break,
+            };
+            has_bids = true;
+
+            for bid in bids {
+                if bid.wait_end.is_some() {
+                    claimable.waiting_bid_amount += bid.amount;
+                    claimable.pending_collateral += bid.pending_liquidated_collateral;
+                    continue;
+                }
+
+                let bid_pool = match bid_pools.entry(bid.premium_slot) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => entry.insert(read_bid_pool(
+                        deps.storage,
+                        &collateral_token_raw,
+                        bid.premium_slot,
+                    )?),
+                };
+                let (remaining_bid, _) = calculate_remaining_bid(&bid, bid_pool)?;
+                let (liquidated_collateral, residue_collateral) =
+                    calculate_liquidated_collateral(deps.storage, &bid)?;
+                bid_pool.residue_collateral += residue_collateral;
+
+                let pending_collateral = bid.pending_liquidated_collateral + liquidated_collateral;
+                claimable.bid_amount += remaining_bid;
+                claimable.pending_collateral += pending_collateral;
+                claimable.claimable_collateral += pending_collateral + claim_col_residue(bid_pool);
+            }
+            start_after = Some(last_bid);
+        }
+
+        if has_bids {
+            collaterals.push(claimable);
+        }
+    }
+
+    Ok(ClaimableByUserResponse {
+        bidder,
+        collaterals,
     })
 }
 
@@ -266,6 +438,7 @@ pub fn query_bids_by_user(
             wait_end: bid.wait_end,
             epoch_snapshot: bid.epoch_snapshot,
             scale_snapshot: bid.scale_snapshot,
+            auto_claim: read_auto_claim(deps.storage, bid)?,
         };
         Ok(res)
     })
@@ -329,5 +502,6 @@ pub fn query_collateral_info(
         bid_threshold: collateral_info.bid_threshold,
         max_slot: collateral_info.max_slot,
         premium_rate_per_slot: collateral_info.premium_rate_per_slot,
+        premium_rates: collateral_info.premium_rates,
     })
 }
@@ -1,13 +1,21 @@
 use cosmwasm_std::{
-    CanonicalAddr, Decimal256, Order, StdError, StdResult, Storage, Uint128, Uint256,
+    Addr, CanonicalAddr, Decimal256, Order, StdError, StdResult, Storage, Uint128, Uint256,
 };
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use moneymarket::astroport_router::AssetInfo;
+use moneymarket::liquidation_queue::{AutoClaim, DutchAuction};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_BID_IDX: &[u8] = b"bid_idx";
+static KEY_STABLE_ASSET: &[u8] = b"stable_asset";
+static KEY_BID_FEES_COLLECTED: &[u8] = b"bid_fees_collected";
+static KEY_SWAP_CONFIG: &[u8] = b"swap_config";
+static KEY_PENDING_VAULT_TOKEN: &[u8] = b"pending_vault_token";
+static KEY_AUCTION_IDX: &[u8] = b"auction_idx";
+static KEY_PENDING_DEX_LIQUIDATION: &[u8] = b"pending_dex_liquidation";
 
 static PREFIX_BID: &[u8] = b"bid";
 static PREFIX_BID_BY_USER: &[u8] = b"bid_by_user";
@@ -15,6 +23,15 @@ static PREFIX_BID_POOL_BY_COLLATERAL: &[u8] = b"bid_pool_by_col";
 static PREFIX_TOTAL_BIDS_BY_COLLATERAL: &[u8] = b"total_bids_by_col";
 static PREFIX_COLLATERAL_INFO: &[u8] = b"col_info";
 static PREFIX_EPOCH_SCALE_SUM: &[u8] = b"epoch_scale_sum";
+static PREFIX_AUTO_CLAIM_BY_COLLATERAL: &[u8] = b"auto_claim_by_col";
+static PREFIX_COLLATERAL_AUTO_CLAIM_BY_SLOT: &[u8] = b"col_auto_claim_by_slot";
+static PREFIX_VAULT: &[u8] = b"vault";
+static PREFIX_VAULT_SHARES: &[u8] = b"vault_shares";
+static PREFIX_DUTCH_AUCTION: &[u8] = b"dutch_auction";
+static PREFIX_DEX_FALLBACK: &[u8] = b"dex_fallback";
+static PREFIX_AGE_PRIORITY: &[u8] = b"age_priority";
+static PREFIX_ACTIVE_BID_BY_SLOT: &[u8] = b"active_bid_by_slot";
+static PREFIX_AUCTION_BY_COLLATERAL: &[u8] = b"auction_by_col";
 
 const MAX_LIMIT: u8 = 31;
 const DEFAULT_LIMIT: u8 = 10;
@@ -41,6 +58,53 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
 
+#[allow(deprecated)]
+pub fn store_stable_asset(storage: &mut dyn Storage, stable_asset: &AssetInfo) -> StdResult<()> {
+    singleton(storage, KEY_STABLE_ASSET).save(stable_asset)
+}
+
+/// Queues without a stored stable asset take bids in the native stable denom
+#[allow(deprecated)]
+pub fn read_stable_asset(storage: &dyn Storage, config: &Config) -> StdResult<AssetInfo> {
+    Ok(singleton_read(storage, KEY_STABLE_ASSET)
+        .may_load()?
+        .unwrap_or_else(|| AssetInfo::NativeToken {
+            denom: config.stable_denom.clone(),
+        }))
+}
+
+#[allow(deprecated)]
+pub fn store_bid_fees_collected(storage: &mut dyn Storage, amount: &Uint256) -> StdResult<()> {
+    singleton(storage, KEY_BID_FEES_COLLECTED).save(amount)
+}
+
+/// Bid fees collected since the ledger was introduced
+#[allow(deprecated)]
+pub fn read_bid_fees_collected(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(singleton_read(storage, KEY_BID_FEES_COLLECTED)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapConfig {
+    pub astroport_addr: Addr,
+    pub phoenix_addr: Addr,
+    pub terraswap_addr: Addr,
+    pub max_slippage: Decimal256,
+}
+
+#[allow(deprecated)]
+pub fn store_swap_config(storage: &mut dyn Storage, swap_config: &SwapConfig) -> StdResult<()> {
+    singleton(storage, KEY_SWAP_CONFIG).save(swap_config)
+}
+
+/// Auto-claimed collateral can only be sold once the owner sets the routers
+#[allow(deprecated)]
+pub fn read_swap_config(storage: &dyn Storage) -> StdResult<Option<SwapConfig>> {
+    singleton_read(storage, KEY_SWAP_CONFIG).may_load()
+}
+
 pub fn pop_bid_idx(storage: &mut dyn Storage) -> StdResult<Uint128> {
     let mut idx_store = singleton(storage, KEY_BID_IDX);
     let last_idx: Uint128 = idx_store.load().unwrap_or_else(|_| Uint128::from(1u128));
@@ -114,6 +178,18 @@ pub struct CollateralInfo {
     pub bid_threshold: Uint256,
     pub max_slot: u8,
     pub premium_rate_per_slot: Decimal256,
+    /// Explicit premium rate of each slot, linear premium when unset
+    #[serde(default)]
+    pub premium_rates: Option<Vec<Decimal256>>,
+}
+
+impl CollateralInfo {
+    pub fn premium_rate(&self, premium_slot: u8) -> Decimal256 {
+        match &self.premium_rates {
+            Some(premium_rates) => premium_rates[premium_slot as usize],
+            None => self.premium_rate_per_slot * Decimal256::from_ratio(premium_slot, 1u128),
+        }
+    }
 }
 
 pub fn store_collateral_info(
@@ -139,6 +215,20 @@ pub fn read_collateral_info(
         .map_err(|_| StdError::generic_err("Collateral is not whitelisted"))
 }
 
+#[allow(deprecated)]
+pub fn read_collateral_infos(storage: &dyn Storage) -> StdResult<Vec<CollateralInfo>> {
+    let collateral_info_bucket: ReadonlyBucket<CollateralInfo> =
+        ReadonlyBucket::new(storage, PREFIX_COLLATERAL_INFO);
+
+    collateral_info_bucket
+        .range(None, None, Order::Ascending)
+        .map(|elem| {
+            let (_, collateral_info) = elem?;
+            Ok(collateral_info)
+        })
+        .collect()
+}
+
 pub fn remove_collateral_info(storage: &mut dyn Storage, collateral_token: &CanonicalAddr) {
     let mut collateral_info_bucket: Bucket<CollateralInfo> =
         Bucket::new(storage, PREFIX_COLLATERAL_INFO);
@@ -184,6 +274,7 @@ pub fn read_bid_pool(
         .map_err(|_| StdError::generic_err("Bid pool not found"))
 }
 
+#[allow(deprecated)]
 pub fn read_or_create_bid_pool(
     storage: &mut dyn Storage,
     collateral_info: &CollateralInfo,
@@ -204,8 +295,7 @@ pub fn read_or_create_bid_pool(
                     product_snapshot: Decimal256::one(),
                     sum_snapshot: Decimal256::zero(),
                     total_bid_amount: Uint256::zero(),
-                    premium_rate: collateral_info.premium_rate_per_slot
-                        * Decimal256::from_ratio(premium_slot, 1u128),
+                    premium_rate: collateral_info.premium_rate(premium_slot),
                     current_epoch: Uint128::zero(),
                     current_scale: Uint128::zero(),
                     residue_collateral: Decimal256::zero(),
@@ -264,6 +354,7 @@ pub struct Bid {
     pub scale_snapshot: Uint128,
 }
 
+#[allow(deprecated)]
 pub fn store_bid(storage: &mut dyn Storage, bid_idx: Uint128, bid: &Bid) -> StdResult<()> {
     let mut bid_bucket: Bucket<Bid> = Bucket::new(storage, PREFIX_BID);
     bid_bucket.save(&bid_idx.u128().to_be_bytes(), bid)?;
@@ -278,15 +369,59 @@ pub fn store_bid(storage: &mut dyn Storage, bid_idx: Uint128, bid: &Bid) -> StdR
     );
     bid_indexer_by_user.save(&bid_idx.u128().to_be_bytes(), &true)?;
 
+    // active bids with stable left are indexed by age for the age priority fill
+    if bid.wait_end.is_none() && !bid.amount.is_zero() {
+        let mut active_bid_indexer: Bucket<bool> = Bucket::multilevel(
+            storage,
+            &[
+                PREFIX_ACTIVE_BID_BY_SLOT,
+                bid.collateral_token.as_slice(),
+                &bid.premium_slot.to_be_bytes(),
+            ],
+        );
+        active_bid_indexer.save(&bid_idx.u128().to_be_bytes(), &true)?;
+    } else {
+        remove_active_bid_indexer(storage, bid);
+    }
+
+    // waiting bids join the collateral auto-claims of their slot once activated
+    if bid.wait_end.is_none() && read_auto_claim(storage, bid)? == Some(AutoClaim::Collateral) {
+        store_collateral_auto_claim_indexer(storage, bid, true)?;
+    }
+
     Ok(())
 }
 
+#[allow(deprecated)]
 pub fn remove_bid(storage: &mut dyn Storage, bid_idx: Uint128) -> StdResult<()> {
     let bid: Bid = read_bid(storage, bid_idx)?;
     let mut bid_bucket: Bucket<Bid> = Bucket::new(storage, PREFIX_BID);
     bid_bucket.remove(&bid_idx.u128().to_be_bytes());
 
-    // remove indexer
+    remove_bid_indexer(storage, &bid);
+    remove_active_bid_indexer(storage, &bid);
+
+    store_auto_claim(storage, &bid, None)
+}
+
+/// Move a bid to a new bidder, the snapshots and the pending collateral stay
+/// with the bid
+pub fn store_bid_owner(
+    storage: &mut dyn Storage,
+    bid_idx: Uint128,
+    bidder: CanonicalAddr,
+) -> StdResult<Bid> {
+    let mut bid: Bid = read_bid(storage, bid_idx)?;
+    remove_bid_indexer(storage, &bid);
+
+    bid.bidder = bidder;
+    store_bid(storage, bid_idx, &bid)?;
+
+    Ok(bid)
+}
+
+#[allow(deprecated)]
+fn remove_bid_indexer(storage: &mut dyn Storage, bid: &Bid) {
     let mut bid_indexer_by_user: Bucket<bool> = Bucket::multilevel(
         storage,
         &[
@@ -295,9 +430,98 @@ pub fn remove_bid(storage: &mut dyn Storage, bid_idx: Uint128) -> StdResult<()>
             bid.bidder.as_slice(),
         ],
     );
-    bid_indexer_by_user.remove(&bid_idx.u128().to_be_bytes());
+    bid_indexer_by_user.remove(&bid.idx.u128().to_be_bytes());
+}
 
-    Ok(())
+/// Set the auto-claim mode of a bid, indexed by collateral for the liquidations
+#[allow(deprecated)]
+pub fn store_auto_claim(
+    storage: &mut dyn Storage,
+    bid: &Bid,
+    auto_claim: Option<AutoClaim>,
+) -> StdResult<()> {
+    let mut auto_claim_bucket: Bucket<AutoClaim> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_AUTO_CLAIM_BY_COLLATERAL,
+            bid.collateral_token.as_slice(),
+        ],
+    );
+    match &auto_claim {
+        Some(auto_claim) => auto_claim_bucket.save(&bid.idx.u128().to_be_bytes(), auto_claim)?,
+        None => auto_claim_bucket.remove(&bid.idx.u128().to_be_bytes()),
+    }
+
+    // the collateral auto-claims of active bids are delivered by the liquidations
+    // of their slot, waiting bids are indexed on activation
+    store_collateral_auto_claim_indexer(
+        storage,
+        bid,
+        auto_claim == Some(AutoClaim::Collateral) && bid.wait_end.is_none(),
+    )
+}
+
+#[allow(deprecated)]
+fn store_collateral_auto_claim_indexer(
+    storage: &mut dyn Storage,
+    bid: &Bid,
+    indexed: bool,
+) -> StdResult<()> {
+    let mut slot_indexer: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_COLLATERAL_AUTO_CLAIM_BY_SLOT,
+            bid.collateral_token.as_slice(),
+            &[bid.premium_slot],
+        ],
+    );
+    if indexed {
+        slot_indexer.save(&bid.idx.u128().to_be_bytes(), &true)
+    } else {
+        slot_indexer.remove(&bid.idx.u128().to_be_bytes());
+        Ok(())
+    }
+}
+
+#[allow(deprecated)]
+pub fn read_auto_claim(storage: &dyn Storage, bid: &Bid) -> StdResult<Option<AutoClaim>> {
+    let auto_claim_bucket: ReadonlyBucket<AutoClaim> = ReadonlyBucket::multilevel(
+        storage,
+        &[
+            PREFIX_AUTO_CLAIM_BY_COLLATERAL,
+            bid.collateral_token.as_slice(),
+        ],
+    );
+    auto_claim_bucket.may_load(&bid.idx.u128().to_be_bytes())
+}
+
+/// Active collateral auto-claim bids of a premium slot, in bid_idx order
+#[allow(deprecated)]
+pub fn read_collateral_auto_claim_bids(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+    start_after: Option<Uint128>,
+    limit: usize,
+) -> StdResult<Vec<Bid>> {
+    let slot_indexer: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        storage,
+        &[
+            PREFIX_COLLATERAL_AUTO_CLAIM_BY_SLOT,
+            collateral_token.as_slice(),
+            &[premium_slot],
+        ],
+    );
+
+    let start = calc_range_start_idx(start_after);
+    slot_indexer
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, _) = elem?;
+            read_bid(storage, Uint128::from(bytes_to_u128(&k)?))
+        })
+        .collect()
 }
 
 pub fn read_bid(storage: &dyn Storage, bid_idx: Uint128) -> StdResult<Bid> {
@@ -336,6 +560,371 @@ pub fn read_bids_by_user(
         .collect()
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Vault {
+    pub total_shares: Uint256,
+    /// Bids of the vault in the pool, owned by the contract
+    pub bids_idx: Vec<Uint128>,
+    /// Liquidated collateral claimed by the vault and not sold yet
+    pub collateral: Uint256,
+    /// CW20 minted for the shares of a tokenized vault
+    #[serde(default)]
+    pub share_token: Option<CanonicalAddr>,
+}
+
+#[allow(deprecated)]
+pub fn store_vault(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+    vault: &Vault,
+) -> StdResult<()> {
+    let mut vault_bucket: Bucket<Vault> =
+        Bucket::multilevel(storage, &[PREFIX_VAULT, collateral_token.as_slice()]);
+    vault_bucket.save(&premium_slot.to_be_bytes(), vault)
+}
+
+#[allow(deprecated)]
+pub fn read_vault(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+) -> StdResult<Vault> {
+    let vault_bucket: ReadonlyBucket<Vault> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_VAULT, collateral_token.as_slice()]);
+    Ok(vault_bucket
+        .may_load(&premium_slot.to_be_bytes())?
+        .unwrap_or_default())
+}
+
+/// Vault whose share token is being instantiated, until the reply
+#[allow(deprecated)]
+pub fn store_pending_vault_token(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+) -> StdResult<()> {
+    singleton(storage, KEY_PENDING_VAULT_TOKEN).save(&(collateral_token.clone(), premium_slot))
+}
+
+#[allow(deprecated)]
+pub fn take_pending_vault_token(storage: &mut dyn Storage) -> StdResult<(CanonicalAddr, u8)> {
+    let mut pending_vault_token = singleton(storage, KEY_PENDING_VAULT_TOKEN);
+    let pending: (CanonicalAddr, u8) = pending_vault_token.load()?;
+    pending_vault_token.remove();
+    Ok(pending)
+}
+
+#[allow(deprecated)]
+pub fn store_vault_shares(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+    depositor: &CanonicalAddr,
+    shares: Uint256,
+) -> StdResult<()> {
+    let mut shares_bucket: Bucket<Uint256> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_VAULT_SHARES,
+            collateral_token.as_slice(),
+            &premium_slot.to_be_bytes(),
+        ],
+    );
+    if shares.is_zero() {
+        shares_bucket.remove(depositor.as_slice());
+        Ok(())
+    } else {
+        shares_bucket.save(depositor.as_slice(), &shares)
+    }
+}
+
+#[allow(deprecated)]
+pub fn read_vault_shares(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+    depositor: &CanonicalAddr,
+) -> StdResult<Uint256> {
+    let shares_bucket: ReadonlyBucket<Uint256> = ReadonlyBucket::multilevel(
+        storage,
+        &[
+            PREFIX_VAULT_SHARES,
+            collateral_token.as_slice(),
+            &premium_slot.to_be_bytes(),
+        ],
+    );
+    Ok(shares_bucket
+        .may_load(depositor.as_slice())?
+        .unwrap_or_default())
+}
+
+/// Set the Dutch auction of a collateral, `None` removes it
+#[allow(deprecated)]
+pub fn store_dutch_auction(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    dutch_auction: Option<DutchAuction>,
+) -> StdResult<()> {
+    let mut dutch_auction_bucket: Bucket<DutchAuction> = Bucket::new(storage, PREFIX_DUTCH_AUCTION);
+    match dutch_auction {
+        Some(dutch_auction) => {
+            dutch_auction_bucket.save(collateral_token.as_slice(), &dutch_auction)
+        }
+        None => {
+            dutch_auction_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+#[allow(deprecated)]
+pub fn read_dutch_auction(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<DutchAuction>> {
+    let dutch_auction_bucket: ReadonlyBucket<DutchAuction> =
+        ReadonlyBucket::new(storage, PREFIX_DUTCH_AUCTION);
+    dutch_auction_bucket.may_load(collateral_token.as_slice())
+}
+
+/// Opt a collateral in or out of selling on the DEX what the queue can't fill
+#[allow(deprecated)]
+pub fn store_dex_fallback(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    enabled: bool,
+) -> StdResult<()> {
+    let mut dex_fallback_bucket: Bucket<bool> = Bucket::new(storage, PREFIX_DEX_FALLBACK);
+    if enabled {
+        dex_fallback_bucket.save(collateral_token.as_slice(), &true)
+    } else {
+        dex_fallback_bucket.remove(collateral_token.as_slice());
+        Ok(())
+    }
+}
+
+#[allow(deprecated)]
+pub fn read_dex_fallback(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<bool> {
+    let dex_fallback_bucket: ReadonlyBucket<bool> =
+        ReadonlyBucket::new(storage, PREFIX_DEX_FALLBACK);
+    Ok(dex_fallback_bucket
+        .may_load(collateral_token.as_slice())?
+        .unwrap_or_default())
+}
+
+#[allow(deprecated)]
+pub fn remove_active_bid_indexer(storage: &mut dyn Storage, bid: &Bid) {
+    let mut active_bid_indexer: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_ACTIVE_BID_BY_SLOT,
+            bid.collateral_token.as_slice(),
+            &bid.premium_slot.to_be_bytes(),
+        ],
+    );
+    active_bid_indexer.remove(&bid.idx.u128().to_be_bytes());
+}
+
+/// Drop all the bids of a slot from the age priority fill
+#[allow(deprecated)]
+pub fn clear_active_bid_indexer(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+) -> StdResult<()> {
+    let mut active_bid_indexer: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_ACTIVE_BID_BY_SLOT,
+            collateral_token.as_slice(),
+            &premium_slot.to_be_bytes(),
+        ],
+    );
+    let keys = active_bid_indexer
+        .range(None, None, Order::Ascending)
+        .map(|elem| elem.map(|(k, _)| k))
+        .collect::<StdResult<Vec<Vec<u8>>>>()?;
+    for key in keys {
+        active_bid_indexer.remove(&key);
+    }
+
+    Ok(())
+}
+
+/// Active bid of a slot with the lowest index, i.e. the oldest
+#[allow(deprecated)]
+pub fn read_oldest_active_bid(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+) -> StdResult<Option<Bid>> {
+    let active_bid_indexer: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        storage,
+        &[
+            PREFIX_ACTIVE_BID_BY_SLOT,
+            collateral_token.as_slice(),
+            &premium_slot.to_be_bytes(),
+        ],
+    );
+
+    let oldest_bid = active_bid_indexer
+        .range(None, None, Order::Ascending)
+        .next()
+        .map(|elem| {
+            let (k, _) = elem?;
+            read_bid(storage, Uint128::from(bytes_to_u128(&k)?))
+        })
+        .transpose();
+    oldest_bid
+}
+
+/// Opt a collateral in or out of filling the oldest bids of a slot first,
+/// with the minimum amount of its bids
+#[allow(deprecated)]
+pub fn store_age_priority(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    min_bid_amount: Option<Uint256>,
+) -> StdResult<()> {
+    let mut age_priority_bucket: Bucket<Uint256> = Bucket::new(storage, PREFIX_AGE_PRIORITY);
+    match min_bid_amount {
+        Some(min_bid_amount) => {
+            age_priority_bucket.save(collateral_token.as_slice(), &min_bid_amount)
+        }
+        None => {
+            age_priority_bucket.remove(collateral_token.as_slice());
+            Ok(())
+        }
+    }
+}
+
+/// Minimum bid amount of a collateral filled by age priority,
+/// None when its bids are filled pro-rata
+#[allow(deprecated)]
+pub fn read_age_priority(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Option<Uint256>> {
+    let age_priority_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::new(storage, PREFIX_AGE_PRIORITY);
+    age_priority_bucket.may_load(collateral_token.as_slice())
+}
+
+/// Collateral of a liquidation left to the Dutch auction, with the routing
+/// of the proceeds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Auction {
+    pub idx: Uint128,
+    pub collateral_token: CanonicalAddr,
+    pub amount: Uint256,
+    pub start_height: u64,
+    pub dutch_auction: DutchAuction,
+    pub borrower: String,
+    pub repay_address: String,
+    pub fee_address: String,
+    pub liquidator: String,
+}
+
+/// Collateral of a liquidation sent to the DEX, with the stable balance
+/// before the sale and the routing of the proceeds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DexLiquidation {
+    pub collateral_token: CanonicalAddr,
+    pub amount: Uint256,
+    pub prev_balance: Uint256,
+    pub borrower: Option<String>,
+    pub repay_address: String,
+    pub fee_address: String,
+    pub liquidator: String,
+}
+
+/// DEX sale of a liquidation, until the reply
+#[allow(deprecated)]
+pub fn store_pending_dex_liquidation(
+    storage: &mut dyn Storage,
+    dex_liquidation: &DexLiquidation,
+) -> StdResult<()> {
+    singleton(storage, KEY_PENDING_DEX_LIQUIDATION).save(dex_liquidation)
+}
+
+#[allow(deprecated)]
+pub fn take_pending_dex_liquidation(storage: &mut dyn Storage) -> StdResult<DexLiquidation> {
+    let mut pending_dex_liquidation = singleton(storage, KEY_PENDING_DEX_LIQUIDATION);
+    let pending: DexLiquidation = pending_dex_liquidation.load()?;
+    pending_dex_liquidation.remove();
+    Ok(pending)
+}
+
+#[allow(deprecated)]
+pub fn pop_auction_idx(storage: &mut dyn Storage) -> StdResult<Uint128> {
+    let mut idx_store = singleton(storage, KEY_AUCTION_IDX);
+    let last_idx: Uint128 = idx_store.load().unwrap_or_else(|_| Uint128::from(1u128));
+    idx_store.save(&(last_idx + Uint128::from(1u128)))?;
+    Ok(last_idx)
+}
+
+/// Store an auction lot, removed once its collateral is sold out
+#[allow(deprecated)]
+pub fn store_auction(storage: &mut dyn Storage, auction: &Auction) -> StdResult<()> {
+    let mut auction_bucket: Bucket<Auction> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_AUCTION_BY_COLLATERAL,
+            auction.collateral_token.as_slice(),
+        ],
+    );
+    if auction.amount.is_zero() {
+        auction_bucket.remove(&auction.idx.u128().to_be_bytes());
+        Ok(())
+    } else {
+        auction_bucket.save(&auction.idx.u128().to_be_bytes(), auction)
+    }
+}
+
+#[allow(deprecated)]
+pub fn read_auction(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    auction_idx: Uint128,
+) -> StdResult<Auction> {
+    let auction_bucket: ReadonlyBucket<Auction> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_AUCTION_BY_COLLATERAL, collateral_token.as_slice()],
+    );
+    auction_bucket
+        .load(&auction_idx.u128().to_be_bytes())
+        .map_err(|_| StdError::generic_err("Auction not found"))
+}
+
+#[allow(deprecated)]
+pub fn read_auctions(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<Vec<Auction>> {
+    let auction_bucket: ReadonlyBucket<Auction> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_AUCTION_BY_COLLATERAL, collateral_token.as_slice()],
+    );
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start_idx(start_after);
+
+    auction_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (_, v) = elem?;
+            Ok(v)
+        })
+        .collect()
+}
+
 fn bytes_to_u128(data: &[u8]) -> StdResult<u128> {
     match data[0..16].try_into() {
         Ok(bytes) => Ok(u128::from_be_bytes(bytes)),
@@ -0,0 +1,147 @@
+use std::str::FromStr;
+
+use crate::state::SwapConfig;
+use cosmwasm_std::{
+    to_json_binary, Coin, CosmosMsg, Decimal, Deps, QueryRequest, StdError, StdResult, Uint128,
+    WasmMsg, WasmQuery,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::astroport_router::{
+    AssetInfo, ExecuteMsg as AstroportExecuteMsg, QueryMsg as AstroportQueryMsg,
+    SimulateSwapOperationsResponse, SwapMessageType, SwapOperation,
+};
+use moneymarket::custody::Asset;
+use strum::IntoEnumIterator;
+
+/// Sell `asset_to_swap` on the configured DEX returning the most `ask_token`,
+/// the proceeds are sent to `to`
+pub fn create_swap_msg(
+    deps: Deps,
+    swap_config: &SwapConfig,
+    asset_to_swap: Asset,
+    ask_token: AssetInfo,
+    minimum_receive: Uint128,
+    to: String,
+) -> StdResult<CosmosMsg> {
+    let (best_marketplace, best_return): (SwapMessageType, Uint128) = SwapMessageType::iter()
+        .filter_map(|message_type| {
+            get_swap_result_for(
+                deps,
+                swap_config,
+                asset_to_swap.clone(),
+                ask_token.clone(),
+                message_type,
+            )
+            .ok()
+            .map(|swap_result| (message_type, swap_result))
+        })
+        .max_by_key(|&(_, swap_result)| swap_result)
+        .ok_or_else(|| StdError::generic_err("No DEX can simulate the swap"))?;
+
+    if best_return < minimum_receive {
+        return Err(StdError::generic_err(
+            "Swap return is below the max slippage versus the oracle price",
+        ));
+    }
+
+    create_swap_message_for(
+        swap_config,
+        asset_to_swap,
+        ask_token,
+        best_marketplace,
+        minimum_receive,
+        to,
+    )
+}
+
+fn get_astroport_swap_operation(
+    offer_token: AssetInfo,
+    ask_token: AssetInfo,
+    message_type: SwapMessageType,
+) -> SwapOperation {
+    match message_type {
+        SwapMessageType::Astroport => SwapOperation::AstroSwap {
+            offer_asset_info: offer_token,
+            ask_asset_info: ask_token,
+        },
+        SwapMessageType::Phoenix => SwapOperation::TokenSwap {
+            offer_asset_info: offer_token,
+            ask_asset_info: ask_token,
+        },
+        SwapMessageType::TerraSwap => SwapOperation::TerraSwap {
+            offer_asset_info: offer_token,
+            ask_asset_info: ask_token,
+        },
+    }
+}
+
+fn get_contract_address(swap_config: &SwapConfig, message_type: SwapMessageType) -> String {
+    match message_type {
+        SwapMessageType::Astroport => swap_config.astroport_addr.to_string(),
+        SwapMessageType::Phoenix => swap_config.phoenix_addr.to_string(),
+        SwapMessageType::TerraSwap => swap_config.terraswap_addr.to_string(),
+    }
+}
+
+fn create_swap_message_for(
+    swap_config: &SwapConfig,
+    asset_to_swap: Asset,
+    ask_token: AssetInfo,
+    message_type: SwapMessageType,
+    minimum_receive: Uint128,
+    to: String,
+) -> StdResult<CosmosMsg> {
+    let swap_contract_address = get_contract_address(swap_config, message_type);
+    let swap_msg = AstroportExecuteMsg::ExecuteSwapOperations {
+        operations: vec![get_astroport_swap_operation(
+            asset_to_swap.asset_info.clone(),
+            ask_token,
+            message_type,
+        )],
+        to: Some(to),
+        max_spread: Some(Decimal::from_str(&swap_config.max_slippage.to_string())?),
+        minimum_receive: Some(minimum_receive),
+    };
+
+    match asset_to_swap.asset_info {
+        AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                contract: swap_contract_address,
+                amount: asset_to_swap.amount,
+                msg: to_json_binary(&swap_msg)?,
+            })?,
+        })),
+        AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: swap_contract_address,
+            funds: vec![Coin {
+                amount: asset_to_swap.amount,
+                denom,
+            }],
+            msg: to_json_binary(&swap_msg)?,
+        })),
+    }
+}
+
+fn get_swap_result_for(
+    deps: Deps,
+    swap_config: &SwapConfig,
+    asset_to_swap: Asset,
+    ask_token: AssetInfo,
+    message_type: SwapMessageType,
+) -> StdResult<Uint128> {
+    let swap_operation_response: SimulateSwapOperationsResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: get_contract_address(swap_config, message_type),
+            msg: to_json_binary(&AstroportQueryMsg::SimulateSwapOperations {
+                offer_amount: asset_to_swap.amount,
+                operations: vec![get_astroport_swap_operation(
+                    asset_to_swap.asset_info,
+                    ask_token,
+                    message_type,
+                )],
+            })?,
+        }))?;
+    Ok(swap_operation_response.amount)
+}
@@ -0,0 +1,227 @@
+use crate::contract::{execute, instantiate, query};
+use crate::testing::mock_querier::mock_dependencies;
+
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi};
+use cosmwasm_std::{
+    from_json, to_json_binary, Coin, Decimal, Decimal256, MemoryStorage, OwnedDeps, Uint128,
+    Uint256,
+};
+use cw20::Cw20ReceiveMsg;
+use moneymarket::liquidation_queue::{
+    BidResponse, BidsResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
+};
+
+use super::mock_querier::WasmMockQuerier;
+
+const ITERATIONS: u32 = 60u32;
+const BIDDERS: [&str; 3] = ["alice0000", "bob0000", "carol0000"];
+
+#[test]
+fn age_priority_stress_tests() {
+    // submit bids of different sizes and execute liquidations repeatedly,
+    // the queue must always be consumed from the oldest bid
+
+    // liquidations smaller than a bid
+    simulate_age_priority_bids(
+        ITERATIONS,
+        Decimal256::percent(2000),
+        1000000000u128,
+        49999999u128,
+    );
+    // liquidations spanning several bids
+    simulate_age_priority_bids(
+        ITERATIONS,
+        Decimal256::percent(500),
+        10000000u128,
+        11999999u128,
+    );
+    // very small asset price
+    simulate_age_priority_bids(
+        ITERATIONS,
+        Decimal256::percent(10), // 0.1 UST/asset
+        100000000000u128,
+        5999999999999u128,
+    );
+    // with greater asset price (10k UST per collateral)
+    simulate_age_priority_bids(
+        ITERATIONS,
+        Decimal256::percent(1000000),
+        10001000000u128,
+        1000000u128,
+    );
+}
+
+fn instantiate_and_whitelist(deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>) {
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"col0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 101u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "col0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000000000000u128), // to get instant activation
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateAgePriority {
+        collateral_token: "col0000".to_string(),
+        enabled: true,
+        min_bid_amount: Some(Uint256::from(1000000u64)),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+}
+
+fn simulate_age_priority_bids(
+    iterations: u32,
+    asset_price: Decimal256,
+    bid_amount: u128,
+    liq_amount: u128,
+) {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_whitelist(&mut deps);
+    deps.querier.with_oracle_price(&[(
+        &("col0000".to_string(), "uusd".to_string()),
+        &(
+            asset_price,
+            mock_env().block.time.seconds(),
+            mock_env().block.time.seconds(),
+        ),
+    )]);
+
+    let mut deposits: Vec<Uint256> = vec![];
+    let mut total_deposited = Uint256::zero();
+    let mut total_liquidated = Uint256::zero();
+    let mut liquidations = 0u128;
+    for i in 0..iterations {
+        // bids of 1x, 2x and 3x the bid amount, in turns
+        let amount = bid_amount * (i as u128 % 3 + 1);
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "col0000".to_string(),
+            premium_slot: 0u8,
+        };
+        let info = mock_info(
+            BIDDERS[i as usize % BIDDERS.len()],
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        deposits.push(Uint256::from(amount));
+        total_deposited += Uint256::from(amount);
+
+        // liquidate after every round of bidders
+        if i % 3 != 2 {
+            continue;
+        }
+        let info = mock_info("col0000", &[]);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "custody0000".to_string(),
+            amount: Uint128::from(liq_amount),
+            msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+                liquidator: "liquidator00000".to_string(),
+                fee_address: Some("fee0000".to_string()),
+                repay_address: Some("repay0000".to_string()),
+                borrower: None,
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        total_liquidated += Uint256::from(liq_amount);
+        liquidations += 1;
+
+        assert_age_priority(&deps, &deposits);
+    }
+
+    let mut bids: Vec<BidResponse> = vec![];
+    for bidder in BIDDERS.iter() {
+        let bids_res: BidsResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::BidsByUser {
+                    collateral_token: "col0000".to_string(),
+                    bidder: bidder.to_string(),
+                    start_after: None,
+                    limit: Some(30u8),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        bids.extend(bids_res.bids);
+    }
+    assert_eq!(bids.len(), iterations as usize);
+
+    let total_claimed: Uint256 = bids
+        .iter()
+        .map(|bid| bid.pending_liquidated_collateral)
+        .sum();
+    let total_remaining: Uint256 = bids.iter().map(|bid| bid.amount).sum();
+    println!("total claimed:    {}", total_claimed);
+    println!("total liquidated: {}", total_liquidated);
+    // no collateral is lost to residues
+    assert_eq!(total_claimed, total_liquidated);
+    // bids pay the premium price of the collateral they receive, the stable
+    // of a partially consumed bid is rounded down once per liquidation
+    assert!(
+        total_deposited - total_remaining + Uint256::from(liquidations)
+            >= total_liquidated * asset_price
+    );
+}
+
+/// Every bid older than the oldest remaining one has been consumed, and
+/// every bid after it is untouched
+fn assert_age_priority(
+    deps: &OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+    deposits: &[Uint256],
+) {
+    let mut oldest_remaining: Option<usize> = None;
+    for (i, deposit) in deposits.iter().enumerate() {
+        let bid: BidResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Bid {
+                    bid_idx: Uint128::from(i as u128 + 1),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        match oldest_remaining {
+            None if !bid.amount.is_zero() => oldest_remaining = Some(i),
+            None => {}
+            Some(_) => {
+                assert_eq!(bid.amount, *deposit);
+                assert!(bid.pending_liquidated_collateral.is_zero());
+            }
+        }
+    }
+}
@@ -30,6 +30,7 @@ fn one_bidder_distribution() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -50,6 +51,7 @@ fn one_bidder_distribution() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -88,6 +90,7 @@ fn one_bidder_distribution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -145,6 +148,7 @@ fn two_bidder_distribution() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -165,6 +169,7 @@ fn two_bidder_distribution() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -203,6 +208,7 @@ fn two_bidder_distribution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -251,6 +257,7 @@ fn two_bidder_distribution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -331,6 +338,7 @@ fn two_bidder_distribution_big_numbers() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -351,6 +359,7 @@ fn two_bidder_distribution_big_numbers() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -389,6 +398,7 @@ fn two_bidder_distribution_big_numbers() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -437,6 +447,7 @@ fn two_bidder_distribution_big_numbers() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -517,6 +528,7 @@ fn one_user_two_bid_slots() {
         price_timeframe: 10u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -537,6 +549,7 @@ fn one_user_two_bid_slots() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -589,6 +602,7 @@ fn one_user_two_bid_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -619,6 +633,7 @@ fn one_user_two_bid_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -683,6 +698,7 @@ fn partial_withdraw_after_execution() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -703,6 +719,7 @@ fn partial_withdraw_after_execution() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -741,6 +758,7 @@ fn partial_withdraw_after_execution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -785,6 +803,7 @@ fn partial_withdraw_after_execution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -884,6 +903,7 @@ fn completely_empty_pool() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -904,6 +924,7 @@ fn completely_empty_pool() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), env, info, msg).unwrap();
@@ -931,6 +952,7 @@ fn completely_empty_pool() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -998,6 +1020,7 @@ fn completely_empty_pool() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1062,6 +1085,7 @@ fn product_truncated_to_zero() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1082,6 +1106,7 @@ fn product_truncated_to_zero() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1111,6 +1136,7 @@ fn product_truncated_to_zero() {
                 liquidator: "liquidator00000".to_string(),
                 fee_address: Some("fee0000".to_string()),
                 repay_address: Some("repay0000".to_string()),
+                borrower: None,
             })
             .unwrap(),
         });
@@ -1184,6 +1210,7 @@ fn two_bidder_reward_distribution_common_slot() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     // 10 ust/col
@@ -1205,6 +1232,7 @@ fn two_bidder_reward_distribution_common_slot() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), env, info, msg).unwrap();
@@ -1270,6 +1298,7 @@ fn two_bidder_reward_distribution_common_slot() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1361,6 +1390,7 @@ fn two_bidder_distribution_multiple_common_slots() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1381,6 +1411,7 @@ fn two_bidder_distribution_multiple_common_slots() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
 
     let info = mock_info("owner0000", &[]);
@@ -1502,6 +1533,7 @@ fn two_bidder_distribution_multiple_common_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1517,6 +1549,7 @@ fn two_bidder_distribution_multiple_common_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1676,6 +1709,7 @@ fn two_bidder_unequal_deposit_reward_distribution() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     // 2 ust/col
@@ -1697,6 +1731,7 @@ fn two_bidder_unequal_deposit_reward_distribution() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), env, info, msg).unwrap();
@@ -1762,6 +1797,7 @@ fn two_bidder_unequal_deposit_reward_distribution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1860,6 +1896,7 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1880,6 +1917,7 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
 
     let info = mock_info("owner0000", &[]);
@@ -1977,6 +2015,7 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2050,6 +2089,7 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2115,6 +2155,7 @@ fn not_enough_bid_for_collateral() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -2135,6 +2176,7 @@ fn not_enough_bid_for_collateral() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), env, info, msg).unwrap();
@@ -2199,6 +2241,7 @@ fn not_enough_bid_for_collateral() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2231,6 +2274,7 @@ fn two_bidder_reward_distribution_common_slot_large_numbers() {
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     // 1000 ust/col
@@ -2252,6 +2296,7 @@ fn two_bidder_reward_distribution_common_slot_large_numbers() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -2315,6 +2360,7 @@ fn two_bidder_reward_distribution_common_slot_large_numbers() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1,4 +1,5 @@
 use cosmwasm_std::Empty;
+use moneymarket::astroport_router::{SimulateSwapOperationsResponse, SwapOperation};
 use moneymarket::overseer::{WhitelistResponse, WhitelistResponseElem};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -10,6 +11,7 @@ use cosmwasm_std::{
     Querier, QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
 use std::collections::HashMap;
+use std::str::FromStr;
 
 use moneymarket::oracle::PriceResponse;
 
@@ -23,6 +25,10 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    SimulateSwapOperations {
+        offer_amount: Uint128,
+        operations: Vec<SwapOperation>,
+    },
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -46,6 +52,13 @@ pub struct WasmMockQuerier {
     base: MockQuerier<Empty>,
     oracle_price_querier: OraclePriceQuerier,
     collateral_querier: CollateralQuerier,
+    swap_querier: SwapQuerier,
+}
+
+#[derive(Clone, Default)]
+pub struct SwapQuerier {
+    // router address -> swap rate
+    swap_rates: HashMap<String, Decimal256>,
 }
 
 #[derive(Clone, Default)]
@@ -118,7 +131,7 @@ impl WasmMockQuerier {
     pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
         match &request {
             QueryRequest::Wasm(WasmQuery::Smart {
-                contract_addr: _,
+                contract_addr,
                 msg,
             }) => match from_json(msg).unwrap() {
                 QueryMsg::Price { base, quote } => {
@@ -163,6 +176,20 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                QueryMsg::SimulateSwapOperations {
+                    offer_amount,
+                    operations: _,
+                } => match self.swap_querier.swap_rates.get(contract_addr) {
+                    Some(rate) => SystemResult::Ok(ContractResult::from(to_json_binary(
+                        &SimulateSwapOperationsResponse {
+                            amount: offer_amount * Decimal::from_str(&rate.to_string()).unwrap(),
+                        },
+                    ))),
+                    None => SystemResult::Err(SystemError::InvalidRequest {
+                        error: "No swap route exists".to_string(),
+                        request: msg.as_slice().into(),
+                    }),
+                },
             },
             _ => self.base.handle_query(request),
         }
@@ -175,6 +202,7 @@ impl WasmMockQuerier {
             base,
             oracle_price_querier: OraclePriceQuerier::default(),
             collateral_querier: CollateralQuerier::default(),
+            swap_querier: SwapQuerier::default(),
         }
     }
 
@@ -192,4 +220,15 @@ impl WasmMockQuerier {
     pub fn with_collateral_max_ltv(&mut self, collaterals: &[(&String, &Decimal256)]) {
         self.collateral_querier = CollateralQuerier::new(collaterals);
     }
+
+    pub fn update_balance(&mut self, addr: &str, balance: Vec<Coin>) {
+        self.base.update_balance(addr, balance);
+    }
+
+    pub fn with_swap_rates(&mut self, swap_rates: &[(&str, Decimal256)]) {
+        self.swap_querier.swap_rates = swap_rates
+            .iter()
+            .map(|(router, rate)| (router.to_string(), *rate))
+            .collect();
+    }
 }
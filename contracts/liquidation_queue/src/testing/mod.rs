@@ -1,3 +1,4 @@
+mod age_priority_stress_tests;
 mod bid_pools_tests;
 mod mock_querier;
 mod product_stress_tests;
@@ -114,6 +114,7 @@ fn instantiate_and_whitelist(deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMo
         price_timeframe: 101u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -126,6 +127,7 @@ fn instantiate_and_whitelist(deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMo
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000000000000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -176,6 +178,7 @@ fn simulate_bids_with_2_liq_amounts(
                     liquidator: "liquidator00000".to_string(),
                     fee_address: Some("fee0000".to_string()),
                     repay_address: Some("repay0000".to_string()),
+                    borrower: None,
                 })
                 .unwrap(),
             });
@@ -192,6 +195,7 @@ fn simulate_bids_with_2_liq_amounts(
                     liquidator: "liquidator00000".to_string(),
                     fee_address: Some("fee0000".to_string()),
                     repay_address: Some("repay0000".to_string()),
+                    borrower: None,
                 })
                 .unwrap(),
             });
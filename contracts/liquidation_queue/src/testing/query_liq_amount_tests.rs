@@ -30,6 +30,7 @@ fn partial_one_collateral_one_slot_high_ltv() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -40,6 +41,7 @@ fn partial_one_collateral_one_slot_high_ltv() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -98,6 +100,7 @@ fn partial_one_collateral_one_slot_high_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -136,6 +139,7 @@ fn partial_one_collateral_one_slot() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -146,6 +150,7 @@ fn partial_one_collateral_one_slot() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -204,6 +209,7 @@ fn partial_one_collateral_one_slot() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -242,6 +248,7 @@ fn partial_one_collateral_one_slot_with_fees() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -252,6 +259,7 @@ fn partial_one_collateral_one_slot_with_fees() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -310,6 +318,7 @@ fn partial_one_collateral_one_slot_with_fees() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -357,6 +366,7 @@ fn partial_one_collateral_one_slot_with_fees_all() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -367,6 +377,7 @@ fn partial_one_collateral_one_slot_with_fees_all() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -425,6 +436,7 @@ fn partial_one_collateral_one_slot_with_fees_all() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -479,6 +491,7 @@ fn partial_one_collateral_two_slots() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -489,6 +502,7 @@ fn partial_one_collateral_two_slots() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -554,6 +568,7 @@ fn partial_one_collateral_two_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -592,6 +607,7 @@ fn partial_one_collateral_two_slots_with_fees() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -602,6 +618,7 @@ fn partial_one_collateral_two_slots_with_fees() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -667,6 +684,7 @@ fn partial_one_collateral_two_slots_with_fees() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -714,6 +732,7 @@ fn non_partial_liquidation() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -724,6 +743,7 @@ fn non_partial_liquidation() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -775,6 +795,7 @@ fn non_partial_liquidation() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -813,6 +834,7 @@ fn non_partial_liquidation_two_slots() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -823,6 +845,7 @@ fn non_partial_liquidation_two_slots() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -879,6 +902,7 @@ fn non_partial_liquidation_two_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -917,6 +941,7 @@ fn non_partial_liquidation_with_fees() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -927,6 +952,7 @@ fn non_partial_liquidation_with_fees() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -978,6 +1004,7 @@ fn non_partial_liquidation_with_fees() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1025,6 +1052,7 @@ fn non_partial_liquidation_two_slots_with_fees() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1035,6 +1063,7 @@ fn non_partial_liquidation_two_slots_with_fees() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1091,6 +1120,7 @@ fn non_partial_liquidation_two_slots_with_fees() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1138,6 +1168,7 @@ fn non_partial_liquidation_two_slots_with_fees_big_nums() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1148,6 +1179,7 @@ fn non_partial_liquidation_two_slots_with_fees_big_nums() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000000000000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1204,6 +1236,7 @@ fn non_partial_liquidation_two_slots_with_fees_big_nums() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1253,6 +1286,7 @@ fn partial_two_collaterals_ltv_diff() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1263,6 +1297,7 @@ fn partial_two_collaterals_ltv_diff() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -1271,6 +1306,7 @@ fn partial_two_collaterals_ltv_diff() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -1353,6 +1389,7 @@ fn partial_two_collaterals_ltv_diff() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1377,6 +1414,7 @@ fn partial_two_collaterals_ltv_diff() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1418,6 +1456,7 @@ fn partial_two_collaterals_multi_slots_per_col() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1428,6 +1467,7 @@ fn partial_two_collaterals_multi_slots_per_col() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -1436,6 +1476,7 @@ fn partial_two_collaterals_multi_slots_per_col() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -1528,6 +1569,7 @@ fn partial_two_collaterals_multi_slots_per_col() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1552,6 +1594,7 @@ fn partial_two_collaterals_multi_slots_per_col() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1593,6 +1636,7 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1603,6 +1647,7 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -1611,6 +1656,7 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -1693,6 +1739,7 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1717,6 +1764,7 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1759,6 +1807,7 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1769,6 +1818,7 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -1777,6 +1827,7 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     let msg = ExecuteMsg::WhitelistCollateral {
@@ -1784,6 +1835,7 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(1000000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -1887,6 +1939,7 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1911,6 +1964,7 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1935,6 +1989,7 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1977,6 +2032,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1987,6 +2043,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(100000000000000000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -1995,6 +2052,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(100000000000000000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     let msg = ExecuteMsg::WhitelistCollateral {
@@ -2002,6 +2060,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(100000000000000000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -2111,6 +2170,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2135,6 +2195,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2159,6 +2220,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2201,6 +2263,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -2211,6 +2274,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(100000000000000000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -2219,6 +2283,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(100000000000000000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     let msg = ExecuteMsg::WhitelistCollateral {
@@ -2226,6 +2291,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(100000000000000000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -2335,6 +2401,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2359,6 +2426,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2383,6 +2451,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2424,6 +2493,7 @@ fn not_enough_bids_for_one_of_two_col() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -2434,6 +2504,7 @@ fn not_enough_bids_for_one_of_two_col() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -2442,6 +2513,7 @@ fn not_enough_bids_for_one_of_two_col() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -2531,6 +2603,7 @@ fn not_enough_bids_for_one_of_two_col() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2564,6 +2637,7 @@ fn not_enough_bids_for_one_of_two_col() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2614,6 +2688,7 @@ fn integration_test_simul() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -2624,6 +2699,7 @@ fn integration_test_simul() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(100000000000000000000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -2698,6 +2774,7 @@ fn integration_test_simul() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1,10 +1,12 @@
 use crate::contract::{execute, instantiate, query};
 use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_std::testing::{mock_env, mock_info};
-use cosmwasm_std::{from_json, Coin, Decimal, Decimal256, Uint128, Uint256};
+use cosmwasm_std::{attr, from_json, to_json_binary, Coin, Decimal, Decimal256, Uint128, Uint256};
+use cw20::Cw20ReceiveMsg;
 use moneymarket::liquidation_queue::{
-    BidPoolResponse, BidPoolsResponse, BidResponse, BidsResponse, CollateralInfoResponse,
-    ExecuteMsg, InstantiateMsg, LiquidationAmountResponse, QueryMsg,
+    BidPoolResponse, BidPoolsResponse, BidResponse, BidsResponse, ClaimableByUserResponse,
+    ClaimableCollateralResponse, CollateralInfoResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
+    LiquidationAmountResponse, LiquidationDepthResponse, QueryMsg, SlotDepthResponse,
 };
 
 #[test]
@@ -31,6 +33,7 @@ fn query_liquidation_amount() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -41,6 +44,7 @@ fn query_liquidation_amount() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -49,6 +53,7 @@ fn query_liquidation_amount() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     let msg = ExecuteMsg::WhitelistCollateral {
@@ -56,6 +61,7 @@ fn query_liquidation_amount() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -168,6 +174,7 @@ fn query_bids() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -178,6 +185,7 @@ fn query_bids() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -230,6 +238,7 @@ fn query_bids() {
             sum_snapshot: Decimal256::zero(),
             epoch_snapshot: Uint128::zero(),
             scale_snapshot: Uint128::zero(),
+            auto_claim: None,
         }
     );
 
@@ -263,6 +272,7 @@ fn query_bids() {
                     sum_snapshot: Decimal256::zero(),
                     epoch_snapshot: Uint128::zero(),
                     scale_snapshot: Uint128::zero(),
+                    auto_claim: None,
                 },
                 BidResponse {
                     idx: Uint128::from(2u128),
@@ -276,6 +286,7 @@ fn query_bids() {
                     sum_snapshot: Decimal256::zero(),
                     epoch_snapshot: Uint128::zero(),
                     scale_snapshot: Uint128::zero(),
+                    auto_claim: None,
                 },
                 BidResponse {
                     idx: Uint128::from(3u128),
@@ -289,6 +300,7 @@ fn query_bids() {
                     sum_snapshot: Decimal256::zero(),
                     epoch_snapshot: Uint128::zero(),
                     scale_snapshot: Uint128::zero(),
+                    auto_claim: None,
                 }
             ]
         }
@@ -323,6 +335,7 @@ fn query_bids() {
                 sum_snapshot: Decimal256::zero(),
                 epoch_snapshot: Uint128::zero(),
                 scale_snapshot: Uint128::zero(),
+                auto_claim: None,
             }]
         }
     );
@@ -349,6 +362,7 @@ fn query_bid_pools() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -359,6 +373,7 @@ fn query_bid_pools() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128), // to get instant activation
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -504,6 +519,7 @@ fn query_collateral_info() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -514,6 +530,7 @@ fn query_collateral_info() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -536,6 +553,286 @@ fn query_collateral_info() {
             max_slot: 30u8,
             bid_threshold: Uint256::from(10000u128),
             premium_rate_per_slot: Decimal256::percent(1),
+            premium_rates: None,
         }
     );
 }
+
+#[test]
+fn query_liquidation_depth() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"token0000".to_string(), &Decimal256::percent(50))]);
+    deps.querier.with_oracle_price(&[(
+        &("token0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            mock_env().block.time.seconds(),
+            mock_env().block.time.seconds(),
+        ),
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "token0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(100000000u128), // to get instant activation
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for (premium_slot, amount) in [(0u8, 1000000u128), (2u8, 2000000u128)] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "token0000".to_string(),
+            premium_slot,
+        };
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // filled by the first slot
+    let msg = QueryMsg::LiquidationDepth {
+        collateral_token: "token0000".to_string(),
+        collateral_amount: Uint256::from(500000u64),
+    };
+    let res: LiquidationDepthResponse =
+        from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        LiquidationDepthResponse {
+            collateral_token: "token0000".to_string(),
+            collateral_amount: Uint256::from(500000u64),
+            repay_amount: Uint256::from(495000u64),
+            effective_discount: Decimal256::percent(1),
+            slots: vec![SlotDepthResponse {
+                premium_slot: 0u8,
+                premium_rate: Decimal256::zero(),
+                bid_amount: Uint256::from(500000u64),
+                collateral_amount: Uint256::from(500000u64),
+            }],
+            unfilled_amount: Uint256::zero(),
+        }
+    );
+
+    // runs through both slots and leaves a remainder
+    let msg = QueryMsg::LiquidationDepth {
+        collateral_token: "token0000".to_string(),
+        collateral_amount: Uint256::from(3500000u64),
+    };
+    let res: LiquidationDepthResponse =
+        from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        LiquidationDepthResponse {
+            collateral_token: "token0000".to_string(),
+            collateral_amount: Uint256::from(3500000u64),
+            repay_amount: Uint256::from(2970000u64),
+            effective_discount: Decimal256::one() - Decimal256::from_ratio(2970000u64, 3040816u64),
+            slots: vec![
+                SlotDepthResponse {
+                    premium_slot: 0u8,
+                    premium_rate: Decimal256::zero(),
+                    bid_amount: Uint256::from(1000000u64),
+                    collateral_amount: Uint256::from(1000000u64),
+                },
+                SlotDepthResponse {
+                    premium_slot: 2u8,
+                    premium_rate: Decimal256::percent(2),
+                    bid_amount: Uint256::from(2000000u64),
+                    collateral_amount: Uint256::from(2040816u64),
+                },
+            ],
+            unfilled_amount: Uint256::from(459184u64),
+        }
+    );
+
+    // the bids are not consumed
+    let msg = QueryMsg::BidPool {
+        collateral_token: "token0000".to_string(),
+        bid_slot: 2u8,
+    };
+    let res: BidPoolResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.total_bid_amount, Uint256::from(2000000u64));
+}
+
+#[test]
+fn query_claimable_by_user() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_collateral_max_ltv(&[
+        (&"token0000".to_string(), &Decimal256::percent(50)),
+        (&"token0001".to_string(), &Decimal256::percent(50)),
+        (&"token0002".to_string(), &Decimal256::percent(50)),
+    ]);
+    deps.querier.with_oracle_price(&[(
+        &("token0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            mock_env().block.time.seconds(),
+            mock_env().block.time.seconds(),
+        ),
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // bids on token0001 wait for activation above the first one
+    for (collateral_token, bid_threshold) in [
+        ("token0000", 100000000u128),
+        ("token0001", 10000u128),
+        ("token0002", 100000000u128),
+    ] {
+        let msg = ExecuteMsg::WhitelistCollateral {
+            collateral_token: collateral_token.to_string(),
+            max_slot: 30u8,
+            bid_threshold: Uint256::from(bid_threshold),
+            premium_rate_per_slot: Decimal256::percent(1),
+            premium_rates: None,
+        };
+        let info = mock_info("owner0000", &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    for (bidder, collateral_token, premium_slot, amount) in [
+        ("addr0000", "token0000", 0u8, 1000000u128),
+        ("addr0000", "token0000", 0u8, 1000000u128),
+        ("addr0000", "token0000", 1u8, 500000u128),
+        ("addr0001", "token0000", 0u8, 1000000u128),
+        ("addr0001", "token0001", 0u8, 300000u128),
+        ("addr0000", "token0001", 0u8, 300000u128),
+    ] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: collateral_token.to_string(),
+            premium_slot,
+        };
+        let info = mock_info(
+            bidder,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // consumes a third of the first slot, leaving residues
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000001u128),
+        msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: None,
+            repay_address: None,
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("token0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the residue of the other bidder is left in the pool
+    let msg = ExecuteMsg::ClaimLiquidations {
+        collateral_token: "token0000".to_string(),
+        bids_idx: None,
+    };
+    let info = mock_info("addr0001", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = QueryMsg::ClaimableByUser {
+        bidder: "addr0000".to_string(),
+    };
+    let res: ClaimableByUserResponse =
+        from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        ClaimableByUserResponse {
+            bidder: "addr0000".to_string(),
+            collaterals: vec![
+                ClaimableCollateralResponse {
+                    collateral_token: "token0001".to_string(),
+                    pending_collateral: Uint256::zero(),
+                    claimable_collateral: Uint256::zero(),
+                    bid_amount: Uint256::zero(),
+                    waiting_bid_amount: Uint256::from(300000u64),
+                },
+                ClaimableCollateralResponse {
+                    collateral_token: "token0000".to_string(),
+                    pending_collateral: Uint256::from(666666u64),
+                    claimable_collateral: Uint256::from(666667u64),
+                    bid_amount: Uint256::from(1833332u64),
+                    waiting_bid_amount: Uint256::zero(),
+                },
+            ],
+        }
+    );
+
+    // matches the collateral of the bids
+    let msg = QueryMsg::BidsByUser {
+        collateral_token: "token0000".to_string(),
+        bidder: "addr0000".to_string(),
+        start_after: None,
+        limit: None,
+    };
+    let res: BidsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    let pending_collateral: Uint256 = res
+        .bids
+        .iter()
+        .map(|bid| bid.pending_liquidated_collateral)
+        .sum();
+    assert_eq!(pending_collateral, Uint256::from(666666u64));
+
+    // and the collateral sent on claim
+    let msg = ExecuteMsg::ClaimLiquidations {
+        collateral_token: "token0000".to_string(),
+        bids_idx: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("collateral_amount", "666667")));
+
+    let msg = QueryMsg::ClaimableByUser {
+        bidder: "addr0002".to_string(),
+    };
+    let res: ClaimableByUserResponse =
+        from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.collaterals, vec![]);
+}
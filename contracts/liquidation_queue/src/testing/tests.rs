@@ -1,16 +1,26 @@
-use crate::contract::{execute, instantiate, query};
-use crate::testing::mock_querier::mock_dependencies;
+use crate::contract::{execute, instantiate, query, reply};
+use crate::response::MsgInstantiateContractResponse;
+use crate::state::{read_collateral_auto_claim_bids, read_oldest_active_bid};
+use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
 
-use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, from_json, to_json_binary, BankMsg, Coin, CosmosMsg, Decimal, Decimal256, StdError, SubMsg,
-    Uint128, Uint256,
+    attr, from_json, to_json_binary, Addr, Api, BankMsg, Coin, CosmosMsg, Decimal, Decimal256,
+    Deps, MemoryStorage, OwnedDeps, Reply, StdError, SubMsg, SubMsgResponse, SubMsgResult, Uint128,
+    Uint256, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use cw20_base::msg::InstantiateMsg as TokenInstantiateMsg;
+use moneymarket::astroport_router::{
+    AssetInfo, ExecuteMsg as AstroportExecuteMsg, SwapOperation,
 };
-use cw20::Cw20ReceiveMsg;
 use moneymarket::liquidation_queue::{
-    BidResponse, CollateralInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
-    QueryMsg,
+    AuctionResponse, AuctionsResponse, AutoClaim, BidPoolResponse, BidResponse, BidsResponse,
+    CollateralInfoResponse, ConfigResponse, Cw20HookMsg, DutchAuction, ExecuteMsg,
+    FeesCollectedResponse, InstantiateMsg, QueryMsg, VaultResponse, VaultSharesResponse,
 };
+use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
+use protobuf::Message;
 
 #[test]
 fn proper_initialization() {
@@ -27,6 +37,7 @@ fn proper_initialization() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -51,6 +62,9 @@ fn proper_initialization() {
             price_timeframe: 60u64,
             waiting_period: 60u64,
             overseer: "overseer0000".to_string(),
+            stable_asset: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
         }
     );
 }
@@ -70,6 +84,7 @@ fn update_config() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -108,6 +123,9 @@ fn update_config() {
             price_timeframe: 60u64,
             waiting_period: 60u64,
             overseer: "overseer0000".to_string(),
+            stable_asset: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
         }
     );
 
@@ -144,6 +162,9 @@ fn update_config() {
             price_timeframe: 120u64,
             waiting_period: 100u64,
             overseer: "overseer0001".to_string(),
+            stable_asset: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
         }
     );
 
@@ -182,6 +203,7 @@ fn submit_bid() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -192,6 +214,7 @@ fn submit_bid() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -275,10 +298,123 @@ fn submit_bid() {
             wait_end: Some(wait_end.seconds()),
             epoch_snapshot: Uint128::zero(),
             scale_snapshot: Uint128::zero(),
+            auto_claim: None,
         }
     );
 }
 
+#[test]
+fn submit_cw20_bid() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "stable0000".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: Some(AssetInfo::Token {
+            contract_addr: Addr::unchecked("stable0000"),
+        }),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // native funds are not accepted
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "stable0000".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Invalid asset provided, only stable0000 allowed")
+    );
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_json_binary(&Cw20HookMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_slot: 1u8,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("token0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("stable0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "submit_bid"),
+            attr("bid_idx", "1"),
+            attr("amount", "1000000"),
+        ]
+    );
+
+    let bid_response: BidResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Bid {
+                bid_idx: Uint128::from(1u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bid_response.bidder, "addr0000".to_string());
+    assert_eq!(bid_response.amount, Uint256::from(1000000u128));
+
+    // retracted bids are paid back with the token
+    let msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(1u128),
+        amount: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "stable0000".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(1000000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
 #[test]
 fn activate_bid() {
     let mut deps = mock_dependencies(&[]);
@@ -296,6 +432,7 @@ fn activate_bid() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -306,6 +443,7 @@ fn activate_bid() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -390,6 +528,7 @@ fn activate_bid() {
             wait_end: None,
             epoch_snapshot: Uint128::zero(),
             scale_snapshot: Uint128::zero(),
+            auto_claim: None,
         }
     );
 }
@@ -411,6 +550,7 @@ fn activate_same_bid_multiple_times() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -421,6 +561,7 @@ fn activate_same_bid_multiple_times() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -469,6 +610,7 @@ fn retract_bid() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -479,6 +621,7 @@ fn retract_bid() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -542,6 +685,7 @@ fn retract_unactive_bid() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -552,6 +696,7 @@ fn retract_unactive_bid() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -621,6 +766,7 @@ fn execute_bid() {
         price_timeframe: 100000u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -641,6 +787,7 @@ fn execute_bid() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), env, info, msg).unwrap();
@@ -680,6 +827,7 @@ fn execute_bid() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -700,6 +848,7 @@ fn execute_bid() {
             liquidator: "liquidator0000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -739,6 +888,7 @@ fn execute_bid() {
             liquidator: "liquidator0000".to_string(),
             fee_address: None,
             repay_address: None,
+            borrower: None,
         })
         .unwrap(),
     });
@@ -770,6 +920,10 @@ fn execute_bid() {
         ]
     );
 
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::FeesCollected {}).unwrap();
+    let fees_collected: FeesCollectedResponse = from_json(res).unwrap();
+    assert_eq!(fees_collected.bid_fee, Uint256::from(9900u128));
+
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "custody0000".to_string(),
         amount: Uint128::from(2020206u128),
@@ -777,6 +931,7 @@ fn execute_bid() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -807,6 +962,7 @@ fn claim_liquidations() {
         price_timeframe: 1000000u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -827,6 +983,7 @@ fn claim_liquidations() {
         max_slot: 30u8,
         bid_threshold: Uint256::zero(),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -865,6 +1022,7 @@ fn claim_liquidations() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -886,6 +1044,2160 @@ fn claim_liquidations() {
     );
 }
 
+#[test]
+fn transfer_bid() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let env = mock_env();
+    let wait_end = env.block.time.plus_seconds(60u64);
+    execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::ActivateBids {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: Some(vec![Uint128::from(1u128)]),
+    };
+    let mut env = mock_env();
+    env.block.time = wait_end;
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // required_stable 495,000
+    // bid_fee         4,950
+    // repay_amount    490,050
+    let info = mock_info("asset0000", &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::TransferBid {
+        bid_idx: Uint128::from(1u128),
+        recipient: "addr0001".to_string(),
+    };
+    let info = mock_info("addr0001", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the bid is indexed under the recipient with its liquidated collateral
+    let bids_response: BidsResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidsByUser {
+                collateral_token: "asset0000".to_string(),
+                bidder: "addr0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(bids_response.bids.is_empty());
+
+    let bids_response: BidsResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidsByUser {
+                collateral_token: "asset0000".to_string(),
+                bidder: "addr0001".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bids_response.bids.len(), 1);
+    assert_eq!(bids_response.bids[0].bidder, "addr0001".to_string());
+    assert_eq!(bids_response.bids[0].amount, Uint256::from(505000u64));
+    assert_eq!(
+        bids_response.bids[0].pending_liquidated_collateral,
+        Uint256::from(1000000u64)
+    );
+
+    let msg = ExecuteMsg::ClaimLiquidations {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: None,
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_liquidations"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "1000000"),
+        ]
+    );
+}
+
+#[test]
+fn dutch_auction() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(200000u128),
+        }],
+    );
+    let env = mock_env();
+    let wait_end = env.block.time.plus_seconds(60u64);
+    execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::ActivateBids {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: Some(vec![Uint128::from(1u128)]),
+    };
+    let mut env = mock_env();
+    env.block.time = wait_end;
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let liquidation_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: Some("borrower0000".to_string()),
+        })
+        .unwrap(),
+    });
+
+    let msg = ExecuteMsg::UpdateDutchAuction {
+        collateral_token: "asset0000".to_string(),
+        dutch_auction: Some(DutchAuction {
+            start_discount: Decimal256::percent(5),
+            discount_per_block: Decimal256::percent(1),
+            max_discount: Decimal256::percent(20),
+        }),
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the queue fills 200,000 / (0.5 * 0.99) = 404,040 of the collateral
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, liquidation_msg).unwrap();
+    assert!(res.attributes.contains(&attr("auction_amount", "595960")));
+
+    let auctions_response: AuctionsResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Auctions {
+                collateral_token: "asset0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        auctions_response.auctions,
+        vec![AuctionResponse {
+            idx: Uint128::from(1u128),
+            collateral_token: "asset0000".to_string(),
+            borrower: "borrower0000".to_string(),
+            amount: Uint256::from(595960u64),
+            start_height: mock_env().block.height,
+            discount: Decimal256::percent(5),
+        }]
+    );
+
+    // 10 blocks later the discount is 15%, price 0.5 * 0.85 = 0.425
+    // collateral 200,000 / 0.425 = 470,588 for 199,999
+    let msg = ExecuteMsg::BuyAuction {
+        collateral_token: "asset0000".to_string(),
+        auction_idx: Uint128::from(1u128),
+    };
+    let info = mock_info(
+        "keeper0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(200000u128),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.height += 10;
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "keeper0000".to_string(),
+                    amount: Uint128::from(470588u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "repay0000".to_string(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(198000u128),
+                }],
+                msg: to_json_binary(&MarketExecuteMsg::RepayStableFromAuction {
+                    borrower: "borrower0000".to_string(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(1999u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "keeper0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(1u128),
+                }],
+            })),
+        ]
+    );
+
+    // a liquidation can't leave collateral to the auction without a borrower to repay
+    let info = mock_info("asset0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "custody0000".to_string(),
+            amount: Uint128::from(1000000u128),
+            msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+                liquidator: "liquidator00000".to_string(),
+                fee_address: Some("fee0000".to_string()),
+                repay_address: Some("repay0000".to_string()),
+                borrower: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Not enough bids to execute this liquidation")
+    );
+}
+
+#[test]
+fn dex_fallback() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(200000u128),
+        }],
+    );
+    let env = mock_env();
+    let wait_end = env.block.time.plus_seconds(60u64);
+    execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::ActivateBids {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: Some(vec![Uint128::from(1u128)]),
+    };
+    let mut env = mock_env();
+    env.block.time = wait_end;
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let liquidation_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+
+    let msg = ExecuteMsg::UpdateDexFallback {
+        collateral_token: "asset0000".to_string(),
+        enabled: true,
+    };
+    let info = mock_info("owner0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("Swap is not configured"));
+
+    let swap_config_msg = ExecuteMsg::UpdateSwapConfig {
+        astroport_addr: "astroport0000".to_string(),
+        phoenix_addr: "phoenix0000".to_string(),
+        terraswap_addr: "terraswap0000".to_string(),
+        max_slippage: Decimal256::percent(5),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), swap_config_msg).unwrap();
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the queue fills 404,040 of the collateral, the rest is sold
+    // minimum receive: 595,960 * 0.5 * 0.95 = 283,081
+    deps.querier
+        .with_swap_rates(&[("phoenix0000", Decimal256::percent(49))]);
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, liquidation_msg.clone()).unwrap();
+    assert!(res.attributes.contains(&attr("dex_amount", "595960")));
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "asset0000".to_string(),
+                    funds: vec![],
+                    msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                        contract: "phoenix0000".to_string(),
+                        amount: Uint128::from(595960u128),
+                        msg: to_json_binary(&AstroportExecuteMsg::ExecuteSwapOperations {
+                            operations: vec![SwapOperation::TokenSwap {
+                                offer_asset_info: AssetInfo::Token {
+                                    contract_addr: Addr::unchecked("asset0000"),
+                                },
+                                ask_asset_info: AssetInfo::NativeToken {
+                                    denom: "uusd".to_string(),
+                                },
+                            }],
+                            minimum_receive: Some(Uint128::from(283081u128)),
+                            to: Some(MOCK_CONTRACT_ADDR.to_string()),
+                            max_spread: Some(Decimal::percent(5)),
+                        })
+                        .unwrap(),
+                    })
+                    .unwrap(),
+                }),
+                2
+            ),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "repay0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(198000u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(2000u128),
+                }],
+            })),
+        ]
+    );
+
+    // the proceeds of the sale pay the queue fees like the bids
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(290000u128),
+        }],
+    );
+    let reply_msg = Reply {
+        id: 2,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "settle_dex_liquidation"),
+            attr("collateral_token", "asset0000"),
+            attr("dex_amount", "595960"),
+            attr("repay_amount", "287100"),
+            attr("bid_fee", "2900"),
+            attr("liquidator_fee", "0"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "repay0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(287100u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(2900u128),
+                }],
+            })),
+        ]
+    );
+
+    // a failed sale without a Dutch auction can't be settled
+    let info = mock_info("asset0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, liquidation_msg).unwrap();
+    let reply_msg = Reply {
+        id: 2,
+        result: SubMsgResult::Err("slippage".to_string()),
+    };
+    let err = reply(deps.as_mut(), mock_env(), reply_msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Not enough bids to execute this liquidation")
+    );
+
+    // a failed sale leaves the collateral to the Dutch auction
+    let msg = ExecuteMsg::UpdateDutchAuction {
+        collateral_token: "asset0000".to_string(),
+        dutch_auction: Some(DutchAuction {
+            start_discount: Decimal256::percent(5),
+            discount_per_block: Decimal256::percent(1),
+            max_discount: Decimal256::percent(20),
+        }),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let liquidation_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: Some("borrower0000".to_string()),
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, liquidation_msg).unwrap();
+    assert!(res.attributes.contains(&attr("dex_amount", "1000000")));
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert!(res.attributes.contains(&attr("auction_amount", "1000000")));
+
+    let auctions_response: AuctionsResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Auctions {
+                collateral_token: "asset0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        auctions_response.auctions,
+        vec![AuctionResponse {
+            idx: Uint128::from(1u128),
+            collateral_token: "asset0000".to_string(),
+            borrower: "borrower0000".to_string(),
+            amount: Uint256::from(1000000u64),
+            start_height: mock_env().block.height,
+            discount: Decimal256::percent(5),
+        }]
+    );
+}
+
+#[test]
+fn age_priority() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(100000000u64), // to get instant activation
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let submit_bid = |deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+                      bidder: &str| {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_slot: 0u8,
+        };
+        let info = mock_info(
+            bidder,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    };
+
+    // pro-rata bids must be consumed before switching
+    submit_bid(&mut deps, "addr0000");
+    let msg = ExecuteMsg::UpdateAgePriority {
+        collateral_token: "asset0000".to_string(),
+        enabled: true,
+        min_bid_amount: Some(Uint256::from(100000u64)),
+    };
+    let info = mock_info("owner0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Age priority can only be enabled without active bids")
+    );
+
+    let retract_msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(1u128),
+        amount: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        retract_msg,
+    )
+    .unwrap();
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    submit_bid(&mut deps, "addr0001");
+    submit_bid(&mut deps, "addr0002");
+    submit_bid(&mut deps, "addr0003");
+
+    // the oldest bid is consumed first, the next one partially
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1200000u128),
+        msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for (bid_idx, amount, pending_liquidated_collateral) in [
+        (2u128, 0u64, 1000000u64),
+        (3u128, 800000u64, 200000u64),
+        (4u128, 1000000u64, 0u64),
+    ] {
+        let bid_response: BidResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Bid {
+                    bid_idx: Uint128::from(bid_idx),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(bid_response.amount, Uint256::from(amount));
+        assert_eq!(
+            bid_response.pending_liquidated_collateral,
+            Uint256::from(pending_liquidated_collateral)
+        );
+    }
+
+    let msg = ExecuteMsg::ClaimLiquidations {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: None,
+    };
+    let info = mock_info("addr0002", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("collateral_amount", "200000")));
+
+    let msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(3u128),
+        amount: None,
+    };
+    let info = mock_info("addr0002", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0002".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(800000u128),
+            }],
+        }))]
+    );
+}
+
+#[test]
+fn age_priority_small_bids() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(100000000u64), // to get instant activation
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateAgePriority {
+        collateral_token: "asset0000".to_string(),
+        enabled: true,
+        min_bid_amount: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Age priority requires a non-zero minimum bid amount")
+    );
+
+    let msg = ExecuteMsg::UpdateAgePriority {
+        collateral_token: "asset0000".to_string(),
+        enabled: true,
+        min_bid_amount: Some(Uint256::from(100000u64)),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let submit_bid = |deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+                      premium_slot: u8,
+                      amount: u128| {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_slot,
+        };
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg)
+    };
+
+    let err = submit_bid(&mut deps, 0u8, 99999u128).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Bid amount is lower than the minimum bid amount (100000)")
+    );
+
+    // 15 bids of the minimum amount in slot 0, a larger one in slot 1
+    for _ in 0..15 {
+        submit_bid(&mut deps, 0u8, 100000u128).unwrap();
+    }
+    submit_bid(&mut deps, 1u8, 1000000u128).unwrap();
+
+    // a partial retract can't leave a bid under the minimum
+    let msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(15u128),
+        amount: Some(Uint256::from(50000u64)),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Bid amount is lower than the minimum bid amount (100000)")
+    );
+
+    // slot 0 settles 10 bids for 1,000,000 of the collateral, the
+    // remaining 200,000 falls through to slot 1 at 0.99
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1200000u128),
+        msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("repay_amount", "1186020")));
+
+    for bid_idx in 1u128..17u128 {
+        let bid_response: BidResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Bid {
+                    bid_idx: Uint128::from(bid_idx),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let (amount, pending_liquidated_collateral) = match bid_idx {
+            1..=10 => (0u64, 100000u64),
+            11..=15 => (100000u64, 0u64),
+            _ => (802000u64, 200000u64),
+        };
+        assert_eq!(bid_response.amount, Uint256::from(amount));
+        assert_eq!(
+            bid_response.pending_liquidated_collateral,
+            Uint256::from(pending_liquidated_collateral)
+        );
+    }
+}
+
+#[test]
+fn age_priority_rounding() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(100000000u64), // to get instant activation
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let submit_bid = |deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+                      bidder: &str,
+                      amount: u128| {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_slot: 0u8,
+        };
+        let info = mock_info(
+            bidder,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    };
+    let liquidate = |deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+                     amount: u128| {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "custody0000".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+                liquidator: "liquidator00000".to_string(),
+                fee_address: Some("fee0000".to_string()),
+                repay_address: Some("repay0000".to_string()),
+                borrower: None,
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+    };
+    let query_bid = |deps: &OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>, bid_idx: u128| {
+        let bid_response: BidResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Bid {
+                    bid_idx: Uint128::from(bid_idx),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        (
+            bid_response.amount,
+            bid_response.pending_liquidated_collateral,
+        )
+    };
+    let oldest_active_bid = |deps: &OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>| {
+        read_oldest_active_bid(
+            deps.as_ref().storage,
+            &deps.api.addr_canonicalize("asset0000").unwrap(),
+            0u8,
+        )
+        .unwrap()
+        .map(|bid| bid.idx)
+    };
+
+    // the pro-rata bid consumed before the switch is left out of the fill
+    submit_bid(&mut deps, "addr0000", 1000000u128);
+    liquidate(&mut deps, 1000000u128);
+    assert_eq!(oldest_active_bid(&deps), Some(Uint128::from(1u128)));
+
+    let msg = ExecuteMsg::UpdateAgePriority {
+        collateral_token: "asset0000".to_string(),
+        enabled: true,
+        min_bid_amount: Some(Uint256::from(100000u64)),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(oldest_active_bid(&deps), None);
+
+    let msg = ExecuteMsg::ClaimLiquidations {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("collateral_amount", "1000000")));
+
+    // 100001 / 3 = 33333 collateral for 99999, the 2 left are retractable
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(3u128, 1u128),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    submit_bid(&mut deps, "addr0001", 100001u128);
+    submit_bid(&mut deps, "addr0002", 100000u128);
+    liquidate(&mut deps, 40000u128);
+    assert_eq!(
+        query_bid(&deps, 2u128),
+        (Uint256::from(2u64), Uint256::from(33333u64))
+    );
+    assert_eq!(
+        query_bid(&deps, 3u128),
+        (Uint256::from(79999u64), Uint256::from(6667u64))
+    );
+    assert_eq!(oldest_active_bid(&deps), Some(Uint128::from(3u128)));
+
+    // a claim indexes the bid again, the fill skips it for buying no collateral
+    let msg = ExecuteMsg::ClaimLiquidations {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(oldest_active_bid(&deps), Some(Uint128::from(2u128)));
+
+    liquidate(&mut deps, 1000u128);
+    assert_eq!(
+        query_bid(&deps, 2u128),
+        (Uint256::from(2u64), Uint256::zero())
+    );
+    assert_eq!(
+        query_bid(&deps, 3u128),
+        (Uint256::from(76999u64), Uint256::from(7667u64))
+    );
+    assert_eq!(oldest_active_bid(&deps), Some(Uint128::from(3u128)));
+
+    let msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(2u128),
+        amount: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(2u128),
+            }],
+        }))]
+    );
+}
+
+#[test]
+fn auto_claim() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000000u64),
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // bids are activated right away, under the bid threshold
+    for bidder in ["addr0000", "addr0001"] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_slot: 1u8,
+        };
+        let info = mock_info(
+            bidder,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // selling the collateral requires the swap config
+    let msg = ExecuteMsg::SetAutoClaim {
+        bid_idx: Uint128::from(2u128),
+        auto_claim: Some(AutoClaim::Stable),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("Swap is not configured"));
+
+    let swap_config_msg = ExecuteMsg::UpdateSwapConfig {
+        astroport_addr: "astroport0000".to_string(),
+        phoenix_addr: "phoenix0000".to_string(),
+        terraswap_addr: "terraswap0000".to_string(),
+        max_slippage: Decimal256::percent(5),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, swap_config_msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, swap_config_msg).unwrap();
+
+    let info = mock_info("addr0001", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only the bidder can set the auto claim of a bid
+    let msg = ExecuteMsg::SetAutoClaim {
+        bid_idx: Uint128::from(1u128),
+        auto_claim: Some(AutoClaim::Collateral),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let bid_response: BidResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Bid {
+                bid_idx: Uint128::from(1u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bid_response.auto_claim, Some(AutoClaim::Collateral));
+
+    // each bid gets 500,000 collateral, delivered to addr0000 right away
+    let info = mock_info("asset0000", &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(500000u128),
+            })
+            .unwrap(),
+        }))
+    );
+    assert!(res
+        .attributes
+        .contains(&attr("auto_claim_amount", "500000")));
+
+    // a keeper sells the collateral of addr0001 on the DEX with the best return
+    // minimum receive: 500,000 * 0.5 * 0.95 = 237,500
+    deps.querier.with_swap_rates(&[
+        ("astroport0000", Decimal256::percent(48)),
+        ("phoenix0000", Decimal256::percent(49)),
+    ]);
+    let msg = ExecuteMsg::ClaimFor {
+        bidder: "addr0001".to_string(),
+        collateral_token: "asset0000".to_string(),
+    };
+    let info = mock_info("keeper0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                contract: "phoenix0000".to_string(),
+                amount: Uint128::from(500000u128),
+                msg: to_json_binary(&AstroportExecuteMsg::ExecuteSwapOperations {
+                    operations: vec![SwapOperation::TokenSwap {
+                        offer_asset_info: AssetInfo::Token {
+                            contract_addr: Addr::unchecked("asset0000"),
+                        },
+                        ask_asset_info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                    }],
+                    minimum_receive: Some(Uint128::from(237500u128)),
+                    to: Some("addr0001".to_string()),
+                    max_spread: Some(Decimal::percent(5)),
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_for"),
+            attr("bidder", "addr0001"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "0"),
+            attr("swap_amount", "500000"),
+        ]
+    );
+
+    // nothing left to deliver
+    let msg = ExecuteMsg::ClaimFor {
+        bidder: "addr0000".to_string(),
+        collateral_token: "asset0000".to_string(),
+    };
+    let info = mock_info("keeper0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.messages.is_empty());
+
+    // sales below the max slippage are rejected
+    let info = mock_info("asset0000", &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_swap_rates(&[("terraswap0000", Decimal256::percent(40))]);
+    let msg = ExecuteMsg::ClaimFor {
+        bidder: "addr0001".to_string(),
+        collateral_token: "asset0000".to_string(),
+    };
+    let info = mock_info("keeper0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Swap return is below the max slippage versus the oracle price")
+    );
+}
+
+#[test]
+fn auto_claim_consumed_slots() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000000u64),
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateSwapConfig {
+        astroport_addr: "astroport0000".to_string(),
+        phoenix_addr: "phoenix0000".to_string(),
+        terraswap_addr: "terraswap0000".to_string(),
+        max_slippage: Decimal256::percent(5),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the oldest auto-claim bids are in a slot the liquidation doesn't reach,
+    // or sell their collateral to stable
+    let bids = [
+        ("addr0002", 10u8, AutoClaim::Collateral),
+        ("addr0001", 1u8, AutoClaim::Stable),
+        ("addr0000", 1u8, AutoClaim::Collateral),
+    ];
+    let mut bid_idx = 0u128;
+    for (bidder, premium_slot, auto_claim) in bids {
+        for _ in 0..12 {
+            let msg = ExecuteMsg::SubmitBid {
+                collateral_token: "asset0000".to_string(),
+                premium_slot,
+            };
+            let info = mock_info(
+                bidder,
+                &[Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100000u128),
+                }],
+            );
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            bid_idx += 1;
+
+            let msg = ExecuteMsg::SetAutoClaim {
+                bid_idx: Uint128::from(bid_idx),
+                auto_claim: Some(auto_claim),
+            };
+            let info = mock_info(bidder, &[]);
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+    }
+
+    // each bid of slot 1 gets 1,000,000 / 24 = 41,666.67 collateral,
+    // the collateral of 10 bids of addr0000 is delivered right away
+    let info = mock_info("asset0000", &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let auto_claims: Vec<String> = res
+        .messages
+        .iter()
+        .filter_map(|msg| match &msg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) if contract_addr == "asset0000" => match from_json(msg).unwrap() {
+                Cw20ExecuteMsg::Transfer { recipient, .. } => Some(recipient),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    assert_eq!(auto_claims, vec!["addr0000".to_string(); 10]);
+    assert!(res
+        .attributes
+        .contains(&attr("auto_claim_amount", "416666")));
+
+    // the last bids of addr0000 are left to claim
+    let msg = ExecuteMsg::ClaimLiquidations {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: Some(vec![Uint128::from(35u128), Uint128::from(36u128)]),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("collateral_amount", "83333")));
+}
+
+#[test]
+fn auto_claim_waiting_bids() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(1000000u64),
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the first bid is activated right away, the next ones wait
+    let mut bids = vec![("addr0000", 1000000u128)];
+    bids.extend(vec![("addr0001", 100000u128); 12]);
+    for (bid_idx, (bidder, amount)) in bids.into_iter().enumerate() {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_slot: 1u8,
+        };
+        let info = mock_info(
+            bidder,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::SetAutoClaim {
+            bid_idx: Uint128::from(bid_idx as u128 + 1),
+            auto_claim: Some(AutoClaim::Collateral),
+        };
+        let info = mock_info(bidder, &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // waiting bids are left out of the auto-claims of the slot
+    let auto_claim_bids = read_collateral_auto_claim_bids(
+        deps.as_ref().storage,
+        &deps.api.addr_canonicalize("asset0000").unwrap(),
+        1u8,
+        None,
+        30,
+    )
+    .unwrap();
+    assert_eq!(auto_claim_bids.len(), 1);
+    assert_eq!(auto_claim_bids[0].idx, Uint128::from(1u128));
+
+    let liquidate = |deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>| {
+        let info = mock_info("asset0000", &[]);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "custody0000".to_string(),
+            amount: Uint128::from(100000u128),
+            msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+                liquidator: "liquidator00000".to_string(),
+                fee_address: Some("fee0000".to_string()),
+                repay_address: Some("repay0000".to_string()),
+                borrower: None,
+            })
+            .unwrap(),
+        });
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        res.messages
+            .iter()
+            .filter_map(|msg| match &msg.msg {
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }) if contract_addr == "asset0000" => match from_json(msg).unwrap() {
+                    Cw20ExecuteMsg::Transfer { recipient, .. } => Some(recipient),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(liquidate(&mut deps), vec!["addr0000".to_string()]);
+
+    // once activated the bids are auto-claimed, up to the bids visited
+    // by a liquidation
+    let msg = ExecuteMsg::ActivateBids {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: None,
+    };
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(60u64);
+    execute(deps.as_mut(), env, mock_info("addr0001", &[]), msg).unwrap();
+
+    let mut recipients = vec!["addr0000".to_string()];
+    recipients.extend(vec!["addr0001".to_string(); 9]);
+    assert_eq!(liquidate(&mut deps), recipients);
+}
+
+#[test]
+fn vault() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(3505000u128),
+    }]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000000u64),
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateSwapConfig {
+        astroport_addr: "astroport0000".to_string(),
+        phoenix_addr: "phoenix0000".to_string(),
+        terraswap_addr: "terraswap0000".to_string(),
+        max_slippage: Decimal256::percent(5),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the deposits are bid by a single vault bid
+    for depositor in ["addr0000", "addr0001"] {
+        let msg = ExecuteMsg::DepositVault {
+            collateral_token: "asset0000".to_string(),
+            premium_slot: 1u8,
+        };
+        let info = mock_info(
+            depositor,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+        );
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(res.attributes.contains(&attr("shares", "1000000")));
+    }
+
+    let bids_response: BidsResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidsByUser {
+                collateral_token: "asset0000".to_string(),
+                bidder: MOCK_CONTRACT_ADDR.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bids_response.bids.len(), 1);
+    assert_eq!(bids_response.bids[0].amount, Uint256::from(2000000u64));
+
+    // required_stable 495,000
+    let info = mock_info("asset0000", &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_json_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let vault_response: VaultResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Vault {
+                collateral_token: "asset0000".to_string(),
+                premium_slot: 1u8,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        vault_response,
+        VaultResponse {
+            total_shares: Uint256::from(2000000u64),
+            bid_amount: Uint256::from(1505000u64),
+            collateral_amount: Uint256::from(1000000u64),
+            share_token: None,
+        }
+    );
+
+    // vault value: 1,505,000 + 1,000,000 * 0.5 = 2,005,000
+    // shares: 1,000,000 * 2,000,000 / 2,005,000 = 997,506
+    let msg = ExecuteMsg::DepositVault {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    };
+    let info = mock_info(
+        "addr0002",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("shares", "997506")));
+
+    // the collateral is sold to the vault, then bid again
+    // minimum receive: 1,000,000 * 0.5 * 0.95 = 475,000
+    deps.querier
+        .with_swap_rates(&[("phoenix0000", Decimal256::percent(49))]);
+    let msg = ExecuteMsg::CompoundVault {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    };
+    let info = mock_info("keeper0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                    contract: "phoenix0000".to_string(),
+                    amount: Uint128::from(1000000u128),
+                    msg: to_json_binary(&AstroportExecuteMsg::ExecuteSwapOperations {
+                        operations: vec![SwapOperation::TokenSwap {
+                            offer_asset_info: AssetInfo::Token {
+                                contract_addr: Addr::unchecked("asset0000"),
+                            },
+                            ask_asset_info: AssetInfo::NativeToken {
+                                denom: "uusd".to_string(),
+                            },
+                        }],
+                        minimum_receive: Some(Uint128::from(475000u128)),
+                        to: Some(MOCK_CONTRACT_ADDR.to_string()),
+                        max_spread: Some(Decimal::percent(5)),
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_json_binary(&ExecuteMsg::RebidVault {
+                    collateral_token: "asset0000".to_string(),
+                    premium_slot: 1u8,
+                    prev_balance: Uint256::from(3505000u64),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(3995000u128),
+        }],
+    );
+    let msg = ExecuteMsg::RebidVault {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+        prev_balance: Uint256::from(3505000u64),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let vault_response: VaultResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Vault {
+                collateral_token: "asset0000".to_string(),
+                premium_slot: 1u8,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        vault_response,
+        VaultResponse {
+            total_shares: Uint256::from(2997506u64),
+            bid_amount: Uint256::from(2995000u64),
+            collateral_amount: Uint256::zero(),
+            share_token: None,
+        }
+    );
+
+    // 2,995,000 * 1,000,000 / 2,997,506 = 999,163
+    let msg = ExecuteMsg::WithdrawVault {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+        shares: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(999163u128),
+            }],
+        }))]
+    );
+
+    let shares_response: VaultSharesResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::VaultShares {
+                collateral_token: "asset0000".to_string(),
+                premium_slot: 1u8,
+                depositor: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(shares_response.shares, Uint256::zero());
+}
+
+#[test]
+fn vault_token() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000000u64),
+        premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::CreateVaultToken {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+        token_code_id: 123u64,
+        name: "Vault Share".to_string(),
+        symbol: "VSHARE".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                admin: None,
+                code_id: 123u64,
+                funds: vec![],
+                label: "vault share".to_string(),
+                msg: to_json_binary(&TokenInstantiateMsg {
+                    name: "Vault Share".to_string(),
+                    symbol: "VSHARE".to_string(),
+                    decimals: 6u8,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: MOCK_CONTRACT_ADDR.to_string(),
+                        cap: None,
+                    }),
+                    marketing: None,
+                })
+                .unwrap(),
+            }),
+            1
+        )]
+    );
+
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("share0000".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // shares are minted to the depositor
+    let msg = ExecuteMsg::DepositVault {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "share0000".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(1000000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let vault_response: VaultResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Vault {
+                collateral_token: "asset0000".to_string(),
+                premium_slot: 1u8,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(vault_response.share_token, Some("share0000".to_string()));
+
+    let msg = ExecuteMsg::WithdrawVault {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+        shares: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Vault shares are tokenized, send them with the WithdrawVault hook")
+    );
+
+    // only the share token can redeem
+    let hook_msg = to_json_binary(&Cw20HookMsg::WithdrawVault {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    })
+    .unwrap();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(400000u128),
+        msg: hook_msg,
+    });
+    let info = mock_info("asset0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    // the holder redeems the transferred shares, which are burnt
+    let info = mock_info("share0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(400000u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "share0000".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(400000u128),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+}
+
+#[test]
+fn premium_rates() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"token0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+        stable_asset: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "token0000".to_string(),
+        max_slot: 3u8,
+        bid_threshold: Uint256::from(10000u128),
+        premium_rate_per_slot: Decimal256::percent(3),
+        premium_rates: Some(vec![
+            Decimal256::zero(),
+            Decimal256::permille(5),
+            Decimal256::percent(1),
+        ]),
+    };
+    let info = mock_info("owner0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Premium rates must be set for each slot up to the max slot")
+    );
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "token0000".to_string(),
+        max_slot: 3u8,
+        bid_threshold: Uint256::from(10000u128),
+        premium_rate_per_slot: Decimal256::percent(3),
+        premium_rates: Some(vec![
+            Decimal256::zero(),
+            Decimal256::permille(5),
+            Decimal256::percent(1),
+            Decimal256::percent(5),
+        ]),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "token0000".to_string(),
+        premium_slot: 2u8,
+    };
+    let bid_info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), bid_info, msg).unwrap();
+
+    let query_premium_rate = |deps: Deps| -> Decimal256 {
+        let bid_pool_response: BidPoolResponse = from_json(
+            query(
+                deps,
+                mock_env(),
+                QueryMsg::BidPool {
+                    collateral_token: "token0000".to_string(),
+                    bid_slot: 2u8,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        bid_pool_response.premium_rate
+    };
+    assert_eq!(query_premium_rate(deps.as_ref()), Decimal256::percent(1));
+
+    // the existing bid pool takes the new premium rate of its slot
+    let msg = ExecuteMsg::UpdateCollateralInfo {
+        collateral_token: "token0000".to_string(),
+        bid_threshold: None,
+        max_slot: None,
+        premium_rates: Some(vec![
+            Decimal256::zero(),
+            Decimal256::percent(1),
+            Decimal256::percent(2),
+            Decimal256::percent(10),
+        ]),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(query_premium_rate(deps.as_ref()), Decimal256::percent(2));
+
+    // a shorter max slot needs a new ladder
+    let msg = ExecuteMsg::UpdateCollateralInfo {
+        collateral_token: "token0000".to_string(),
+        bid_threshold: None,
+        max_slot: Some(2u8),
+        premium_rates: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Premium rates must be set for each slot up to the max slot")
+    );
+
+    // back to the linear premium
+    let msg = ExecuteMsg::UpdateCollateralInfo {
+        collateral_token: "token0000".to_string(),
+        bid_threshold: None,
+        max_slot: None,
+        premium_rates: Some(vec![]),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(query_premium_rate(deps.as_ref()), Decimal256::percent(6));
+
+    let collateral_info_response: CollateralInfoResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::CollateralInfo {
+                collateral_token: "token0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(collateral_info_response.premium_rates, None);
+}
+
 #[test]
 fn update_collateral_info() {
     let mut deps = mock_dependencies(&[]);
@@ -903,6 +3215,7 @@ fn update_collateral_info() {
         price_timeframe: 60u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
+        stable_asset: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -913,6 +3226,7 @@ fn update_collateral_info() {
         max_slot: 30u8,
         bid_threshold: Uint256::from(10000u128),
         premium_rate_per_slot: Decimal256::percent(1),
+        premium_rates: None,
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -921,6 +3235,7 @@ fn update_collateral_info() {
         collateral_token: "token0000".to_string(),
         bid_threshold: Some(Uint256::from(20000u128)),
         max_slot: Some(20u8),
+        premium_rates: None,
     };
 
     // unauthorized attempt
@@ -951,6 +3266,7 @@ fn update_collateral_info() {
             max_slot: 20u8,                          // updated max_slot
             bid_threshold: Uint256::from(20000u128), // updated bid threshold
             premium_rate_per_slot: Decimal256::percent(1),
+            premium_rates: None,
         }
     );
 }
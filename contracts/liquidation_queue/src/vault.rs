@@ -0,0 +1,643 @@
+use crate::asserts::{assert_activate_status, assert_withdraw_amount};
+use crate::bid::{
+    assert_min_bid_amount, calculate_liquidated_collateral, calculate_remaining_bid, claim_bid,
+    collateral_transfer_msg, place_bid, process_bid_activation,
+};
+use crate::querier::query_collateral_price;
+use crate::state::{
+    read_age_priority, read_bid, read_bid_pool, read_collateral_info, read_config,
+    read_stable_asset, read_swap_config, read_total_bids, read_vault, read_vault_shares,
+    remove_bid, store_bid, store_bid_pool, store_pending_vault_token, store_total_bids,
+    store_vault, store_vault_shares, take_pending_vault_token, Bid, CollateralInfo, Config, Vault,
+};
+use crate::swap::create_swap_msg;
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, CanonicalAddr, CosmosMsg, Decimal256, Deps, DepsMut, Env,
+    MessageInfo, Response, StdError, StdResult, Storage, SubMsg, Uint256, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, MinterResponse};
+use cw20_base::msg::InstantiateMsg as TokenInstantiateMsg;
+use moneymarket::astroport_router::AssetInfo;
+use moneymarket::custody::Asset;
+use moneymarket::liquidation_queue::{ExecuteMsg, VaultResponse, VaultSharesResponse};
+use moneymarket::querier::query_all_token_types_balance;
+use std::convert::TryInto;
+
+pub const VAULT_TOKEN_REPLY_ID: u64 = 1;
+
+/// Stable asset is deposited in the vault of a premium slot. Shares are minted
+/// at the vault value, the collateral not sold yet is valued at the oracle price
+pub fn deposit_vault(
+    deps: DepsMut,
+    env: Env,
+    depositor: Addr,
+    collateral_token: String,
+    premium_slot: u8,
+    amount: Uint256,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_info: CollateralInfo =
+        read_collateral_info(deps.storage, &collateral_token_raw)?;
+    let vault_bidder = deps.api.addr_canonicalize(env.contract.address.as_str())?;
+    assert_min_bid_amount(deps.storage, &collateral_token_raw, amount)?;
+
+    let mut vault: Vault = read_vault(deps.storage, &collateral_token_raw, premium_slot)?;
+    settle_vault(deps.storage, &mut vault)?;
+
+    let shares = if vault.total_shares.is_zero() {
+        amount
+    } else {
+        let mut vault_value = vault
+            .bids_idx
+            .iter()
+            .map(|idx| Ok(read_bid(deps.storage, *idx)?.amount))
+            .sum::<StdResult<Uint256>>()?;
+        if !vault.collateral.is_zero() {
+            let price =
+                query_collateral_price(deps.as_ref(), &env, &config, collateral_token.clone())?;
+            vault_value += vault.collateral * price;
+        }
+        if vault_value.is_zero() {
+            return Err(StdError::generic_err("Vault has no value left"));
+        }
+
+        amount.multiply_ratio(vault.total_shares, vault_value)
+    };
+    if shares.is_zero() {
+        return Err(StdError::generic_err("Deposit amount is too small"));
+    }
+
+    add_vault_bid(
+        deps.storage,
+        &env,
+        &config,
+        &collateral_info,
+        &vault_bidder,
+        &mut vault,
+        premium_slot,
+        amount,
+    )?;
+    vault.total_shares += shares;
+    store_vault(deps.storage, &collateral_token_raw, premium_slot, &vault)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let Some(share_token) = vault.share_token {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&share_token)?.to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+                recipient: depositor.to_string(),
+                amount: shares.try_into()?,
+            })?,
+        }));
+    } else {
+        let depositor_raw = deps.api.addr_canonicalize(depositor.as_str())?;
+        let depositor_shares = read_vault_shares(
+            deps.storage,
+            &collateral_token_raw,
+            premium_slot,
+            &depositor_raw,
+        )?;
+        store_vault_shares(
+            deps.storage,
+            &collateral_token_raw,
+            premium_slot,
+            &depositor_raw,
+            depositor_shares + shares,
+        )?;
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "deposit_vault"),
+        attr("collateral_token", collateral_token),
+        attr("premium_slot", premium_slot.to_string()),
+        attr("amount", amount),
+        attr("shares", shares),
+    ]))
+}
+
+/// Depositors redeem their shares for the same part of the vault bids
+/// and of the collateral not sold yet
+pub fn withdraw_vault(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: String,
+    premium_slot: u8,
+    shares: Option<Uint256>,
+) -> StdResult<Response> {
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let depositor_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    let vault: Vault = read_vault(deps.storage, &collateral_token_raw, premium_slot)?;
+    if vault.share_token.is_some() {
+        return Err(StdError::generic_err(
+            "Vault shares are tokenized, send them with the WithdrawVault hook",
+        ));
+    }
+
+    let depositor_shares = read_vault_shares(
+        deps.storage,
+        &collateral_token_raw,
+        premium_slot,
+        &depositor_raw,
+    )?;
+    let shares = assert_withdraw_amount(shares, depositor_shares)?;
+    if shares.is_zero() {
+        return Err(StdError::generic_err("No vault shares to withdraw"));
+    }
+    store_vault_shares(
+        deps.storage,
+        &collateral_token_raw,
+        premium_slot,
+        &depositor_raw,
+        depositor_shares - shares,
+    )?;
+
+    redeem_vault_shares(deps, collateral_token, premium_slot, info.sender, shares)
+}
+
+/// Share token holders redeem the tokens sent to the contract, which are burnt
+/// Executor: vault share token
+pub fn withdraw_vault_tokens(
+    deps: DepsMut,
+    share_token: Addr,
+    redeemer: Addr,
+    collateral_token: String,
+    premium_slot: u8,
+    shares: Uint256,
+) -> StdResult<Response> {
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let vault: Vault = read_vault(deps.storage, &collateral_token_raw, premium_slot)?;
+    if vault.share_token != Some(deps.api.addr_canonicalize(share_token.as_str())?) {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let res = redeem_vault_shares(deps, collateral_token, premium_slot, redeemer, shares)?;
+
+    Ok(res.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: share_token.to_string(),
+        funds: vec![],
+        msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+            amount: shares.try_into()?,
+        })?,
+    })))
+}
+
+/// Owner instantiates the share token of a vault, before any deposit so that
+/// all the shares are tokens
+#[allow(clippy::too_many_arguments)]
+pub fn create_vault_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: String,
+    premium_slot: u8,
+    token_code_id: u64,
+    name: String,
+    symbol: String,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_info: CollateralInfo =
+        read_collateral_info(deps.storage, &collateral_token_raw)?;
+    if premium_slot > collateral_info.max_slot {
+        return Err(StdError::generic_err("Invalid premium slot"));
+    }
+
+    let vault: Vault = read_vault(deps.storage, &collateral_token_raw, premium_slot)?;
+    if vault.share_token.is_some() {
+        return Err(StdError::generic_err("Vault share token already exists"));
+    }
+    if !vault.total_shares.is_zero() {
+        return Err(StdError::generic_err("Vault already has shares"));
+    }
+
+    store_pending_vault_token(deps.storage, &collateral_token_raw, premium_slot)?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                admin: None,
+                code_id: token_code_id,
+                funds: vec![],
+                label: "vault share".to_string(),
+                msg: to_json_binary(&TokenInstantiateMsg {
+                    name,
+                    symbol,
+                    decimals: 6u8,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: env.contract.address.to_string(),
+                        cap: None,
+                    }),
+                    marketing: None,
+                })?,
+            }),
+            VAULT_TOKEN_REPLY_ID,
+        ))
+        .add_attributes(vec![
+            attr("action", "create_vault_token"),
+            attr("collateral_token", collateral_token),
+            attr("premium_slot", premium_slot.to_string()),
+        ]))
+}
+
+/// Record the instantiated share token on its vault
+pub fn register_vault_token(deps: DepsMut, token_addr: Addr) -> StdResult<Response> {
+    let (collateral_token_raw, premium_slot) = take_pending_vault_token(deps.storage)?;
+    let mut vault: Vault = read_vault(deps.storage, &collateral_token_raw, premium_slot)?;
+    vault.share_token = Some(deps.api.addr_canonicalize(token_addr.as_str())?);
+    store_vault(deps.storage, &collateral_token_raw, premium_slot, &vault)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_vault_token"),
+        attr("share_token", token_addr),
+    ]))
+}
+
+/// Pay out `shares` of a vault, already deducted from the redeemer
+fn redeem_vault_shares(
+    deps: DepsMut,
+    collateral_token: String,
+    premium_slot: u8,
+    redeemer: Addr,
+    shares: Uint256,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+
+    let mut vault: Vault = read_vault(deps.storage, &collateral_token_raw, premium_slot)?;
+    settle_vault(deps.storage, &mut vault)?;
+
+    let mut available_bids: Uint256 =
+        read_total_bids(deps.storage, &collateral_token_raw).unwrap_or_default();
+    let mut stable_amount = Uint256::zero();
+    let mut bids_idx = vec![];
+    for bid_idx in vault.bids_idx.iter() {
+        let mut bid: Bid = read_bid(deps.storage, *bid_idx)?;
+        let withdraw_amount = bid.amount.multiply_ratio(shares, vault.total_shares);
+
+        // active bids leave the pool, the vault settled them at its snapshots
+        if bid.wait_end.is_none() {
+            let mut bid_pool = read_bid_pool(deps.storage, &collateral_token_raw, premium_slot)?;
+            bid_pool.total_bid_amount -= withdraw_amount;
+            store_bid_pool(deps.storage, &collateral_token_raw, premium_slot, &bid_pool)?;
+            available_bids -= withdraw_amount;
+        }
+
+        bid.amount -= withdraw_amount;
+        stable_amount += withdraw_amount;
+        if bid.amount.is_zero() {
+            remove_bid(deps.storage, bid.idx)?;
+        } else {
+            store_bid(deps.storage, bid.idx, &bid)?;
+            bids_idx.push(bid.idx);
+        }
+    }
+    store_total_bids(deps.storage, &collateral_token_raw, available_bids)?;
+
+    let collateral_amount = vault.collateral.multiply_ratio(shares, vault.total_shares);
+    vault.collateral -= collateral_amount;
+    vault.total_shares -= shares;
+    vault.bids_idx = bids_idx;
+    store_vault(deps.storage, &collateral_token_raw, premium_slot, &vault)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !stable_amount.is_zero() {
+        messages.push(
+            Asset {
+                amount: stable_amount.try_into()?,
+                asset_info: read_stable_asset(deps.storage, &config)?,
+            }
+            .to_msg(redeemer.clone())?,
+        );
+    }
+    if !collateral_amount.is_zero() {
+        messages.push(collateral_transfer_msg(
+            &collateral_token,
+            redeemer,
+            collateral_amount,
+        )?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "withdraw_vault"),
+        attr("collateral_token", collateral_token),
+        attr("premium_slot", premium_slot.to_string()),
+        attr("shares", shares),
+        attr("amount", stable_amount),
+        attr("collateral_amount", collateral_amount),
+    ]))
+}
+
+/// Anyone can compound a vault. The vault bids whose waiting period expired
+/// are activated and the liquidated collateral is sold on the DEX with the best
+/// return, within the max slippage versus the oracle price
+pub fn compound_vault(
+    deps: DepsMut,
+    env: Env,
+    collateral_token: String,
+    premium_slot: u8,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_info: CollateralInfo =
+        read_collateral_info(deps.storage, &collateral_token_raw)?;
+
+    let mut vault: Vault = read_vault(deps.storage, &collateral_token_raw, premium_slot)?;
+
+    let mut available_bids: Uint256 =
+        read_total_bids(deps.storage, &collateral_token_raw).unwrap_or_default();
+    for bid_idx in vault.bids_idx.iter() {
+        let mut bid: Bid = read_bid(deps.storage, *bid_idx)?;
+        if assert_activate_status(&bid, &env, available_bids, collateral_info.bid_threshold)
+            .is_err()
+        {
+            continue;
+        }
+
+        let mut bid_pool = read_bid_pool(deps.storage, &collateral_token_raw, premium_slot)?;
+        let amount_to_activate = bid.amount;
+        process_bid_activation(&mut bid, &mut bid_pool, amount_to_activate);
+        store_bid(deps.storage, bid.idx, &bid)?;
+        store_bid_pool(deps.storage, &collateral_token_raw, premium_slot, &bid_pool)?;
+
+        available_bids += amount_to_activate;
+    }
+    store_total_bids(deps.storage, &collateral_token_raw, available_bids)?;
+
+    settle_vault(deps.storage, &mut vault)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let swap_amount = vault.collateral;
+    if !swap_amount.is_zero() {
+        let swap_config = read_swap_config(deps.storage)?
+            .ok_or_else(|| StdError::generic_err("Swap is not configured"))?;
+        let price = query_collateral_price(deps.as_ref(), &env, &config, collateral_token.clone())?;
+        let minimum_receive = swap_amount * price * (Decimal256::one() - swap_config.max_slippage);
+
+        let stable_asset = read_stable_asset(deps.storage, &config)?;
+        let prev_balance = query_all_token_types_balance(
+            deps.as_ref(),
+            env.contract.address.clone(),
+            stable_asset.clone(),
+        )?;
+
+        messages.push(create_swap_msg(
+            deps.as_ref(),
+            &swap_config,
+            Asset {
+                amount: swap_amount.try_into()?,
+                asset_info: AssetInfo::Token {
+                    contract_addr: deps.api.addr_validate(&collateral_token)?,
+                },
+            },
+            stable_asset,
+            minimum_receive.try_into()?,
+            env.contract.address.to_string(),
+        )?);
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_json_binary(&ExecuteMsg::RebidVault {
+                collateral_token: collateral_token.clone(),
+                premium_slot,
+                prev_balance,
+            })?,
+        }));
+
+        vault.collateral = Uint256::zero();
+    }
+    store_vault(deps.storage, &collateral_token_raw, premium_slot, &vault)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "compound_vault"),
+        attr("collateral_token", collateral_token),
+        attr("premium_slot", premium_slot.to_string()),
+        attr("swap_amount", swap_amount),
+    ]))
+}
+
+/// Bid the proceeds of the collateral sale again, without minting shares
+/// Executor: itself
+pub fn rebid_vault(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: String,
+    premium_slot: u8,
+    prev_balance: Uint256,
+) -> StdResult<Response> {
+    if info.sender != env.contract.address {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_info: CollateralInfo =
+        read_collateral_info(deps.storage, &collateral_token_raw)?;
+    let vault_bidder = deps.api.addr_canonicalize(env.contract.address.as_str())?;
+
+    let cur_balance = query_all_token_types_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        read_stable_asset(deps.storage, &config)?,
+    )?;
+    let amount = cur_balance - prev_balance;
+
+    let mut vault: Vault = read_vault(deps.storage, &collateral_token_raw, premium_slot)?;
+    settle_vault(deps.storage, &mut vault)?;
+    if !amount.is_zero() {
+        add_vault_bid(
+            deps.storage,
+            &env,
+            &config,
+            &collateral_info,
+            &vault_bidder,
+            &mut vault,
+            premium_slot,
+            amount,
+        )?;
+    }
+    store_vault(deps.storage, &collateral_token_raw, premium_slot, &vault)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "rebid_vault"),
+        attr("collateral_token", collateral_token),
+        attr("premium_slot", premium_slot.to_string()),
+        attr("amount", amount),
+    ]))
+}
+
+/// Settle the vault bids against their pool. The liquidated collateral moves to
+/// the vault, consumed bids are dropped and the active bids are merged, as they
+/// now share the pool snapshots
+fn settle_vault(storage: &mut dyn Storage, vault: &mut Vault) -> StdResult<()> {
+    let mut active_bid: Option<Bid> = None;
+    let mut bids_idx = vec![];
+    for bid_idx in vault.bids_idx.iter() {
+        let bid: Bid = read_bid(storage, *bid_idx)?;
+        if bid.wait_end.is_some() {
+            bids_idx.push(bid.idx);
+            continue;
+        }
+
+        vault.collateral += claim_bid(storage, bid)?;
+        let bid = match read_bid(storage, *bid_idx) {
+            Ok(bid) => bid,
+            // the bid has been consumed
+            Err(_) => continue,
+        };
+
+        // with age priority merging would move stable ahead in the queue
+        if read_age_priority(storage, &bid.collateral_token)?.is_some() {
+            bids_idx.push(bid.idx);
+            continue;
+        }
+
+        match active_bid.as_mut() {
+            Some(active_bid) => {
+                active_bid.amount += bid.amount;
+                remove_bid(storage, bid.idx)?;
+            }
+            None => active_bid = Some(bid),
+        }
+    }
+
+    if let Some(active_bid) = active_bid {
+        store_bid(storage, active_bid.idx, &active_bid)?;
+        bids_idx.push(active_bid.idx);
+    }
+    vault.bids_idx = bids_idx;
+
+    Ok(())
+}
+
+/// Bid `amount` from a settled vault. The active vault bid is topped up when
+/// the amount can be activated right away, otherwise a new bid is placed
+#[allow(clippy::too_many_arguments)]
+fn add_vault_bid(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    collateral_info: &CollateralInfo,
+    vault_bidder: &CanonicalAddr,
+    vault: &mut Vault,
+    premium_slot: u8,
+    amount: Uint256,
+) -> StdResult<()> {
+    let available_bids: Uint256 =
+        read_total_bids(storage, &collateral_info.collateral_token).unwrap_or_default();
+    let active_bid: Option<Bid> = vault
+        .bids_idx
+        .iter()
+        .map(|idx| read_bid(storage, *idx))
+        .collect::<StdResult<Vec<Bid>>>()?
+        .into_iter()
+        .find(|bid| bid.wait_end.is_none());
+
+    match active_bid {
+        Some(bid)
+            if available_bids < collateral_info.bid_threshold
+                && read_age_priority(storage, &collateral_info.collateral_token)?.is_none() =>
+        {
+            let mut bid_pool =
+                read_bid_pool(storage, &collateral_info.collateral_token, premium_slot)?;
+            bid_pool.total_bid_amount += amount;
+            store_bid_pool(
+                storage,
+                &collateral_info.collateral_token,
+                premium_slot,
+                &bid_pool,
+            )?;
+            store_total_bids(
+                storage,
+                &collateral_info.collateral_token,
+                available_bids + amount,
+            )?;
+
+            store_bid(
+                storage,
+                bid.idx,
+                &Bid {
+                    amount: bid.amount + amount,
+                    ..bid
+                },
+            )
+        }
+        _ => {
+            let bid = place_bid(
+                storage,
+                env,
+                config,
+                collateral_info,
+                vault_bidder.clone(),
+                premium_slot,
+                amount,
+            )?;
+            vault.bids_idx.push(bid.idx);
+            Ok(())
+        }
+    }
+}
+
+pub fn query_vault(
+    deps: Deps,
+    collateral_token: String,
+    premium_slot: u8,
+) -> StdResult<VaultResponse> {
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let vault: Vault = read_vault(deps.storage, &collateral_token_raw, premium_slot)?;
+
+    let mut bid_amount = Uint256::zero();
+    let mut collateral_amount = vault.collateral;
+    for bid_idx in vault.bids_idx.iter() {
+        let bid: Bid = read_bid(deps.storage, *bid_idx)?;
+        if bid.wait_end.is_some() {
+            bid_amount += bid.amount;
+            continue;
+        }
+
+        let bid_pool = read_bid_pool(deps.storage, &collateral_token_raw, premium_slot)?;
+        let (remaining_bid, _) = calculate_remaining_bid(&bid, &bid_pool)?;
+        let (liquidated_collateral, _) = calculate_liquidated_collateral(deps.storage, &bid)?;
+
+        bid_amount += remaining_bid;
+        collateral_amount += bid.pending_liquidated_collateral + liquidated_collateral;
+    }
+
+    Ok(VaultResponse {
+        total_shares: vault.total_shares,
+        bid_amount,
+        collateral_amount,
+        share_token: vault
+            .share_token
+            .map(|share_token| deps.api.addr_humanize(&share_token))
+            .transpose()?
+            .map(|share_token| share_token.to_string()),
+    })
+}
+
+pub fn query_vault_shares(
+    deps: Deps,
+    collateral_token: String,
+    premium_slot: u8,
+    depositor: String,
+) -> StdResult<VaultSharesResponse> {
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let depositor_raw = deps.api.addr_canonicalize(&depositor)?;
+
+    Ok(VaultSharesResponse {
+        shares: read_vault_shares(
+            deps.storage,
+            &collateral_token_raw,
+            premium_slot,
+            &depositor_raw,
+        )?,
+    })
+}
@@ -1,21 +1,31 @@
 use cosmwasm_std::{
-    attr, to_json_binary, Addr, Api, BankMsg, Coin, CosmosMsg, Decimal256, Deps, DepsMut, Env,
-    MessageInfo, Response, StdResult, Uint256, WasmMsg,
+    attr, to_json_binary, Addr, Api, CanonicalAddr, CosmosMsg, Decimal256, Deps, DepsMut, Env,
+    MessageInfo, Response, StdResult, Storage, Uint128, Uint256, WasmMsg,
 };
+use cw20::Cw20ExecuteMsg;
 use moneymarket::interest_model::BorrowRateResponse;
-use moneymarket::market::{BorrowerInfoResponse, BorrowerInfosResponse};
+use moneymarket::market::{
+    BorrowerInfoResponse, BorrowerInfosResponse, BorrowerRewardsMode, ReserveMode,
+};
 use moneymarket::overseer::BorrowLimitResponse;
-use moneymarket::querier::{query_balance, query_supply};
+use moneymarket::querier::query_supply;
 use std::convert::TryInto;
 
-use crate::deposit::compute_exchange_rate_raw;
+use crate::deposit::{compute_exchange_rate, compute_exchange_rate_raw};
 use crate::error::ContractError;
+use crate::fixed::repay_fixed_loans;
+use crate::flash_loan::credit_flash_loan;
 use crate::querier::{
     query_borrow_limit, query_borrow_rate, query_overseer_config, query_target_deposit_rate,
 };
+use crate::redemption::fill_redemptions;
+use crate::stable::{query_bucket_balance, query_stable_balance, stable_transfer_msg};
 use crate::state::{
-    read_borrower_info, read_borrower_infos, read_config, read_state, store_borrower_info,
-    store_state, BorrowerInfo, Config, State,
+    read_all_interest_tiers, read_borrower_info, read_borrower_infos, read_borrower_rewards_mode,
+    read_borrowers_by_debt, read_config, read_fees_collected, read_fixed_loan_amount,
+    read_fixed_loan_state, read_interest_tier, read_origination_fee, read_reserve_mode, read_state,
+    read_tier_borrow_rate, store_borrower_info, store_fees_collected, store_state, BorrowerInfo,
+    Config, FixedLoanState, InterestTier, State,
 };
 use moneymarket::bucket::ExecuteMsg as BucketExecuteMsg;
 
@@ -37,7 +47,7 @@ pub fn borrow_stable(
     // Compute interest
     let borrow_incentives_messages =
         compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
-    compute_borrower_interest(&state, &mut liability);
+    compute_tiered_borrower_interest(deps.storage, &state, &mut liability)?;
 
     compute_borrower_reward(&state, &mut liability);
 
@@ -45,91 +55,158 @@ pub fn borrow_stable(
     let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
         deps.as_ref(),
         overseer,
+        env.contract.address.clone(),
         borrower.clone(),
         Some(env.block.time.seconds()),
     )?;
+    let fixed_loan_amount = read_fixed_loan_amount(deps.storage, &borrower_raw, env.block.height)?;
+
+    // The origination fee is added to the loan or kept out of the sent amount
+    let origination_fee = read_origination_fee(deps.storage)?;
+    let fee_amount = borrow_amount * origination_fee.rate;
+    let (loan_amount, send_amount) = if origination_fee.deduct_from_disbursement {
+        (borrow_amount, borrow_amount - fee_amount)
+    } else {
+        (borrow_amount + fee_amount, borrow_amount)
+    };
 
-    if borrow_limit_res.borrow_limit < borrow_amount + liability.loan_amount {
+    if borrow_limit_res.borrow_limit < loan_amount + liability.loan_amount + fixed_loan_amount {
         return Err(ContractError::BorrowExceedsLimit(
             borrow_limit_res.borrow_limit.try_into()?,
         ));
     }
 
-    let current_balance = query_balance(
-        deps.as_ref(),
-        env.contract.address,
-        config.stable_denom.to_string(),
-    )?;
+    let current_balance = query_stable_balance(deps.as_ref(), &config, env.contract.address)?;
 
     // Assert borrow amount
-    assert_max_borrow_factor(&config, &state, current_balance, borrow_amount)?;
+    assert_max_borrow_factor(&config, &state, current_balance, loan_amount)?;
+
+    liability.loan_amount += loan_amount;
+    state.total_liabilities += Decimal256::from_ratio(loan_amount, 1u128);
+
+    let mut messages: Vec<CosmosMsg> = vec![stable_transfer_msg(
+        deps.as_ref(),
+        &config,
+        to.unwrap_or_else(|| borrower.clone()),
+        send_amount,
+    )?];
+    if !fee_amount.is_zero() {
+        match origination_fee.receiver {
+            Some(receiver) => messages.push(stable_transfer_msg(
+                deps.as_ref(),
+                &config,
+                Addr::unchecked(receiver),
+                fee_amount,
+            )?),
+            None => state.total_reserves += Decimal256::from_ratio(fee_amount, 1u128),
+        }
+
+        let mut fees_collected = read_fees_collected(deps.storage)?;
+        fees_collected.origination += fee_amount;
+        store_fees_collected(deps.storage, &fees_collected)?;
+    }
 
-    liability.loan_amount += borrow_amount;
-    state.total_liabilities += Decimal256::from_ratio(borrow_amount, 1u128);
     store_state(deps.storage, &state)?;
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
 
+    let mut attributes = vec![
+        attr("action", "borrow_stable"),
+        attr("borrower", borrower),
+        attr("borrow_amount", borrow_amount),
+    ];
+    if !fee_amount.is_zero() {
+        attributes.push(attr("origination_fee", fee_amount));
+    }
+
     Ok(Response::new()
-        .add_message(CosmosMsg::Bank(BankMsg::Send {
-            to_address: to.unwrap_or_else(|| borrower.clone()).to_string(),
-            amount: vec![Coin {
-                denom: config.stable_denom,
-                amount: borrow_amount.try_into()?,
-            }],
-        }))
+        .add_messages(messages)
         .add_messages(borrow_incentives_messages)
-        .add_attributes(vec![
-            attr("action", "borrow_stable"),
-            attr("borrower", borrower),
-            attr("borrow_amount", borrow_amount),
-        ]))
+        .add_attributes(attributes))
 }
 
-pub fn repay_stable_from_liquidation(
+/// Borrow for a borrower whose collaterals were locked by the overseer
+/// in the same transaction
+/// Executor: overseer
+pub fn borrow_stable_from_overseer(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     borrower: Addr,
-    prev_balance: Uint256,
+    borrow_amount: Uint256,
+    to: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if config.overseer_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
         return Err(ContractError::Unauthorized {});
     }
 
-    let cur_balance: Uint256 = query_balance(
-        deps.as_ref(),
-        env.contract.address.clone(),
-        config.stable_denom.to_string(),
-    )?;
-
     // override env
     let mut info = info;
-
     info.sender = borrower;
-    info.funds = vec![Coin {
-        denom: config.stable_denom,
-        amount: (cur_balance - prev_balance).try_into()?,
-    }];
 
-    repay_stable(deps, env, info, None)
+    borrow_stable(deps, env, info, borrow_amount, to)
 }
 
-pub fn repay_stable(
+pub fn repay_stable_from_liquidation(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    borrower: Option<String>,
+    borrower: Addr,
+    prev_balance: Uint256,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
+    if config.overseer_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
 
-    // Check stable denom deposit
-    let amount: Uint256 = info
-        .funds
-        .iter()
-        .find(|c| c.denom == config.stable_denom)
-        .map(|c| Uint256::from(c.amount))
-        .unwrap_or_else(Uint256::zero);
+    let cur_balance: Uint256 =
+        query_stable_balance(deps.as_ref(), &config, env.contract.address.clone())?;
+
+    // The collateral the liquidation queue can't fill is sold in a Dutch
+    // auction whose lots repay the loan as they are bought
+    if cur_balance == prev_balance {
+        return Ok(Response::new().add_attributes(vec![
+            attr("action", "repay_stable"),
+            attr("borrower", borrower),
+            attr("repay_amount", Uint256::zero()),
+        ]));
+    }
+
+    // Liquidations cover the fixed rate loans once the floating rate loan is repaid
+    _repay_stable(deps, env, borrower, cur_balance - prev_balance, None, true)
+}
+
+pub fn repay_stable(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    amount: Uint256,
+    borrower: Option<String>,
+) -> Result<Response, ContractError> {
+    _repay_stable(deps, env, sender, amount, borrower, false)
+}
+
+/// The Dutch auction lots of a liquidation repay the loan in the same
+/// order as the liquidation, fixed rate loans once the floating rate loan is repaid
+pub fn repay_stable_from_auction(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    amount: Uint256,
+    borrower: String,
+) -> Result<Response, ContractError> {
+    _repay_stable(deps, env, sender, amount, Some(borrower), true)
+}
+
+fn _repay_stable(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    amount: Uint256,
+    borrower: Option<String>,
+    repay_fixed: bool,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
 
     // Cannot deposit zero amount
     if amount.is_zero() {
@@ -141,7 +218,7 @@ pub fn repay_stable(
     let borrower = borrower
         .map(|b| deps.api.addr_validate(&b))
         .transpose()?
-        .unwrap_or(info.sender);
+        .unwrap_or(sender);
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
@@ -153,30 +230,44 @@ pub fn repay_stable(
         env.block.height,
         Some(amount),
     )?;
-    compute_borrower_interest(&state, &mut liability);
+    compute_tiered_borrower_interest(deps.storage, &state, &mut liability)?;
 
     compute_borrower_reward(&state, &mut liability);
 
-    let repay_amount: Uint256;
+    let repay_amount = apply_repayment(
+        deps.storage,
+        &mut state,
+        &borrower_raw,
+        &mut liability,
+        amount,
+        env.block.height,
+        repay_fixed,
+    )?;
+
+    credit_flash_loan(deps.storage, repay_amount)?;
+
+    // Payback left repay amount to sender
+    let refund_amount = amount - repay_amount;
     let mut messages: Vec<CosmosMsg> = vec![];
-    if liability.loan_amount < amount {
-        repay_amount = liability.loan_amount;
-        liability.loan_amount = Uint256::zero();
-
-        // Payback left repay amount to sender
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: borrower.to_string(),
-            amount: vec![Coin {
-                denom: config.stable_denom,
-                amount: (amount - repay_amount).try_into()?,
-            }],
-        }));
-    } else {
-        repay_amount = amount;
-        liability.loan_amount -= repay_amount;
+    if !refund_amount.is_zero() {
+        messages.push(stable_transfer_msg(
+            deps.as_ref(),
+            &config,
+            borrower.clone(),
+            refund_amount,
+        )?);
     }
 
-    state.total_liabilities -= Decimal256::from_ratio(repay_amount, 1u128);
+    // The repaid stable fills the redemption queue
+    let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, Some(refund_amount))?;
+    messages.extend(fill_redemptions(
+        deps.branch(),
+        &env,
+        &config,
+        &mut state,
+        exchange_rate,
+        refund_amount,
+    )?);
 
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
     store_state(deps.storage, &state)?;
@@ -191,6 +282,196 @@ pub fn repay_stable(
         ]))
 }
 
+/// Repay a loan with aTerra, burnt at the current exchange rate.
+/// The aTerra left after the loan is repaid is sent back
+/// Executor: aTerra token contract
+pub fn repay_stable_with_aterra(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    borrower: Option<String>,
+    aterra_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    let borrower = borrower
+        .map(|b| deps.api.addr_validate(&b))
+        .transpose()?
+        .unwrap_or_else(|| sender.clone());
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    // Compute interest
+    let borrow_incentives_messages =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_tiered_borrower_interest(deps.storage, &state, &mut liability)?;
+
+    compute_borrower_reward(&state, &mut liability);
+
+    // Load anchor token exchange rate with updated state
+    let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, None)?;
+    let amount = Uint256::from(aterra_amount) * exchange_rate;
+
+    // Cannot repay zero amount
+    if amount.is_zero() {
+        return Err(ContractError::ZeroRepay(config.stable_denom));
+    }
+
+    let repay_amount = apply_repayment(
+        deps.storage,
+        &mut state,
+        &borrower_raw,
+        &mut liability,
+        amount,
+        env.block.height,
+        false,
+    )?;
+
+    // Only burn the aTerra needed to cover the repaid amount, rounding up
+    let burn_amount: Uint256 = if repay_amount == amount {
+        aterra_amount.into()
+    } else {
+        let burn_amount = Decimal256::from_ratio(repay_amount, 1u128) / exchange_rate;
+        let floor_amount = burn_amount * Uint256::one();
+        if Decimal256::from_ratio(floor_amount, 1u128) < burn_amount {
+            floor_amount + Uint256::one()
+        } else {
+            floor_amount
+        }
+    };
+    let refund_amount = Uint256::from(aterra_amount) - burn_amount;
+
+    state.prev_aterra_supply -= burn_amount;
+    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    store_state(deps.storage, &state)?;
+
+    let aterra_contract = deps.api.addr_humanize(&config.aterra_contract)?.to_string();
+    let mut messages: Vec<CosmosMsg> = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: aterra_contract.clone(),
+        funds: vec![],
+        msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+            amount: burn_amount.try_into()?,
+        })?,
+    })];
+
+    // Payback left aTerra to sender
+    if !refund_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: aterra_contract,
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: sender.to_string(),
+                amount: refund_amount.try_into()?,
+            })?,
+        }));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_messages(borrow_incentives_messages)
+        .add_attributes(vec![
+            attr("action", "repay_stable_with_aterra"),
+            attr("borrower", borrower),
+            attr("burn_amount", burn_amount),
+            attr("repay_amount", repay_amount),
+        ]))
+}
+
+/// Apply up to `amount` to the floating rate loan of a borrower, then
+/// to their fixed rate loans when `repay_fixed` is set.
+/// Returns the amount used; interest must be computed beforehand
+fn apply_repayment(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    borrower_raw: &CanonicalAddr,
+    liability: &mut BorrowerInfo,
+    amount: Uint256,
+    block_height: u64,
+    repay_fixed: bool,
+) -> StdResult<Uint256> {
+    if liability.loan_amount >= amount {
+        liability.loan_amount -= amount;
+        state.total_liabilities -= Decimal256::from_ratio(amount, 1u128);
+        return Ok(amount);
+    }
+
+    let mut repay_amount = liability.loan_amount;
+    liability.loan_amount = Uint256::zero();
+    state.total_liabilities -= Decimal256::from_ratio(repay_amount, 1u128);
+
+    if repay_fixed {
+        repay_amount += repay_fixed_loans(
+            storage,
+            state,
+            borrower_raw,
+            amount - repay_amount,
+            block_height,
+        )?;
+    }
+
+    Ok(repay_amount)
+}
+
+/// Pay the borrower rewards accrued in `Claimable` mode
+/// from the borrow incentives bucket
+pub fn claim_borrower_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    // Compute interest and reward
+    let mut messages =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_tiered_borrower_interest(deps.storage, &state, &mut liability)?;
+    compute_borrower_reward(&state, &mut liability);
+
+    // Rewards accrued in `Subsidize` mode were already given out as a lower interest,
+    // only the reserved rewards can be claimed
+    let claim_amount = std::cmp::min(
+        liability.claimable_rewards * Uint256::one(),
+        state.reserved_borrower_rewards,
+    );
+    liability.claimable_rewards -= Decimal256::from_ratio(claim_amount, 1u128);
+    liability.pending_rewards = liability
+        .pending_rewards
+        .checked_sub(Decimal256::from_ratio(claim_amount, 1u128))
+        .unwrap_or_else(|_| Decimal256::zero());
+    state.reserved_borrower_rewards -= claim_amount;
+
+    store_state(deps.storage, &state)?;
+    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+
+    if !claim_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.borrow_reserves_bucket_contract)?
+                .to_string(),
+            funds: vec![],
+            msg: to_json_binary(&BucketExecuteMsg::SendTo {
+                denom: config.stable_denom,
+                amount: claim_amount.try_into()?,
+                recipient: to.unwrap_or_else(|| borrower.clone()).to_string(),
+            })?,
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "claim_borrower_rewards"),
+        attr("borrower", borrower),
+        attr("claim_amount", claim_amount),
+    ]))
+}
+
 /// Compute interest and update state
 /// total liabilities and total reserves
 pub fn compute_interest(
@@ -205,11 +486,9 @@ pub fn compute_interest(
     }
 
     let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
-    let balance: Uint256 = query_balance(
-        deps,
-        deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
-    )? - deposit_amount.unwrap_or_else(Uint256::zero);
+    let balance: Uint256 =
+        query_stable_balance(deps, config, deps.api.addr_humanize(&config.contract_addr)?)?
+            - deposit_amount.unwrap_or_else(Uint256::zero);
 
     let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
         deps,
@@ -319,12 +598,10 @@ pub fn compute_interest_raw(
 
     let interest_factor_borrow = passed_blocks * borrow_rate;
 
-    let mut available_borrower_incentives = query_balance(
-        deps,
-        deps.api
-            .addr_humanize(&config.borrow_reserves_bucket_contract)?,
-        config.stable_denom.to_string(),
-    )?;
+    // The rewards not claimed yet are not available for incentives anymore
+    let mut available_borrower_incentives = query_bucket_balance(deps, config)?
+        .checked_sub(state.reserved_borrower_rewards)
+        .unwrap_or_else(|_| Uint256::zero());
 
     let overseer_config =
         query_overseer_config(deps, deps.api.addr_humanize(&config.overseer_contract)?)?;
@@ -343,26 +620,70 @@ pub fn compute_interest_raw(
         passed_blocks,
     )?;
 
-    let interest_accrued = state.total_liabilities * interest_factor;
+    // In claimable mode, the incentives stay in the bucket until the borrowers claim them
+    // and the borrowers pay the full interest
+    let borrower_rewards_mode = read_borrower_rewards_mode(deps.storage)?;
+    let (interest_factor, interest_factor_messages) = match borrower_rewards_mode {
+        BorrowerRewardsMode::Subsidize => (interest_factor, interest_factor_messages),
+        BorrowerRewardsMode::Claimable => {
+            state.reserved_borrower_rewards += state.prev_borrower_incentives;
+            (interest_factor_borrow, vec![])
+        }
+    };
+
+    // Fixed rate loans accrue at their own rate, the interest factor
+    // only applies to the floating part of the liabilities
+    let fixed_loan_state: FixedLoanState = read_fixed_loan_state(deps.storage)?;
+    let prev_fixed_liabilities = fixed_loan_state.liabilities_at(state.last_interest_updated);
+    let fixed_interest_accrued =
+        fixed_loan_state.liabilities_at(block_height) - prev_fixed_liabilities;
+    let floating_liabilities = state
+        .total_liabilities
+        .checked_sub(prev_fixed_liabilities)
+        .unwrap_or_else(|_| Decimal256::zero());
+
+    // The tier borrowers only accrue `1 - discount` of the floating interest
+    let prev_global_interest_index = state.global_interest_index;
+    let global_interest_index = prev_global_interest_index * (Decimal256::one() + interest_factor);
+    let waived_tier_interest = compute_waived_tier_interest(
+        deps.storage,
+        prev_global_interest_index,
+        global_interest_index,
+    )?;
+    let interest_accrued = (floating_liabilities * interest_factor)
+        .checked_sub(waived_tier_interest)
+        .unwrap_or_else(|_| Decimal256::zero())
+        + fixed_interest_accrued;
 
     // We also subtract the borrower_subsidies to the liabilites.
     // The actual borrow_rate is slightly lower that what is predicted by the borrow_rate affine function
 
-    state.global_interest_index *= Decimal256::one() + interest_factor;
+    state.global_interest_index = global_interest_index;
     state.total_liabilities += interest_accrued;
 
     // We update the reward index as well here
     let borrow_amount = state.total_liabilities;
     if !state.prev_borrower_incentives.is_zero() && !borrow_amount.is_zero() {
-        state.global_reward_index +=
+        let reward_per_liability =
             Decimal256::from_ratio(state.prev_borrower_incentives, 1u128) / borrow_amount;
+        state.global_reward_index += reward_per_liability;
+
+        // Only the reserved incentives can be claimed
+        if borrower_rewards_mode == BorrowerRewardsMode::Claimable {
+            state.global_claimable_reward_index += reward_per_liability;
+        }
+    }
+
+    let reserve_mode = read_reserve_mode(deps.storage)?;
+    if let ReserveMode::ReserveFactor { reserve_factor } = reserve_mode {
+        state.total_reserves += interest_accrued * reserve_factor;
     }
 
     let mut exchange_rate = compute_exchange_rate_raw(state, aterra_supply, balance);
     let effective_deposit_rate = exchange_rate / state.prev_exchange_rate;
     let deposit_rate = (effective_deposit_rate - Decimal256::one()) / passed_blocks;
 
-    if deposit_rate > target_deposit_rate {
+    if reserve_mode == ReserveMode::TargetDepositRate && deposit_rate > target_deposit_rate {
         // excess_deposit_rate(_per_block)
         let excess_deposit_rate = deposit_rate - target_deposit_rate;
         let prev_deposits =
@@ -391,12 +712,64 @@ pub(crate) fn compute_borrower_interest(state: &State, liability: &mut BorrowerI
     liability.interest_index = state.global_interest_index;
 }
 
+/// Compute new interest and apply to the liability, with the index of the borrower tier
+pub(crate) fn compute_tiered_borrower_interest(
+    storage: &dyn Storage,
+    state: &State,
+    liability: &mut BorrowerInfo,
+) -> StdResult<()> {
+    let borrower_tier = match liability.tier.as_mut() {
+        Some(borrower_tier) => borrower_tier,
+        None => {
+            compute_borrower_interest(state, liability);
+            return Ok(());
+        }
+    };
+
+    let tier: InterestTier = read_interest_tier(storage, borrower_tier.tier_id)?;
+    let tier_interest_index = tier.interest_index_at(state.global_interest_index);
+
+    let tier_loan_amount = borrower_tier.principal(liability.loan_amount) * tier_interest_index;
+
+    liability.loan_amount = tier_loan_amount * Uint256::one();
+    liability.interest_index = state.global_interest_index;
+    borrower_tier.interest_index = tier_interest_index;
+    Ok(())
+}
+
+/// Interest waived by the tier discounts while the global interest index
+/// moves from `prev_global_interest_index` to `global_interest_index`
+fn compute_waived_tier_interest(
+    storage: &dyn Storage,
+    prev_global_interest_index: Decimal256,
+    global_interest_index: Decimal256,
+) -> StdResult<Decimal256> {
+    Ok(read_all_interest_tiers(storage)?.iter().fold(
+        Decimal256::zero(),
+        |waived_interest, tier| {
+            let prev_liabilities =
+                tier.principal * tier.interest_index_at(prev_global_interest_index);
+            let global_liabilities =
+                prev_liabilities * global_interest_index / prev_global_interest_index;
+            let tier_liabilities = tier.principal * tier.interest_index_at(global_interest_index);
+            waived_interest
+                + global_liabilities
+                    .checked_sub(tier_liabilities)
+                    .unwrap_or_else(|_| Decimal256::zero())
+        },
+    ))
+}
+
 /// Compute reward amount a borrower received
 pub(crate) fn compute_borrower_reward(state: &State, liability: &mut BorrowerInfo) {
-    liability.pending_rewards += Decimal256::from_ratio(liability.loan_amount, 1u128)
-        / state.global_interest_index
-        * (state.global_reward_index - liability.reward_index);
+    let loan_amount =
+        Decimal256::from_ratio(liability.loan_amount, 1u128) / state.global_interest_index;
+    liability.pending_rewards += loan_amount * (state.global_reward_index - liability.reward_index);
     liability.reward_index = state.global_reward_index;
+
+    liability.claimable_rewards +=
+        loan_amount * (state.global_claimable_reward_index - liability.claimable_reward_index);
+    liability.claimable_reward_index = state.global_claimable_reward_index;
 }
 
 pub fn query_borrower_info(
@@ -405,10 +778,8 @@ pub fn query_borrower_info(
     borrower: Addr,
     block_height: Option<u64>,
 ) -> Result<BorrowerInfoResponse, ContractError> {
-    let mut borrower_info: BorrowerInfo = read_borrower_info(
-        deps.storage,
-        &deps.api.addr_canonicalize(borrower.as_str())?,
-    );
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
     let block_height = if let Some(block_height) = block_height {
         block_height
@@ -420,35 +791,130 @@ pub fn query_borrower_info(
     let mut state: State = read_state(deps.storage)?;
 
     compute_interest(deps, &config, &mut state, block_height, None)?;
-    compute_borrower_interest(&state, &mut borrower_info);
+    compute_tiered_borrower_interest(deps.storage, &state, &mut borrower_info)?;
 
     compute_borrower_reward(&state, &mut borrower_info);
 
-    Ok(BorrowerInfoResponse {
-        borrower: borrower.to_string(),
-        interest_index: borrower_info.interest_index,
-        reward_index: borrower_info.reward_index,
-        loan_amount: borrower_info.loan_amount,
-        pending_rewards: borrower_info.pending_rewards,
-    })
+    let borrow_rate = query_market_borrow_rate(deps, &config, &state)?;
+    Ok(borrower_info_response(
+        deps,
+        &borrower_raw,
+        borrower_info,
+        block_height,
+        borrow_rate,
+    )?)
 }
 
 pub fn query_borrower_infos(
     deps: Deps,
     start_after: Option<Addr>,
     limit: Option<u32>,
-) -> StdResult<BorrowerInfosResponse> {
+    block_height: Option<u64>,
+) -> Result<BorrowerInfosResponse, ContractError> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    let borrower_infos = read_borrower_infos(deps.storage, start_after, limit)?;
+    Ok(BorrowerInfosResponse {
+        borrower_infos: borrower_info_responses(deps, borrower_infos, block_height)?,
+    })
+}
+
+/// Borrowers with the largest loans first. The order follows the stored
+/// loan amounts, which do not include the interest since their last update
+pub fn query_top_borrowers(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    block_height: Option<u64>,
+) -> Result<BorrowerInfosResponse, ContractError> {
     let start_after = if let Some(start_after) = start_after {
         Some(deps.api.addr_canonicalize(start_after.as_str())?)
     } else {
         None
     };
 
-    let borrower_infos: Vec<BorrowerInfoResponse> = read_borrower_infos(deps, start_after, limit)?;
-    Ok(BorrowerInfosResponse { borrower_infos })
+    let borrower_infos = read_borrowers_by_debt(deps.storage, start_after, limit)?;
+    Ok(BorrowerInfosResponse {
+        borrower_infos: borrower_info_responses(deps, borrower_infos, block_height)?,
+    })
+}
+
+/// Stored liabilities of the borrowers, or their liabilities with the interest
+/// accrued up to `block_height` when it is given. The fixed rate loans are
+/// valued at `block_height`, or at the last interest update without it
+fn borrower_info_responses(
+    deps: Deps,
+    borrower_infos: Vec<(CanonicalAddr, BorrowerInfo)>,
+    block_height: Option<u64>,
+) -> Result<Vec<BorrowerInfoResponse>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    if let Some(block_height) = block_height {
+        compute_interest(deps, &config, &mut state, block_height, None)?;
+    }
+
+    let fixed_loans_height = block_height.unwrap_or(state.last_interest_updated);
+    let borrow_rate = query_market_borrow_rate(deps, &config, &state)?;
+    let mut responses: Vec<BorrowerInfoResponse> = vec![];
+    for (borrower_raw, mut borrower_info) in borrower_infos {
+        if block_height.is_some() {
+            compute_tiered_borrower_interest(deps.storage, &state, &mut borrower_info)?;
+            compute_borrower_reward(&state, &mut borrower_info);
+        }
+
+        responses.push(borrower_info_response(
+            deps,
+            &borrower_raw,
+            borrower_info,
+            fixed_loans_height,
+            borrow_rate,
+        )?);
+    }
+
+    Ok(responses)
+}
+
+/// `borrow_rate` is the rate of the default tier,
+/// the fixed rate loans are valued at `block_height`
+fn borrower_info_response(
+    deps: Deps,
+    borrower_raw: &CanonicalAddr,
+    borrower_info: BorrowerInfo,
+    block_height: u64,
+    borrow_rate: Decimal256,
+) -> StdResult<BorrowerInfoResponse> {
+    Ok(BorrowerInfoResponse {
+        borrower: deps.api.addr_humanize(borrower_raw)?.to_string(),
+        interest_index: borrower_info.interest_index,
+        reward_index: borrower_info.reward_index,
+        loan_amount: borrower_info.loan_amount,
+        pending_rewards: borrower_info.pending_rewards,
+        claimable_rewards: borrower_info.claimable_rewards,
+        fixed_loan_amount: read_fixed_loan_amount(deps.storage, borrower_raw, block_height)?,
+        borrow_rate: read_tier_borrow_rate(deps.storage, &borrower_info.tier, borrow_rate)?,
+        interest_tier: borrower_info.tier.map(|tier| tier.tier_id),
+    })
+}
+
+/// Borrow rate of the interest model with the current market balance
+fn query_market_borrow_rate(deps: Deps, config: &Config, state: &State) -> StdResult<Decimal256> {
+    let balance =
+        query_stable_balance(deps, config, deps.api.addr_humanize(&config.contract_addr)?)?;
+    Ok(query_borrow_rate(
+        deps,
+        deps.api.addr_humanize(&config.interest_model)?,
+        balance,
+        state.total_liabilities,
+        state.total_reserves,
+    )?
+    .rate)
 }
 
-fn assert_max_borrow_factor(
+pub(crate) fn assert_max_borrow_factor(
     config: &Config,
     state: &State,
     current_balance: Uint256,
@@ -1,37 +1,59 @@
 use crate::querier::query_next_borrower_incentives;
 use cosmwasm_std::entry_point;
 use serde::Serialize;
-#[cfg(not(feature = "library"))]
-use std::convert::TryInto;
 
 use crate::borrow::{
-    borrow_stable, compute_interest, compute_interest_raw, query_borrower_info,
-    query_borrower_infos, repay_stable, repay_stable_from_liquidation,
+    borrow_stable, borrow_stable_from_overseer, claim_borrower_rewards, compute_interest,
+    compute_interest_raw, query_borrower_info, query_borrower_infos, query_top_borrowers,
+    repay_stable, repay_stable_from_auction, repay_stable_from_liquidation,
+    repay_stable_with_aterra,
+};
+use crate::delegation::{
+    approve_delegate, borrow_stable_on_behalf, query_allowance, query_allowances, revoke_delegate,
 };
 use crate::deposit::{compute_exchange_rate_raw, deposit_stable, redeem_stable};
 use crate::error::ContractError;
+use crate::fixed::{
+    borrow_fixed, query_fixed_loan, query_fixed_loans, query_fixed_rate_quote, repay_fixed,
+    rollover_fixed,
+};
+use crate::flash_loan::{assert_no_flash_loan, flash_loan, repay_flash_loan, FLASH_LOAN_REPLY_ID};
 use crate::querier::{
     query_borrow_rate, query_borrow_reserves_incentives_rate, query_target_deposit_rate,
 };
+use crate::reconcile::{query_liabilities_drift, reconcile_liabilities};
+use crate::redemption::{cancel_redemption, query_redemptions, queue_redemption};
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{read_config, read_state, store_config, store_state, Config, State};
+use crate::stable::{assert_stable_token, query_stable_balance, stable_funds, stable_transfer_msg};
+use crate::state::{
+    index_borrowers_by_debt, read_borrower_rewards_mode, read_config, read_fees_collected,
+    read_fixed_rate_config, read_flash_loan_fee_rate, read_origination_fee, read_reserve_mode,
+    read_reserves_withdrawn, read_stable_asset, read_state, store_borrower_rewards_mode,
+    store_config, store_debt_index_backfill, store_fixed_rate_config, store_flash_loan_fee_rate,
+    store_origination_fee, store_reserve_mode, store_reserves_withdrawn, store_stable_asset,
+    store_state, Config, DebtIndexBackfill, FixedRateConfig, State,
+};
+use crate::tier::{query_interest_tiers, set_borrower_tier, set_interest_tier};
 
 use cosmwasm_std::{
-    attr, from_json, to_json_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg,
-    Decimal256, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg,
-    Uint128, Uint256, WasmMsg,
+    attr, from_json, to_json_binary, Addr, Binary, CanonicalAddr, CosmosMsg, Decimal256, Deps,
+    DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128, Uint256,
+    WasmMsg,
 };
 use cw20::{Cw20Coin, Cw20ReceiveMsg, MinterResponse};
 use cw20_base::msg::{InstantiateMarketingInfo, InstantiateMsg as TokenInstantiateMsg};
 
+use moneymarket::astroport_router::AssetInfo;
 use moneymarket::common::optional_addr_validate;
 use moneymarket::interest_model::BorrowRateResponse;
 use moneymarket::market::{
-    ConfigResponse, Cw20HookMsg, EpochStateResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
-    QueryMsg, StateResponse,
+    BorrowerRewardsMode, ConfigResponse, Cw20HookMsg, EpochStateResponse, ExecuteMsg,
+    FeesCollectedResponse, FixedRateConfigMsg, InstantiateMsg, MigrateMsg, OriginationFee,
+    QueryMsg, ReserveMode, ReservesResponse, StateResponse,
 };
-use moneymarket::querier::{query_balance, query_supply};
+use moneymarket::querier::{query_supply, query_token_balance};
 use protobuf::Message;
+use std::convert::TryInto;
 
 pub const INITIAL_DEPOSIT_AMOUNT: u128 = 1000000;
 
@@ -42,14 +64,45 @@ pub fn instantiate(
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    let initial_deposit = info
-        .funds
-        .iter()
-        .find(|c| c.denom == msg.stable_denom)
-        .map(|c| c.amount)
-        .unwrap_or_else(Uint128::zero);
-
-    if initial_deposit != Uint128::from(INITIAL_DEPOSIT_AMOUNT) {
+    // A CW20 stable can't be sent along with the instantiation,
+    // the initial deposit must already be held by the market
+    let stable_asset = match msg.stable_asset {
+        Some(AssetInfo::Token { contract_addr }) => {
+            let contract_addr = deps.api.addr_validate(contract_addr.as_str())?;
+            if contract_addr.as_str() != msg.stable_denom {
+                return Err(ContractError::Std(StdError::generic_err(
+                    "stable_denom must be the address of the stable token",
+                )));
+            }
+            AssetInfo::Token { contract_addr }
+        }
+        Some(AssetInfo::NativeToken { denom }) if denom != msg.stable_denom => {
+            return Err(ContractError::Std(StdError::generic_err(
+                "stable_denom must be the denom of the stable asset",
+            )));
+        }
+        _ => AssetInfo::NativeToken {
+            denom: msg.stable_denom.clone(),
+        },
+    };
+    // The borrower incentives are paid in the native stable by the overseer
+    if matches!(stable_asset, AssetInfo::Token { .. }) && !msg.initial_borrower_incentives.is_zero()
+    {
+        return Err(ContractError::Std(StdError::generic_err(
+            "borrower incentives are not supported with a CW20 stable",
+        )));
+    }
+
+    let initial_deposit = match &stable_asset {
+        AssetInfo::NativeToken { .. } => stable_asset.funds_amount(&info.funds),
+        AssetInfo::Token { contract_addr } => query_token_balance(
+            deps.as_ref(),
+            contract_addr.clone(),
+            env.contract.address.clone(),
+        )?,
+    };
+
+    if initial_deposit != Uint256::from(INITIAL_DEPOSIT_AMOUNT) {
         return Err(ContractError::InitialFundsNotDeposited(
             INITIAL_DEPOSIT_AMOUNT.into(),
             msg.stable_denom,
@@ -74,6 +127,15 @@ pub fn instantiate(
         },
     )?;
 
+    store_stable_asset(deps.storage, &stable_asset)?;
+    store_debt_index_backfill(
+        deps.storage,
+        &DebtIndexBackfill {
+            done: true,
+            last_borrower: None,
+        },
+    )?;
+
     store_state(
         deps.storage,
         &State {
@@ -87,6 +149,8 @@ pub fn instantiate(
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         },
     )?;
 
@@ -132,6 +196,19 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // The market balance must not move while a flash loan is in flight,
+    // repayments are credited to the loan
+    if !matches!(
+        msg,
+        ExecuteMsg::Receive(_)
+            | ExecuteMsg::RepayStable { .. }
+            | ExecuteMsg::RepayStableFromAuction { .. }
+            | ExecuteMsg::RepayFixed { .. }
+            | ExecuteMsg::RepayStableFromLiquidation { .. }
+    ) {
+        assert_no_flash_loan(deps.storage)?;
+    }
+
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::RegisterContracts {
@@ -160,6 +237,11 @@ pub fn execute(
             distribution_model,
             max_borrow_factor,
             max_borrow_subsidy_rate,
+            fixed_rate,
+            flash_loan_fee_rate,
+            borrower_rewards_mode,
+            reserve_mode,
+            origination_fee,
         } => {
             let api = deps.api;
             update_config(
@@ -171,8 +253,28 @@ pub fn execute(
                 optional_addr_validate(api, distribution_model)?,
                 max_borrow_factor,
                 max_borrow_subsidy_rate,
+                fixed_rate,
+                flash_loan_fee_rate,
+                borrower_rewards_mode,
+                reserve_mode,
+                origination_fee,
             )
         }
+        ExecuteMsg::WithdrawReserves { amount, to } => {
+            let api = deps.api;
+            withdraw_reserves(deps, env, info, amount, optional_addr_validate(api, to)?)
+        }
+        ExecuteMsg::SetInterestTier { tier_id, discount } => {
+            set_interest_tier(deps, env, info, tier_id, discount)
+        }
+        ExecuteMsg::SetBorrowerTier { borrower, tier_id } => {
+            let api = deps.api;
+            set_borrower_tier(deps, env, info, api.addr_validate(&borrower)?, tier_id)
+        }
+        ExecuteMsg::ReconcileLiabilities { max_drift, limit } => {
+            reconcile_liabilities(deps, env, info, max_drift, limit)
+        }
+        ExecuteMsg::IndexBorrowersByDebt { limit } => backfill_debt_index(deps, info, limit),
         ExecuteMsg::ExecuteEpochOperations {
             deposit_rate,
             target_deposit_rate,
@@ -187,7 +289,11 @@ pub fn execute(
             threshold_deposit_rate,
             distributed_interest,
         ),
-        ExecuteMsg::DepositStable {} => deposit_stable(deps, env, info),
+        ExecuteMsg::DepositStable {} => {
+            let config: Config = read_config(deps.storage)?;
+            let deposit_amount = stable_funds(deps.as_ref(), &config, &info)?;
+            deposit_stable(deps, env, info.sender, deposit_amount)
+        }
         ExecuteMsg::BorrowStable { borrow_amount, to } => {
             let api = deps.api;
             borrow_stable(
@@ -198,7 +304,74 @@ pub fn execute(
                 optional_addr_validate(api, to)?,
             )
         }
-        ExecuteMsg::RepayStable { borrower } => repay_stable(deps, env, info, borrower),
+        ExecuteMsg::RepayStable { borrower } => {
+            let config: Config = read_config(deps.storage)?;
+            let amount = stable_funds(deps.as_ref(), &config, &info)?;
+            repay_stable(deps, env, info.sender, amount, borrower)
+        }
+        ExecuteMsg::RepayStableFromAuction { borrower } => {
+            let config: Config = read_config(deps.storage)?;
+            let amount = stable_funds(deps.as_ref(), &config, &info)?;
+            repay_stable_from_auction(deps, env, info.sender, amount, borrower)
+        }
+        ExecuteMsg::BorrowFixed {
+            borrow_amount,
+            term,
+            to,
+        } => {
+            let api = deps.api;
+            borrow_fixed(
+                deps,
+                env,
+                info,
+                borrow_amount,
+                term,
+                optional_addr_validate(api, to)?,
+            )
+        }
+        ExecuteMsg::RepayFixed { loan_id } => {
+            let config: Config = read_config(deps.storage)?;
+            let amount = stable_funds(deps.as_ref(), &config, &info)?;
+            repay_fixed(deps, env, info.sender, amount, loan_id)
+        }
+        ExecuteMsg::RolloverFixed { loan_id, term } => {
+            rollover_fixed(deps, env, info, loan_id, term)
+        }
+        ExecuteMsg::ApproveDelegate { delegate, amount } => {
+            let api = deps.api;
+            approve_delegate(deps, info, api.addr_validate(&delegate)?, amount)
+        }
+        ExecuteMsg::RevokeDelegate { delegate } => {
+            let api = deps.api;
+            revoke_delegate(deps, info, api.addr_validate(&delegate)?)
+        }
+        ExecuteMsg::BorrowStableOnBehalf { borrower, amount } => {
+            let api = deps.api;
+            borrow_stable_on_behalf(deps, env, info, api.addr_validate(&borrower)?, amount)
+        }
+        ExecuteMsg::FlashLoan { amount, msg } => flash_loan(deps, env, info, amount, msg),
+        ExecuteMsg::CancelRedemption { redemption_id } => {
+            cancel_redemption(deps, info, redemption_id)
+        }
+        ExecuteMsg::ClaimBorrowerRewards { to } => {
+            let api = deps.api;
+            claim_borrower_rewards(deps, env, info, optional_addr_validate(api, to)?)
+        }
+        ExecuteMsg::BorrowStableFromOverseer {
+            borrower,
+            borrow_amount,
+            to,
+        } => {
+            let api = deps.api;
+            borrow_stable_from_overseer(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                borrow_amount,
+                optional_addr_validate(api, to)?,
+            )
+        }
         ExecuteMsg::RepayStableFromLiquidation {
             borrower,
             prev_balance,
@@ -216,7 +389,7 @@ pub fn execute(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
         1 => {
             // get new token's contract address
@@ -233,6 +406,7 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 
             register_aterra(deps, token_addr)
         }
+        FLASH_LOAN_REPLY_ID => repay_flash_loan(deps, env),
         _ => Err(ContractError::InvalidReplyId {}),
     }
 }
@@ -246,6 +420,7 @@ pub fn receive_cw20(
     let contract_addr = info.sender;
     match from_json(&cw20_msg.msg) {
         Ok(Cw20HookMsg::RedeemStable {}) => {
+            assert_no_flash_loan(deps.storage)?;
             // only asset contract can execute this message
             let config: Config = read_config(deps.storage)?;
             if deps.api.addr_canonicalize(contract_addr.as_str())? != config.aterra_contract {
@@ -255,6 +430,74 @@ pub fn receive_cw20(
             let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
             redeem_stable(deps, env, cw20_sender_addr, cw20_msg.amount)
         }
+        Ok(Cw20HookMsg::RepayStableWithAterra { borrower }) => {
+            // the exchange rate is off while the loan is out
+            assert_no_flash_loan(deps.storage)?;
+            // only asset contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(contract_addr.as_str())? != config.aterra_contract {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            repay_stable_with_aterra(deps, env, cw20_sender_addr, borrower, cw20_msg.amount)
+        }
+        Ok(Cw20HookMsg::QueueRedemption {}) => {
+            assert_no_flash_loan(deps.storage)?;
+            // only asset contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(contract_addr.as_str())? != config.aterra_contract {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            queue_redemption(deps, env, cw20_sender_addr, cw20_msg.amount)
+        }
+        Ok(Cw20HookMsg::DepositStable {}) => {
+            assert_no_flash_loan(deps.storage)?;
+            // only the stable token contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            assert_stable_token(deps.as_ref(), &config, &contract_addr)?;
+
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            deposit_stable(deps, env, cw20_sender_addr, cw20_msg.amount.into())
+        }
+        Ok(Cw20HookMsg::RepayStable { borrower }) => {
+            // only the stable token contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            assert_stable_token(deps.as_ref(), &config, &contract_addr)?;
+
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            repay_stable(
+                deps,
+                env,
+                cw20_sender_addr,
+                cw20_msg.amount.into(),
+                borrower,
+            )
+        }
+        Ok(Cw20HookMsg::RepayStableFromAuction { borrower }) => {
+            // only the stable token contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            assert_stable_token(deps.as_ref(), &config, &contract_addr)?;
+
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            repay_stable_from_auction(
+                deps,
+                env,
+                cw20_sender_addr,
+                cw20_msg.amount.into(),
+                borrower,
+            )
+        }
+        Ok(Cw20HookMsg::RepayFixed { loan_id }) => {
+            // only the stable token contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            assert_stable_token(deps.as_ref(), &config, &contract_addr)?;
+
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            repay_fixed(deps, env, cw20_sender_addr, cw20_msg.amount.into(), loan_id)
+        }
         _ => Err(ContractError::MissingRedeemStableHook {}),
     }
 }
@@ -306,6 +549,7 @@ pub fn register_contracts(
     Ok(Response::default())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
     env: Env,
@@ -315,6 +559,11 @@ pub fn update_config(
     distribution_model: Option<Addr>,
     max_borrow_factor: Option<Decimal256>,
     max_borrow_subsidy_rate: Option<Decimal256>,
+    fixed_rate: Option<FixedRateConfigMsg>,
+    flash_loan_fee_rate: Option<Decimal256>,
+    borrower_rewards_mode: Option<BorrowerRewardsMode>,
+    reserve_mode: Option<ReserveMode>,
+    origination_fee: Option<OriginationFee>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
 
@@ -327,7 +576,7 @@ pub fn update_config(
         config.owner_addr = deps.api.addr_canonicalize(owner_addr.as_str())?;
     }
 
-    let borrow_incentives_messages = if interest_model.is_some() {
+    let mut borrow_incentives_messages = if interest_model.is_some() {
         let mut state: State = read_state(deps.storage)?;
         let borrow_incentives_messages =
             compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
@@ -352,6 +601,84 @@ pub fn update_config(
         config.max_borrow_subsidy_rate = max_borrow_subsidy_rate
     }
 
+    if let Some(fixed_rate) = fixed_rate {
+        let mut fixed_rate_config: FixedRateConfig = read_fixed_rate_config(deps.storage)?;
+        if let Some(premium_rate) = fixed_rate.premium_rate {
+            fixed_rate_config.premium_rate = premium_rate;
+        }
+        if let Some(min_term) = fixed_rate.min_term {
+            fixed_rate_config.min_term = min_term;
+        }
+        if let Some(max_term) = fixed_rate.max_term {
+            fixed_rate_config.max_term = max_term;
+        }
+        store_fixed_rate_config(deps.storage, &fixed_rate_config)?;
+    }
+
+    if let Some(flash_loan_fee_rate) = flash_loan_fee_rate {
+        store_flash_loan_fee_rate(deps.storage, &flash_loan_fee_rate)?;
+    }
+
+    if let Some(borrower_rewards_mode) = borrower_rewards_mode {
+        if borrower_rewards_mode == BorrowerRewardsMode::Claimable
+            && matches!(
+                read_stable_asset(deps.storage, &config)?,
+                AssetInfo::Token { .. }
+            )
+        {
+            return Err(ContractError::Std(StdError::generic_err(
+                "claimable borrower rewards are not supported with a CW20 stable",
+            )));
+        }
+
+        // Incentives up to this block are given out with the previous mode
+        let mut state: State = read_state(deps.storage)?;
+        borrow_incentives_messages.extend(compute_interest(
+            deps.as_ref(),
+            &config,
+            &mut state,
+            env.block.height,
+            None,
+        )?);
+        store_state(deps.storage, &state)?;
+        store_borrower_rewards_mode(deps.storage, &borrower_rewards_mode)?;
+    }
+
+    if let Some(reserve_mode) = reserve_mode {
+        if let ReserveMode::ReserveFactor { reserve_factor } = reserve_mode {
+            if reserve_factor > Decimal256::one() {
+                return Err(ContractError::InvalidReserveFactor {});
+            }
+        }
+
+        // Reserves up to this block accrue with the previous mode
+        let mut state: State = read_state(deps.storage)?;
+        borrow_incentives_messages.extend(compute_interest(
+            deps.as_ref(),
+            &config,
+            &mut state,
+            env.block.height,
+            None,
+        )?);
+        store_state(deps.storage, &state)?;
+        store_reserve_mode(deps.storage, &reserve_mode)?;
+    }
+
+    if let Some(mut origination_fee) = origination_fee {
+        if origination_fee.rate >= Decimal256::one() {
+            return Err(ContractError::InvalidOriginationFee {});
+        }
+        origination_fee.receiver = origination_fee
+            .receiver
+            .map(|receiver| {
+                deps.api
+                    .addr_validate(&receiver)
+                    .map(|addr| addr.to_string())
+            })
+            .transpose()?;
+        store_origination_fee(deps.storage, &origination_fee)?;
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::new()
         .add_messages(borrow_incentives_messages)
@@ -380,10 +707,10 @@ pub fn execute_epoch_operations(
         deps.as_ref(),
         deps.api.addr_humanize(&config.aterra_contract)?,
     )?;
-    let balance: Uint256 = query_balance(
+    let balance: Uint256 = query_stable_balance(
         deps.as_ref(),
+        &config,
         deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
     )? - distributed_interest;
 
     let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
@@ -414,33 +741,37 @@ pub fn execute_epoch_operations(
 
     // Compute total_reserves to fund collector contract
     // Update total_reserves and send it to collector contract
-    // only when there is enough balance
+    // only when there is enough balance.
+    // With a reserve factor, the reserves are kept until withdrawn
     let total_reserves = state.total_reserves * Uint256::one();
-    if !total_reserves.is_zero() && balance > total_reserves {
+    if read_reserve_mode(deps.storage)? == ReserveMode::TargetDepositRate
+        && !total_reserves.is_zero()
+        && balance > total_reserves
+    {
         state.total_reserves -= Decimal256::from_ratio(total_reserves, 1u128);
+        let total_withdrawn = read_reserves_withdrawn(deps.storage)? + total_reserves;
+        store_reserves_withdrawn(deps.storage, &total_withdrawn)?;
 
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: deps
-                .api
-                .addr_humanize(&config.collector_contract)?
-                .to_string(),
-            amount: vec![Coin {
-                denom: config.stable_denom,
-                amount: total_reserves.try_into()?,
-            }],
-        }));
+        messages.push(stable_transfer_msg(
+            deps.as_ref(),
+            &config,
+            deps.api.addr_humanize(&config.collector_contract)?,
+            total_reserves,
+        )?);
     }
     // Query updated borrower_incentives_rate
-
-    state.reserves_rate_used_for_borrowers = query_borrow_reserves_incentives_rate(
-        deps.as_ref(),
-        deps.api.addr_humanize(&config.distribution_model)?,
-        deposit_rate,
-        target_deposit_rate,
-        threshold_deposit_rate,
-        state.reserves_rate_used_for_borrowers,
-    )?
-    .incentives_rate;
+    // Markets on a CW20 stable don't receive borrower incentives from the overseer
+    if let AssetInfo::NativeToken { .. } = read_stable_asset(deps.storage, &config)? {
+        state.reserves_rate_used_for_borrowers = query_borrow_reserves_incentives_rate(
+            deps.as_ref(),
+            deps.api.addr_humanize(&config.distribution_model)?,
+            deposit_rate,
+            target_deposit_rate,
+            threshold_deposit_rate,
+            state.reserves_rate_used_for_borrowers,
+        )?
+        .incentives_rate;
+    }
 
     store_state(deps.storage, &state)?;
 
@@ -454,6 +785,79 @@ pub fn execute_epoch_operations(
     ]))
 }
 
+/// Executor: owner
+pub fn withdraw_reserves(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint256,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    let mut messages =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+
+    let balance = query_stable_balance(deps.as_ref(), &config, env.contract.address)?;
+    let available = std::cmp::min(state.total_reserves * Uint256::one(), balance);
+    if amount > available {
+        return Err(ContractError::WithdrawReservesTooLarge(
+            available.try_into()?,
+        ));
+    }
+
+    state.total_reserves -= Decimal256::from_ratio(amount, 1u128);
+    store_state(deps.storage, &state)?;
+    let total_withdrawn = read_reserves_withdrawn(deps.storage)? + amount;
+    store_reserves_withdrawn(deps.storage, &total_withdrawn)?;
+
+    let to = match to {
+        Some(to) => to,
+        None => deps.api.addr_humanize(&config.collector_contract)?,
+    };
+    messages.push(stable_transfer_msg(
+        deps.as_ref(),
+        &config,
+        to.clone(),
+        amount,
+    )?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "withdraw_reserves"),
+        attr("to", to),
+        attr("amount", amount),
+    ]))
+}
+
+/// Index the borrowers of a migrated market by debt, one page per execution
+/// Executor: owner
+pub fn backfill_debt_index(
+    deps: DepsMut,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let backfill = index_borrowers_by_debt(deps.storage, limit)?;
+    let last_borrower = match backfill.last_borrower {
+        Some(borrower) => deps.api.addr_humanize(&borrower)?.to_string(),
+        None => "none".to_string(),
+    };
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "index_borrowers_by_debt"),
+        attr("last_borrower", last_borrower),
+        attr("done", backfill.done.to_string()),
+    ]))
+}
+
 pub fn _to_json_binary<T: Serialize>(r: &T) -> Result<Binary, ContractError> {
     Ok(to_json_binary(r)?)
 }
@@ -483,10 +887,88 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
             deps.api.addr_validate(&borrower)?,
             block_height,
         )?),
-        QueryMsg::BorrowerInfos { start_after, limit } => _to_json_binary(&query_borrower_infos(
+        QueryMsg::BorrowerInfos {
+            start_after,
+            limit,
+            block_height,
+        } => _to_json_binary(&query_borrower_infos(
+            deps,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+            block_height,
+        )?),
+        QueryMsg::TopBorrowers {
+            start_after,
+            limit,
+            block_height,
+        } => _to_json_binary(&query_top_borrowers(
+            deps,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+            block_height,
+        )?),
+        QueryMsg::Allowance { borrower, delegate } => _to_json_binary(&query_allowance(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+            deps.api.addr_validate(&delegate)?,
+        )?),
+        QueryMsg::Allowances {
+            borrower,
+            start_after,
+            limit,
+        } => _to_json_binary(&query_allowances(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+        )?),
+        QueryMsg::FixedRateQuote {} => _to_json_binary(&query_fixed_rate_quote(deps, env)?),
+        QueryMsg::FixedLoan {
+            loan_id,
+            block_height,
+        } => _to_json_binary(&query_fixed_loan(deps, env, loan_id, block_height)?),
+        QueryMsg::FixedLoans {
+            borrower,
+            start_after,
+            limit,
+            block_height,
+        } => _to_json_binary(&query_fixed_loans(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+            start_after,
+            limit,
+            block_height,
+        )?),
+        QueryMsg::Redemptions {
+            redeemer,
+            start_after,
+            limit,
+        } => _to_json_binary(&query_redemptions(
+            deps,
+            deps.api.addr_validate(&redeemer)?,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Reserves { block_height } => {
+            _to_json_binary(&query_reserves(deps, env, block_height)?)
+        }
+        QueryMsg::InterestTiers { start_after, limit } => {
+            _to_json_binary(&query_interest_tiers(deps, start_after, limit)?)
+        }
+        QueryMsg::FeesCollected {} => _to_json_binary(&query_fees_collected(deps)?),
+        QueryMsg::LiabilitiesDrift {
+            start_after,
+            limit,
+            block_height,
+            scanned,
+        } => _to_json_binary(&query_liabilities_drift(
             deps,
+            env,
             optional_addr_validate(deps.api, start_after)?,
             limit,
+            block_height,
+            scanned,
         )?),
     }
 }
@@ -518,9 +1000,14 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
             .api
             .addr_humanize(&config.borrow_reserves_bucket_contract)?
             .to_string(),
+        stable_asset: read_stable_asset(deps.storage, &config)?,
         stable_denom: config.stable_denom,
         max_borrow_factor: config.max_borrow_factor,
         max_borrow_subsidy_rate: config.max_borrow_subsidy_rate,
+        flash_loan_fee_rate: read_flash_loan_fee_rate(deps.storage)?,
+        borrower_rewards_mode: read_borrower_rewards_mode(deps.storage)?,
+        reserve_mode: read_reserve_mode(deps.storage)?,
+        origination_fee: read_origination_fee(deps.storage)?,
     })
 }
 
@@ -565,6 +1052,33 @@ pub fn query_state(
         prev_aterra_supply: state.prev_aterra_supply,
         prev_exchange_rate: state.prev_exchange_rate,
         prev_borrower_incentives: state.prev_borrower_incentives,
+        reserved_borrower_rewards: state.reserved_borrower_rewards,
+        global_claimable_reward_index: state.global_claimable_reward_index,
+    })
+}
+
+pub fn query_reserves(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+) -> Result<ReservesResponse, ContractError> {
+    let state = query_state(deps, env, block_height)?;
+    let total_withdrawn = read_reserves_withdrawn(deps.storage)?;
+
+    Ok(ReservesResponse {
+        reserve_mode: read_reserve_mode(deps.storage)?,
+        total_reserves: state.total_reserves,
+        total_withdrawn,
+        total_accrued: state.total_reserves + Decimal256::from_ratio(total_withdrawn, 1u128),
+    })
+}
+
+pub fn query_fees_collected(deps: Deps) -> StdResult<FeesCollectedResponse> {
+    let fees_collected = read_fees_collected(deps.storage)?;
+
+    Ok(FeesCollectedResponse {
+        origination: fees_collected.origination,
+        flash_loan: fees_collected.flash_loan,
     })
 }
 
@@ -578,10 +1092,10 @@ pub fn query_epoch_state(
 
     let distributed_interest = distributed_interest.unwrap_or_else(Uint256::zero);
     let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
-    let balance = query_balance(
+    let balance = query_stable_balance(
         deps,
+        &config,
         deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
     )? - distributed_interest;
 
     let last_interest_updated = state.last_interest_updated;
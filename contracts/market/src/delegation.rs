@@ -0,0 +1,127 @@
+use cosmwasm_std::{attr, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint256};
+use moneymarket::market::{AllowanceResponse, AllowancesResponse};
+use std::convert::TryInto;
+
+use crate::borrow::borrow_stable;
+use crate::error::ContractError;
+use crate::state::{read_allowance, read_allowances, remove_allowance, store_allowance};
+
+/// Set the amount a delegate can borrow on behalf of the sender
+/// Executor: borrower
+pub fn approve_delegate(
+    deps: DepsMut,
+    info: MessageInfo,
+    delegate: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let delegate_raw = deps.api.addr_canonicalize(delegate.as_str())?;
+    store_allowance(deps.storage, &borrower_raw, &delegate_raw, &amount)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "approve_delegate"),
+        attr("borrower", info.sender),
+        attr("delegate", delegate),
+        attr("amount", amount),
+    ]))
+}
+
+/// Executor: borrower
+pub fn revoke_delegate(
+    deps: DepsMut,
+    info: MessageInfo,
+    delegate: Addr,
+) -> Result<Response, ContractError> {
+    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let delegate_raw = deps.api.addr_canonicalize(delegate.as_str())?;
+    remove_allowance(deps.storage, &borrower_raw, &delegate_raw);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "revoke_delegate"),
+        attr("borrower", info.sender),
+        attr("delegate", delegate),
+    ]))
+}
+
+/// Borrow against the collaterals of the borrower and send the funds to the delegate.
+/// The overseer borrow limit of the borrower still applies
+/// Executor: delegate
+pub fn borrow_stable_on_behalf(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let delegate = info.sender.clone();
+    let delegate_raw = deps.api.addr_canonicalize(delegate.as_str())?;
+
+    let allowance = read_allowance(deps.storage, &borrower_raw, &delegate_raw);
+    if allowance < amount {
+        return Err(ContractError::AllowanceExceeded(allowance.try_into()?));
+    }
+
+    if allowance == amount {
+        remove_allowance(deps.storage, &borrower_raw, &delegate_raw);
+    } else {
+        store_allowance(
+            deps.storage,
+            &borrower_raw,
+            &delegate_raw,
+            &(allowance - amount),
+        )?;
+    }
+
+    // override env
+    let mut info = info;
+    info.sender = borrower;
+
+    let res = borrow_stable(deps, env, info, amount, Some(delegate.clone()))?;
+    Ok(res.add_attribute("delegate", delegate))
+}
+
+pub fn query_allowance(deps: Deps, borrower: Addr, delegate: Addr) -> StdResult<AllowanceResponse> {
+    let allowance = read_allowance(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+        &deps.api.addr_canonicalize(delegate.as_str())?,
+    );
+
+    Ok(AllowanceResponse {
+        borrower: borrower.to_string(),
+        delegate: delegate.to_string(),
+        allowance,
+    })
+}
+
+pub fn query_allowances(
+    deps: Deps,
+    borrower: Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<AllowancesResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    let allowances = read_allowances(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+        start_after,
+        limit,
+    )?
+    .into_iter()
+    .map(|(delegate, allowance)| {
+        Ok(AllowanceResponse {
+            borrower: borrower.to_string(),
+            delegate: deps.api.addr_humanize(&delegate)?.to_string(),
+            allowance,
+        })
+    })
+    .collect::<StdResult<Vec<AllowanceResponse>>>()?;
+
+    Ok(AllowancesResponse { allowances })
+}
@@ -1,31 +1,26 @@
 use cosmwasm_std::{
-    attr, to_json_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal256, Deps, DepsMut, Env, MessageInfo,
-    Response, StdResult, Uint128, Uint256, WasmMsg,
+    attr, to_json_binary, Addr, CosmosMsg, Decimal256, Deps, DepsMut, Env, Response, StdResult,
+    Uint128, Uint256, WasmMsg,
 };
 use std::convert::TryInto;
 
 use crate::borrow::compute_interest;
 use crate::error::ContractError;
+use crate::redemption::fill_redemptions;
+use crate::stable::{query_stable_balance, stable_transfer_msg};
 use crate::state::{read_config, read_state, store_state, Config, State};
 
 use cw20::Cw20ExecuteMsg;
-use moneymarket::querier::{query_balance, query_supply};
+use moneymarket::querier::query_supply;
 
 pub fn deposit_stable(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
-    info: MessageInfo,
+    depositor: Addr,
+    deposit_amount: Uint256,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
-    // Check base denom deposit
-    let deposit_amount: Uint256 = info
-        .funds
-        .iter()
-        .find(|c| c.denom == config.stable_denom)
-        .map(|c| Uint256::from(c.amount))
-        .unwrap_or_else(Uint256::zero);
-
     // Cannot deposit zero amount
     if deposit_amount.is_zero() {
         return Err(ContractError::ZeroDeposit(config.stable_denom));
@@ -48,20 +43,31 @@ pub fn deposit_stable(
         Decimal256::from_ratio(deposit_amount, 1u128) / exchange_rate * Uint256::one();
 
     state.prev_aterra_supply += mint_amount;
+
+    // The new deposit fills the redemption queue
+    let redemption_messages = fill_redemptions(
+        deps.branch(),
+        &env,
+        &config,
+        &mut state,
+        exchange_rate,
+        Uint256::zero(),
+    )?;
     store_state(deps.storage, &state)?;
     Ok(Response::new()
         .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
             funds: vec![],
             msg: to_json_binary(&Cw20ExecuteMsg::Mint {
-                recipient: info.sender.to_string(),
+                recipient: depositor.to_string(),
                 amount: mint_amount.try_into()?,
             })?,
         }))
+        .add_messages(redemption_messages)
         .add_messages(borrow_incentives_messages)
         .add_attributes(vec![
             attr("action", "deposit_stable"),
-            attr("depositor", info.sender),
+            attr("depositor", depositor),
             attr("mint_amount", mint_amount),
             attr("deposit_amount", deposit_amount),
         ]))
@@ -84,11 +90,7 @@ pub fn redeem_stable(
     let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, None)?;
     let redeem_amount = Uint256::from(burn_amount) * exchange_rate;
 
-    let current_balance = query_balance(
-        deps.as_ref(),
-        env.contract.address,
-        config.stable_denom.to_string(),
-    )?;
+    let current_balance = query_stable_balance(deps.as_ref(), &config, env.contract.address)?;
 
     // Assert redeem amount
     assert_redeem_amount(&config, &state, current_balance, redeem_amount)?;
@@ -104,13 +106,7 @@ pub fn redeem_stable(
                     amount: burn_amount,
                 })?,
             }),
-            CosmosMsg::Bank(BankMsg::Send {
-                to_address: sender.to_string(),
-                amount: vec![Coin {
-                    denom: config.stable_denom,
-                    amount: redeem_amount.try_into()?,
-                }],
-            }),
+            stable_transfer_msg(deps.as_ref(), &config, sender, redeem_amount)?,
         ])
         .add_messages(borrow_incentives_messages)
         .add_attributes(vec![
@@ -144,10 +140,10 @@ pub(crate) fn compute_exchange_rate(
     deposit_amount: Option<Uint256>,
 ) -> StdResult<Decimal256> {
     let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
-    let balance = query_balance(
+    let balance = query_stable_balance(
         deps,
+        config,
         deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
     )? - deposit_amount.unwrap_or_else(Uint256::zero);
 
     Ok(compute_exchange_rate_raw(state, aterra_supply, balance))
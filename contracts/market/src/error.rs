@@ -1,4 +1,6 @@
-use cosmwasm_std::{ConversionOverflowError, OverflowError, StdError, Uint128, WasmMsg};
+use cosmwasm_std::{
+    ConversionOverflowError, Decimal256, OverflowError, StdError, Uint128, WasmMsg,
+};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -21,6 +23,36 @@ pub enum ContractError {
     #[error("Must deposit initial funds {0}{1}")]
     InitialFundsNotDeposited(Uint128, String),
 
+    #[error("Borrow amount exceeds the delegate allowance: {0}")]
+    AllowanceExceeded(Uint128),
+
+    #[error("Fixed rate loan {0} has not reached maturity yet")]
+    FixedLoanNotMatured(u64),
+
+    #[error("Fixed rate loan term must be between {0} and {1} blocks")]
+    InvalidFixedTerm(u64, u64),
+
+    #[error("A flash loan is already in progress")]
+    FlashLoanInProgress {},
+
+    #[error("Flash loan not paid back; {0} expected")]
+    FlashLoanNotRepaid(Uint128),
+
+    #[error("Interest tier discount should not be greater than 1")]
+    InvalidDiscount {},
+
+    #[error("Reserve factor should not be greater than 1")]
+    InvalidReserveFactor {},
+
+    #[error("Origination fee rate should be lower than 1")]
+    InvalidOriginationFee {},
+
+    #[error("Withdraw amount exceeds the available reserves: {0}")]
+    WithdrawReservesTooLarge(Uint128),
+
+    #[error("Liabilities drift exceeds the max drift: {0}")]
+    LiabilitiesDriftTooLarge(Decimal256),
+
     #[error("Invalid reply ID")]
     InvalidReplyId {},
 
@@ -0,0 +1,470 @@
+use cosmwasm_std::{
+    attr, Addr, CanonicalAddr, CosmosMsg, Decimal256, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, Storage, Uint256,
+};
+use moneymarket::interest_model::BorrowRateResponse;
+use moneymarket::market::{FixedLoanResponse, FixedLoansResponse, FixedRateQuoteResponse};
+use moneymarket::overseer::BorrowLimitResponse;
+use std::convert::TryInto;
+
+use crate::borrow::{
+    assert_max_borrow_factor, compute_borrower_reward, compute_interest,
+    compute_tiered_borrower_interest,
+};
+use crate::deposit::compute_exchange_rate;
+use crate::error::ContractError;
+use crate::flash_loan::credit_flash_loan;
+use crate::querier::{query_borrow_limit, query_borrow_rate};
+use crate::redemption::fill_redemptions;
+use crate::stable::{query_stable_balance, stable_transfer_msg};
+use crate::state::{
+    read_borrower_info, read_config, read_fees_collected, read_fixed_loan, read_fixed_loan_amount,
+    read_fixed_loan_state, read_fixed_loans_by_borrower, read_fixed_rate_config,
+    read_origination_fee, read_state, remove_fixed_loan, store_borrower_info, store_fees_collected,
+    store_fixed_loan, store_fixed_loan_state, store_state, BorrowerInfo, Config, FixedLoan,
+    FixedLoanState, FixedRateConfig, State,
+};
+
+pub fn borrow_fixed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrow_amount: Uint256,
+    term: u64,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let fixed_rate_config: FixedRateConfig = read_fixed_rate_config(deps.storage)?;
+    assert_fixed_term(&fixed_rate_config, term)?;
+
+    let mut state: State = read_state(deps.storage)?;
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    // Compute interest
+    let borrow_incentives_messages =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_tiered_borrower_interest(deps.storage, &state, &mut liability)?;
+
+    compute_borrower_reward(&state, &mut liability);
+
+    // Fixed rate loans share the borrow limit with the floating rate loan
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
+        deps.as_ref(),
+        overseer,
+        env.contract.address.clone(),
+        borrower.clone(),
+        Some(env.block.time.seconds()),
+    )?;
+    let fixed_loan_amount = read_fixed_loan_amount(deps.storage, &borrower_raw, env.block.height)?;
+
+    // The origination fee is added to the principal or kept out of the sent amount
+    let origination_fee = read_origination_fee(deps.storage)?;
+    let fee_amount = borrow_amount * origination_fee.rate;
+    let (loan_amount, send_amount) = if origination_fee.deduct_from_disbursement {
+        (borrow_amount, borrow_amount - fee_amount)
+    } else {
+        (borrow_amount + fee_amount, borrow_amount)
+    };
+
+    if borrow_limit_res.borrow_limit < loan_amount + liability.loan_amount + fixed_loan_amount {
+        return Err(ContractError::BorrowExceedsLimit(
+            borrow_limit_res.borrow_limit.try_into()?,
+        ));
+    }
+
+    let current_balance = query_stable_balance(deps.as_ref(), &config, env.contract.address)?;
+
+    // Assert borrow amount
+    assert_max_borrow_factor(&config, &state, current_balance, loan_amount)?;
+
+    // The rate is quoted before the loan is added to the liabilities
+    let rate = quote_fixed_rate(
+        deps.as_ref(),
+        &config,
+        &state,
+        &fixed_rate_config,
+        current_balance,
+    )?;
+
+    let mut fixed_loan_state: FixedLoanState = read_fixed_loan_state(deps.storage)?;
+    let loan_id = fixed_loan_state.next_loan_id;
+    fixed_loan_state.next_loan_id += 1;
+
+    let loan = FixedLoan {
+        borrower: borrower_raw.clone(),
+        principal: loan_amount,
+        rate,
+        start_height: env.block.height,
+        maturity_height: env.block.height + term,
+    };
+    open_fixed_loan(&mut state, &mut fixed_loan_state, &loan);
+
+    let mut messages: Vec<CosmosMsg> = vec![stable_transfer_msg(
+        deps.as_ref(),
+        &config,
+        to.unwrap_or_else(|| borrower.clone()),
+        send_amount,
+    )?];
+    if !fee_amount.is_zero() {
+        match origination_fee.receiver {
+            Some(receiver) => messages.push(stable_transfer_msg(
+                deps.as_ref(),
+                &config,
+                Addr::unchecked(receiver),
+                fee_amount,
+            )?),
+            None => state.total_reserves += Decimal256::from_ratio(fee_amount, 1u128),
+        }
+
+        let mut fees_collected = read_fees_collected(deps.storage)?;
+        fees_collected.origination += fee_amount;
+        store_fees_collected(deps.storage, &fees_collected)?;
+    }
+
+    store_fixed_loan(deps.storage, loan_id, &loan)?;
+    store_fixed_loan_state(deps.storage, &fixed_loan_state)?;
+    store_state(deps.storage, &state)?;
+    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+
+    let mut attributes = vec![
+        attr("action", "borrow_fixed"),
+        attr("borrower", borrower),
+        attr("loan_id", loan_id.to_string()),
+        attr("borrow_amount", borrow_amount),
+        attr("rate", rate.to_string()),
+        attr("maturity_height", loan.maturity_height.to_string()),
+    ];
+    if !fee_amount.is_zero() {
+        attributes.push(attr("origination_fee", fee_amount));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_messages(borrow_incentives_messages)
+        .add_attributes(attributes))
+}
+
+pub fn repay_fixed(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    amount: Uint256,
+    loan_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // Cannot repay zero amount
+    if amount.is_zero() {
+        return Err(ContractError::ZeroRepay(config.stable_denom));
+    }
+
+    let loan: FixedLoan = read_fixed_loan(deps.storage, loan_id)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    // Compute interest, so the liabilities include the loan interest up to now
+    let borrow_incentives_messages = compute_interest(
+        deps.as_ref(),
+        &config,
+        &mut state,
+        env.block.height,
+        Some(amount),
+    )?;
+
+    let mut fixed_loan_state: FixedLoanState = read_fixed_loan_state(deps.storage)?;
+    let repay_amount = apply_fixed_repayment(
+        deps.storage,
+        &mut state,
+        &mut fixed_loan_state,
+        loan_id,
+        &loan,
+        amount,
+        env.block.height,
+    )?;
+
+    credit_flash_loan(deps.storage, repay_amount)?;
+
+    let refund_amount = amount - repay_amount;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !refund_amount.is_zero() {
+        // Payback left repay amount to sender
+        messages.push(stable_transfer_msg(
+            deps.as_ref(),
+            &config,
+            sender,
+            refund_amount,
+        )?);
+    }
+
+    // The repaid stable fills the redemption queue
+    let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, Some(refund_amount))?;
+    messages.extend(fill_redemptions(
+        deps.branch(),
+        &env,
+        &config,
+        &mut state,
+        exchange_rate,
+        refund_amount,
+    )?);
+
+    store_fixed_loan_state(deps.storage, &fixed_loan_state)?;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_messages(borrow_incentives_messages)
+        .add_attributes(vec![
+            attr("action", "repay_fixed"),
+            attr("borrower", deps.api.addr_humanize(&loan.borrower)?),
+            attr("loan_id", loan_id.to_string()),
+            attr("repay_amount", repay_amount),
+        ]))
+}
+
+pub fn rollover_fixed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    loan_id: u64,
+    term: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let fixed_rate_config: FixedRateConfig = read_fixed_rate_config(deps.storage)?;
+    assert_fixed_term(&fixed_rate_config, term)?;
+
+    let loan: FixedLoan = read_fixed_loan(deps.storage, loan_id)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != loan.borrower {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if env.block.height < loan.maturity_height {
+        return Err(ContractError::FixedLoanNotMatured(loan_id));
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    let borrow_incentives_messages =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+
+    let current_balance = query_stable_balance(deps.as_ref(), &config, env.contract.address)?;
+    let rate = quote_fixed_rate(
+        deps.as_ref(),
+        &config,
+        &state,
+        &fixed_rate_config,
+        current_balance,
+    )?;
+
+    // The amount due becomes the principal of the new term
+    let mut fixed_loan_state: FixedLoanState = read_fixed_loan_state(deps.storage)?;
+    let principal = loan.amount_due(env.block.height) * Uint256::one();
+    close_fixed_loan(&mut state, &mut fixed_loan_state, &loan, env.block.height);
+
+    let new_loan = FixedLoan {
+        borrower: loan.borrower,
+        principal,
+        rate,
+        start_height: env.block.height,
+        maturity_height: env.block.height + term,
+    };
+    open_fixed_loan(&mut state, &mut fixed_loan_state, &new_loan);
+
+    store_fixed_loan(deps.storage, loan_id, &new_loan)?;
+    store_fixed_loan_state(deps.storage, &fixed_loan_state)?;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_messages(borrow_incentives_messages)
+        .add_attributes(vec![
+            attr("action", "rollover_fixed"),
+            attr("borrower", info.sender),
+            attr("loan_id", loan_id.to_string()),
+            attr("principal", principal),
+            attr("rate", rate.to_string()),
+            attr("maturity_height", new_loan.maturity_height.to_string()),
+        ]))
+}
+
+/// Repay the fixed rate loans of a borrower in creation order
+/// and return the amount that was used.
+/// The interest must be computed up to `block_height` beforehand
+pub(crate) fn repay_fixed_loans(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    borrower_raw: &CanonicalAddr,
+    amount: Uint256,
+    block_height: u64,
+) -> StdResult<Uint256> {
+    let mut fixed_loan_state: FixedLoanState = read_fixed_loan_state(storage)?;
+    let mut left_amount = amount;
+    for (loan_id, loan) in read_fixed_loans_by_borrower(storage, borrower_raw, None, None)? {
+        if left_amount.is_zero() {
+            break;
+        }
+
+        left_amount -= apply_fixed_repayment(
+            storage,
+            state,
+            &mut fixed_loan_state,
+            loan_id,
+            &loan,
+            left_amount,
+            block_height,
+        )?;
+    }
+
+    store_fixed_loan_state(storage, &fixed_loan_state)?;
+    Ok(amount - left_amount)
+}
+
+/// Repay up to `amount` of a fixed rate loan and return the amount used.
+/// A partial repayment restarts the loan with the amount left due as principal
+fn apply_fixed_repayment(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    fixed_loan_state: &mut FixedLoanState,
+    loan_id: u64,
+    loan: &FixedLoan,
+    amount: Uint256,
+    block_height: u64,
+) -> StdResult<Uint256> {
+    let amount_due = loan.amount_due(block_height) * Uint256::one();
+    close_fixed_loan(state, fixed_loan_state, loan, block_height);
+
+    if amount_due <= amount {
+        remove_fixed_loan(storage, loan_id, loan);
+        return Ok(amount_due);
+    }
+
+    let new_loan = FixedLoan {
+        principal: amount_due - amount,
+        start_height: block_height,
+        ..loan.clone()
+    };
+    open_fixed_loan(state, fixed_loan_state, &new_loan);
+    store_fixed_loan(storage, loan_id, &new_loan)?;
+
+    Ok(amount)
+}
+
+fn open_fixed_loan(state: &mut State, fixed_loan_state: &mut FixedLoanState, loan: &FixedLoan) {
+    fixed_loan_state.add_loan(loan);
+    state.total_liabilities += Decimal256::from_ratio(loan.principal, 1u128);
+}
+
+fn close_fixed_loan(
+    state: &mut State,
+    fixed_loan_state: &mut FixedLoanState,
+    loan: &FixedLoan,
+    block_height: u64,
+) {
+    fixed_loan_state.remove_loan(loan);
+    state.total_liabilities = state
+        .total_liabilities
+        .checked_sub(loan.amount_due(block_height))
+        .unwrap_or_else(|_| Decimal256::zero());
+}
+
+fn assert_fixed_term(fixed_rate_config: &FixedRateConfig, term: u64) -> Result<(), ContractError> {
+    if term == 0 || term < fixed_rate_config.min_term || term > fixed_rate_config.max_term {
+        return Err(ContractError::InvalidFixedTerm(
+            fixed_rate_config.min_term,
+            fixed_rate_config.max_term,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Fixed rate quote: the current floating borrow rate plus the premium
+fn quote_fixed_rate(
+    deps: Deps,
+    config: &Config,
+    state: &State,
+    fixed_rate_config: &FixedRateConfig,
+    balance: Uint256,
+) -> StdResult<Decimal256> {
+    let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
+        deps,
+        deps.api.addr_humanize(&config.interest_model)?,
+        balance,
+        state.total_liabilities,
+        state.total_reserves,
+    )?;
+
+    Ok(borrow_rate_res.rate + fixed_rate_config.premium_rate)
+}
+
+pub fn query_fixed_rate_quote(deps: Deps, env: Env) -> StdResult<FixedRateQuoteResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let state: State = read_state(deps.storage)?;
+    let fixed_rate_config: FixedRateConfig = read_fixed_rate_config(deps.storage)?;
+
+    let balance = query_stable_balance(deps, &config, env.contract.address)?;
+    let rate = quote_fixed_rate(deps, &config, &state, &fixed_rate_config, balance)?;
+
+    Ok(FixedRateQuoteResponse {
+        rate,
+        premium_rate: fixed_rate_config.premium_rate,
+        min_term: fixed_rate_config.min_term,
+        max_term: fixed_rate_config.max_term,
+    })
+}
+
+pub fn query_fixed_loan(
+    deps: Deps,
+    env: Env,
+    loan_id: u64,
+    block_height: Option<u64>,
+) -> StdResult<FixedLoanResponse> {
+    let loan: FixedLoan = read_fixed_loan(deps.storage, loan_id)?;
+    fixed_loan_response(
+        deps,
+        loan_id,
+        loan,
+        block_height.unwrap_or(env.block.height),
+    )
+}
+
+pub fn query_fixed_loans(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    block_height: Option<u64>,
+) -> StdResult<FixedLoansResponse> {
+    let block_height = block_height.unwrap_or(env.block.height);
+    let loans = read_fixed_loans_by_borrower(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+        start_after,
+        Some(limit.unwrap_or(DEFAULT_LIMIT)),
+    )?
+    .into_iter()
+    .map(|(loan_id, loan)| fixed_loan_response(deps, loan_id, loan, block_height))
+    .collect::<StdResult<Vec<FixedLoanResponse>>>()?;
+
+    Ok(FixedLoansResponse { loans })
+}
+
+const DEFAULT_LIMIT: u32 = 10;
+
+fn fixed_loan_response(
+    deps: Deps,
+    loan_id: u64,
+    loan: FixedLoan,
+    block_height: u64,
+) -> StdResult<FixedLoanResponse> {
+    Ok(FixedLoanResponse {
+        loan_id,
+        borrower: deps.api.addr_humanize(&loan.borrower)?.to_string(),
+        principal: loan.principal,
+        rate: loan.rate,
+        start_height: loan.start_height,
+        maturity_height: loan.maturity_height,
+        amount_due: loan.amount_due(block_height) * Uint256::one(),
+    })
+}
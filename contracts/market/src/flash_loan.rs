@@ -0,0 +1,155 @@
+use cosmwasm_std::{
+    attr, Binary, Coin, CosmosMsg, Decimal256, DepsMut, Env, MessageInfo, Response, StdResult,
+    Storage, SubMsg, Uint256,
+};
+use moneymarket::astroport_router::AssetInfo;
+use moneymarket::market::FlashLoanReceiveMsg;
+use std::convert::TryInto;
+
+use crate::borrow::compute_interest;
+use crate::error::ContractError;
+use crate::stable::{query_stable_balance, stable_transfer_msg};
+use crate::state::{
+    read_config, read_fees_collected, read_flash_loan, read_flash_loan_fee_rate, read_stable_asset,
+    read_state, remove_flash_loan, store_fees_collected, store_flash_loan, store_state, Config,
+    FlashLoan, State,
+};
+
+pub const FLASH_LOAN_REPLY_ID: u64 = 2;
+
+/// Send stable asset to the sender contract and call it back.
+/// The repayment is checked in the reply
+pub fn flash_loan(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint256,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    // Interest is computed before the loan, so that the repayments made during
+    // the loan don't pull borrower incentives into the market balance
+    let borrow_incentives_messages =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    store_state(deps.storage, &state)?;
+    let borrow_incentives = if borrow_incentives_messages.is_empty() {
+        Uint256::zero()
+    } else {
+        state.prev_borrower_incentives
+    };
+
+    // Reserves are not lent out
+    let prev_balance = query_stable_balance(deps.as_ref(), &config, env.contract.address)?;
+    if Decimal256::from_ratio(amount, 1u128) + state.total_reserves
+        > Decimal256::from_ratio(prev_balance, 1u128)
+    {
+        return Err(ContractError::NoStableAvailable(config.stable_denom));
+    }
+
+    let fee = amount * read_flash_loan_fee_rate(deps.storage)?;
+    store_flash_loan(
+        deps.storage,
+        &FlashLoan {
+            receiver: deps.api.addr_canonicalize(info.sender.as_str())?,
+            amount,
+            fee,
+            prev_balance: prev_balance + borrow_incentives,
+            repaid: Uint256::zero(),
+        },
+    )?;
+
+    // Native stables are sent along with the callback,
+    // CW20 stables are transferred right before it
+    let (messages, funds): (Vec<CosmosMsg>, Vec<Coin>) =
+        match read_stable_asset(deps.storage, &config)? {
+            AssetInfo::NativeToken { denom } => (
+                vec![],
+                vec![Coin {
+                    denom,
+                    amount: amount.try_into()?,
+                }],
+            ),
+            AssetInfo::Token { .. } => (
+                vec![stable_transfer_msg(
+                    deps.as_ref(),
+                    &config,
+                    info.sender.clone(),
+                    amount,
+                )?],
+                vec![],
+            ),
+        };
+
+    Ok(Response::new()
+        .add_messages(borrow_incentives_messages)
+        .add_messages(messages)
+        .add_submessage(SubMsg::reply_on_success(
+            FlashLoanReceiveMsg { amount, fee, msg }
+                .into_cosmos_msg(info.sender.to_string(), funds)?,
+            FLASH_LOAN_REPLY_ID,
+        ))
+        .add_attributes(vec![
+            attr("action", "flash_loan"),
+            attr("receiver", info.sender),
+            attr("amount", amount),
+            attr("fee", fee),
+        ]))
+}
+
+/// Check the loan and its fee came back, the fee is added to the reserves
+pub fn repay_flash_loan(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let flash_loan = match read_flash_loan(deps.storage)? {
+        Some(flash_loan) => flash_loan,
+        None => return Err(ContractError::InvalidReplyId {}),
+    };
+
+    let cur_balance = query_stable_balance(deps.as_ref(), &config, env.contract.address)?;
+    let expected_balance = flash_loan.prev_balance + flash_loan.fee + flash_loan.repaid;
+    if cur_balance < expected_balance {
+        return Err(ContractError::FlashLoanNotRepaid(
+            (flash_loan.amount + flash_loan.fee).try_into()?,
+        ));
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    state.total_reserves += Decimal256::from_ratio(flash_loan.fee, 1u128);
+    store_state(deps.storage, &state)?;
+    remove_flash_loan(deps.storage);
+
+    let mut fees_collected = read_fees_collected(deps.storage)?;
+    fees_collected.flash_loan += flash_loan.fee;
+    store_fees_collected(deps.storage, &fees_collected)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "repay_flash_loan"),
+        attr(
+            "receiver",
+            deps.api.addr_humanize(&flash_loan.receiver)?.to_string(),
+        ),
+        attr("fee", flash_loan.fee),
+    ]))
+}
+
+/// Only repayments can run while a loan is in flight,
+/// anything else would move the balance the loan is checked against
+pub(crate) fn assert_no_flash_loan(storage: &dyn Storage) -> Result<(), ContractError> {
+    if read_flash_loan(storage)?.is_some() {
+        return Err(ContractError::FlashLoanInProgress {});
+    }
+
+    Ok(())
+}
+
+/// Stable repaid while a loan is in flight is added to the balance
+/// expected back from the loan
+pub(crate) fn credit_flash_loan(storage: &mut dyn Storage, amount: Uint256) -> StdResult<()> {
+    if let Some(mut flash_loan) = read_flash_loan(storage)? {
+        flash_loan.repaid += amount;
+        store_flash_loan(storage, &flash_loan)?;
+    }
+
+    Ok(())
+}
@@ -1,10 +1,17 @@
 pub mod borrow;
 pub mod contract;
+pub mod delegation;
 pub mod deposit;
 pub mod error;
+pub mod fixed;
+pub mod flash_loan;
 pub mod querier;
+pub mod reconcile;
+pub mod redemption;
 pub mod response;
+pub mod stable;
 pub mod state;
+pub mod tier;
 
 #[cfg(test)]
 mod testing;
@@ -3,7 +3,6 @@ use cosmwasm_std::{
     to_json_binary, Addr, Decimal256, Deps, QueryRequest, StdResult, Uint256, WasmQuery,
 };
 use cosmwasm_std::{Env, StdError};
-use moneymarket::querier::query_balance;
 
 use moneymarket::distribution_model::{
     BorrowerIncentivesRateResponse, QueryMsg as DistributionQueryMsg,
@@ -11,6 +10,7 @@ use moneymarket::distribution_model::{
 use moneymarket::interest_model::{BorrowRateResponse, QueryMsg as InterestQueryMsg};
 use moneymarket::overseer::{BorrowLimitResponse, ConfigResponse, QueryMsg as OverseerQueryMsg};
 
+use crate::stable::{query_bucket_balance, query_stable_balance};
 use crate::state::{read_config, read_state};
 
 pub fn query_borrow_rate(
@@ -45,17 +45,14 @@ pub fn query_next_borrower_incentives(
         return Err(StdError::generic_err("Can't query borrow rate in the past"));
     }
     let passed_blocks = Decimal256::from_ratio(block_height - state.last_interest_updated, 1u128);
-    let mut available_borrower_incentives = query_balance(
-        deps,
-        deps.api
-            .addr_humanize(&config.borrow_reserves_bucket_contract)?,
-        config.stable_denom.to_string(),
-    )?;
+    let mut available_borrower_incentives = query_bucket_balance(deps, &config)?
+        .checked_sub(state.reserved_borrower_rewards)
+        .unwrap_or_else(|_| Uint256::zero());
 
-    let balance = query_balance(
+    let balance = query_stable_balance(
         deps,
+        &config,
         deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
     )?;
 
     let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
@@ -90,9 +87,12 @@ pub fn query_next_borrower_incentives(
     })
 }
 
+/// Borrow limit of the borrower in this market,
+/// the overseer deducts the loans taken in the other markets
 pub fn query_borrow_limit(
     deps: Deps,
     overseer_addr: Addr,
+    market_addr: Addr,
     borrower: Addr,
     block_time: Option<u64>,
 ) -> StdResult<BorrowLimitResponse> {
@@ -102,6 +102,7 @@ pub fn query_borrow_limit(
             msg: to_json_binary(&OverseerQueryMsg::BorrowLimit {
                 borrower: borrower.to_string(),
                 block_time,
+                market: Some(market_addr.to_string()),
             })?,
         }))?;
 
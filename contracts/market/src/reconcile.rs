@@ -0,0 +1,186 @@
+use cosmwasm_std::{
+    attr, Addr, CanonicalAddr, Decimal256, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    Storage,
+};
+use moneymarket::market::{LiabilitiesDriftResponse, LiabilitiesScan};
+
+use crate::borrow::{compute_interest, compute_tiered_borrower_interest};
+use crate::error::ContractError;
+use crate::state::{
+    read_borrower_infos, read_config, read_fixed_loan_state, read_reconcile_scan, read_state,
+    remove_reconcile_scan, store_reconcile_scan, store_state, BorrowerInfo, Config, ReconcileScan,
+    State,
+};
+
+const RECONCILE_PAGE_LIMIT: u32 = 100;
+
+/// Book the floating rate liabilities at the sum of the interest-accrued loans.
+/// The rounding dust is taken from or added to the reserves so that
+/// the exchange rate does not move. The borrowers are scanned one page per
+/// execution, the liabilities are only adjusted after the final page
+/// Executor: owner
+pub fn reconcile_liabilities(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    max_drift: Decimal256,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    let messages = compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+
+    // Resume the scan in progress, its loans accrue with the global interest index
+    let mut scan = read_reconcile_scan(deps.storage)?;
+    if let Some(scan) = scan.as_mut() {
+        scan.accrue(state.global_interest_index);
+    }
+
+    let limit = limit
+        .unwrap_or(RECONCILE_PAGE_LIMIT)
+        .min(RECONCILE_PAGE_LIMIT);
+    let borrower_infos = read_borrower_infos(
+        deps.storage,
+        scan.as_ref().map(|scan| scan.last_borrower.clone()),
+        Some(limit),
+    )?;
+    let last_borrower = match borrower_infos.last() {
+        Some((borrower, _)) if borrower_infos.len() == limit as usize => Some(borrower.clone()),
+        _ => None,
+    };
+
+    let mut scanned = LiabilitiesScan::default();
+    scan_liabilities(deps.storage, &state, borrower_infos, &mut scanned)?;
+    let scanned_liabilities =
+        scan.map_or_else(Decimal256::zero, |scan| scan.loans) + scanned_liabilities(&scanned);
+
+    // More borrowers are left to scan
+    if let Some(last_borrower) = last_borrower {
+        store_state(deps.storage, &state)?;
+        store_reconcile_scan(
+            deps.storage,
+            &ReconcileScan {
+                last_borrower: last_borrower.clone(),
+                loans: scanned_liabilities,
+                global_interest_index: state.global_interest_index,
+            },
+        )?;
+
+        return Ok(Response::new().add_messages(messages).add_attributes(vec![
+            attr("action", "reconcile_liabilities"),
+            attr("last_borrower", deps.api.addr_humanize(&last_borrower)?),
+        ]));
+    }
+    remove_reconcile_scan(deps.storage);
+
+    let floating_liabilities = floating_liabilities(deps.storage, &state)?;
+    let (excess_liabilities, missing_liabilities) = if floating_liabilities > scanned_liabilities {
+        let excess_liabilities = floating_liabilities - scanned_liabilities;
+        if excess_liabilities > max_drift {
+            return Err(ContractError::LiabilitiesDriftTooLarge(excess_liabilities));
+        }
+
+        state.total_liabilities -= excess_liabilities;
+        state.total_reserves = state
+            .total_reserves
+            .checked_sub(excess_liabilities)
+            .unwrap_or_else(|_| Decimal256::zero());
+        (excess_liabilities, Decimal256::zero())
+    } else {
+        let missing_liabilities = scanned_liabilities - floating_liabilities;
+        if missing_liabilities > max_drift {
+            return Err(ContractError::LiabilitiesDriftTooLarge(missing_liabilities));
+        }
+
+        state.total_liabilities += missing_liabilities;
+        state.total_reserves += missing_liabilities;
+        (Decimal256::zero(), missing_liabilities)
+    };
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "reconcile_liabilities"),
+        attr("excess_liabilities", excess_liabilities.to_string()),
+        attr("missing_liabilities", missing_liabilities.to_string()),
+    ]))
+}
+
+pub fn query_liabilities_drift(
+    deps: Deps,
+    env: Env,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    block_height: Option<u64>,
+    scanned: Option<LiabilitiesScan>,
+) -> Result<LiabilitiesDriftResponse, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(
+        deps,
+        &config,
+        &mut state,
+        block_height.unwrap_or(env.block.height),
+        None,
+    )?;
+
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+    let borrower_infos = read_borrower_infos(deps.storage, start_after, limit)?;
+    let last_borrower = match borrower_infos.last() {
+        Some((borrower, _)) => Some(deps.api.addr_humanize(borrower)?.to_string()),
+        None => None,
+    };
+
+    let mut scanned = scanned.unwrap_or_default();
+    scan_liabilities(deps.storage, &state, borrower_infos, &mut scanned)?;
+
+    let floating_liabilities = floating_liabilities(deps.storage, &state)?;
+    let scanned_liabilities = scanned_liabilities(&scanned);
+    Ok(LiabilitiesDriftResponse {
+        floating_liabilities,
+        excess_liabilities: floating_liabilities
+            .checked_sub(scanned_liabilities)
+            .unwrap_or_else(|_| Decimal256::zero()),
+        missing_liabilities: scanned_liabilities
+            .checked_sub(floating_liabilities)
+            .unwrap_or_else(|_| Decimal256::zero()),
+        scanned,
+        last_borrower,
+    })
+}
+
+/// Add the loans of the borrowers with the interest accrued up to the state height
+fn scan_liabilities(
+    storage: &dyn Storage,
+    state: &State,
+    borrower_infos: Vec<(CanonicalAddr, BorrowerInfo)>,
+    scanned: &mut LiabilitiesScan,
+) -> StdResult<()> {
+    for (_, mut borrower_info) in borrower_infos {
+        compute_tiered_borrower_interest(storage, state, &mut borrower_info)?;
+        scanned.loans += borrower_info.loan_amount;
+    }
+
+    Ok(())
+}
+
+/// The fixed rate loans are tracked by their own aggregates
+fn floating_liabilities(storage: &dyn Storage, state: &State) -> StdResult<Decimal256> {
+    let fixed_liabilities =
+        read_fixed_loan_state(storage)?.liabilities_at(state.last_interest_updated);
+    Ok(state
+        .total_liabilities
+        .checked_sub(fixed_liabilities)
+        .unwrap_or_else(|_| Decimal256::zero()))
+}
+
+fn scanned_liabilities(scanned: &LiabilitiesScan) -> Decimal256 {
+    Decimal256::from_ratio(scanned.loans, 1u128)
+}
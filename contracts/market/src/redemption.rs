@@ -0,0 +1,204 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, CosmosMsg, Decimal256, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, Uint128, Uint256, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::market::{RedemptionResponse, RedemptionsResponse};
+use std::convert::TryInto;
+
+use crate::borrow::compute_interest;
+use crate::deposit::compute_exchange_rate;
+use crate::error::ContractError;
+use crate::stable::{query_stable_balance, stable_transfer_msg};
+use crate::state::{
+    read_config, read_flash_loan, read_redemption, read_redemption_queue, read_redemptions,
+    read_redemptions_by_redeemer, read_state, remove_redemption, store_redemption,
+    store_redemption_queue, store_state, Config, Redemption, RedemptionQueue, State,
+};
+
+/// Bounds the work added to deposits and repayments
+const MAX_REDEMPTIONS_PER_FILL: usize = 10;
+
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Escrow aTerra in the redemption queue and fill the queue
+/// with the stable available right now
+/// Executor: aTerra token contract
+pub fn queue_redemption(
+    mut deps: DepsMut,
+    env: Env,
+    redeemer: Addr,
+    aterra_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    let mut state: State = read_state(deps.storage)?;
+    let mut messages =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+
+    let mut queue: RedemptionQueue = read_redemption_queue(deps.storage)?;
+    let redemption_id = queue.next_redemption_id;
+    queue.next_redemption_id += 1;
+    queue.total_aterra += Uint256::from(aterra_amount);
+    store_redemption_queue(deps.storage, &queue)?;
+    store_redemption(
+        deps.storage,
+        redemption_id,
+        &Redemption {
+            redeemer: deps.api.addr_canonicalize(redeemer.as_str())?,
+            aterra_amount: aterra_amount.into(),
+        },
+    )?;
+
+    let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, None)?;
+    messages.extend(fill_redemptions(
+        deps.branch(),
+        &env,
+        &config,
+        &mut state,
+        exchange_rate,
+        Uint256::zero(),
+    )?);
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "queue_redemption"),
+        attr("redeemer", redeemer),
+        attr("redemption_id", redemption_id.to_string()),
+        attr("aterra_amount", aterra_amount),
+    ]))
+}
+
+/// Executor: redeemer
+pub fn cancel_redemption(
+    deps: DepsMut,
+    info: MessageInfo,
+    redemption_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let redemption: Redemption = read_redemption(deps.storage, redemption_id)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != redemption.redeemer {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut queue: RedemptionQueue = read_redemption_queue(deps.storage)?;
+    queue.total_aterra -= redemption.aterra_amount;
+    store_redemption_queue(deps.storage, &queue)?;
+    remove_redemption(deps.storage, redemption_id, &redemption);
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: redemption.aterra_amount.try_into()?,
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "cancel_redemption"),
+            attr("redeemer", info.sender),
+            attr("redemption_id", redemption_id.to_string()),
+            attr("aterra_amount", redemption.aterra_amount),
+        ]))
+}
+
+/// Redeem the queued aTerra, oldest first, with the stable the market can spare.
+/// `outflow` is the stable already leaving the market in the same response.
+/// The caller stores the state, `prev_aterra_supply` is updated with the burnt aTerra
+pub(crate) fn fill_redemptions(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    state: &mut State,
+    exchange_rate: Decimal256,
+    outflow: Uint256,
+) -> StdResult<Vec<CosmosMsg>> {
+    // The queue is filled after the flash loan, its balance check
+    // only expects the repayments
+    let mut queue: RedemptionQueue = read_redemption_queue(deps.storage)?;
+    if queue.total_aterra.is_zero() || read_flash_loan(deps.storage)?.is_some() {
+        return Ok(vec![]);
+    }
+
+    // Reserves are not redeemed
+    let balance = query_stable_balance(deps.as_ref(), config, env.contract.address.clone())?;
+    let balance = Decimal256::from_ratio(balance.checked_sub(outflow).unwrap_or_default(), 1u128);
+    let mut available = balance
+        .checked_sub(state.total_reserves)
+        .unwrap_or_else(|_| Decimal256::zero());
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut burn_amount = Uint256::zero();
+    for (redemption_id, mut redemption) in read_redemptions(deps.storage, MAX_REDEMPTIONS_PER_FILL)?
+    {
+        let fill_amount = std::cmp::min(
+            redemption.aterra_amount,
+            available / exchange_rate * Uint256::one(),
+        );
+        let redeem_amount = fill_amount * exchange_rate;
+        if redeem_amount.is_zero() {
+            break;
+        }
+
+        available -= Decimal256::from_ratio(redeem_amount, 1u128);
+        burn_amount += fill_amount;
+        redemption.aterra_amount -= fill_amount;
+        if redemption.aterra_amount.is_zero() {
+            remove_redemption(deps.storage, redemption_id, &redemption);
+        } else {
+            store_redemption(deps.storage, redemption_id, &redemption)?;
+        }
+
+        messages.push(stable_transfer_msg(
+            deps.as_ref(),
+            config,
+            deps.api.addr_humanize(&redemption.redeemer)?,
+            redeem_amount,
+        )?);
+    }
+
+    if burn_amount.is_zero() {
+        return Ok(vec![]);
+    }
+
+    queue.total_aterra -= burn_amount;
+    store_redemption_queue(deps.storage, &queue)?;
+    state.prev_aterra_supply -= burn_amount;
+
+    messages.insert(
+        0,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+                amount: burn_amount.try_into()?,
+            })?,
+        }),
+    );
+
+    Ok(messages)
+}
+
+pub fn query_redemptions(
+    deps: Deps,
+    redeemer: Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<RedemptionsResponse> {
+    let redemptions = read_redemptions_by_redeemer(
+        deps.storage,
+        &deps.api.addr_canonicalize(redeemer.as_str())?,
+        start_after,
+        Some(limit.unwrap_or(DEFAULT_LIMIT)),
+    )?
+    .into_iter()
+    .map(|(redemption_id, redemption)| RedemptionResponse {
+        redemption_id,
+        redeemer: redeemer.to_string(),
+        aterra_amount: redemption.aterra_amount,
+    })
+    .collect();
+
+    Ok(RedemptionsResponse { redemptions })
+}
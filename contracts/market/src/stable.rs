@@ -0,0 +1,57 @@
+use cosmwasm_std::{Addr, CosmosMsg, Deps, MessageInfo, StdResult, Uint256};
+use moneymarket::astroport_router::AssetInfo;
+use moneymarket::custody::Asset;
+use moneymarket::querier::{query_all_token_types_balance, query_balance};
+use std::convert::TryInto;
+
+use crate::error::ContractError;
+use crate::state::{read_stable_asset, Config};
+
+/// Amount of native stable sent with the message.
+/// CW20 stables are only received through the `Receive` hooks
+pub fn stable_funds(deps: Deps, config: &Config, info: &MessageInfo) -> StdResult<Uint256> {
+    Ok(read_stable_asset(deps.storage, config)?.funds_amount(&info.funds))
+}
+
+pub fn assert_stable_token(
+    deps: Deps,
+    config: &Config,
+    token_contract: &Addr,
+) -> Result<(), ContractError> {
+    match read_stable_asset(deps.storage, config)? {
+        AssetInfo::Token { contract_addr } if contract_addr == *token_contract => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+pub fn query_stable_balance(deps: Deps, config: &Config, account_addr: Addr) -> StdResult<Uint256> {
+    query_all_token_types_balance(deps, account_addr, read_stable_asset(deps.storage, config)?)
+}
+
+/// Incentives held by the borrow reserves bucket.
+/// The overseer funds the bucket in the native stable only,
+/// markets on a CW20 stable don't give out borrower incentives
+pub fn query_bucket_balance(deps: Deps, config: &Config) -> StdResult<Uint256> {
+    match read_stable_asset(deps.storage, config)? {
+        AssetInfo::NativeToken { denom } => query_balance(
+            deps,
+            deps.api
+                .addr_humanize(&config.borrow_reserves_bucket_contract)?,
+            denom,
+        ),
+        AssetInfo::Token { .. } => Ok(Uint256::zero()),
+    }
+}
+
+pub fn stable_transfer_msg(
+    deps: Deps,
+    config: &Config,
+    recipient: Addr,
+    amount: Uint256,
+) -> StdResult<CosmosMsg> {
+    Asset {
+        amount: amount.try_into()?,
+        asset_info: read_stable_asset(deps.storage, config)?,
+    }
+    .to_msg(recipient)
+}
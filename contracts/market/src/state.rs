@@ -1,15 +1,38 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, Decimal256, Deps, Order, StdResult, Storage, Uint256};
-use cosmwasm_storage::{bucket, bucket_read, ReadonlyBucket, ReadonlySingleton, Singleton};
+use cosmwasm_std::{CanonicalAddr, Decimal256, Order, StdError, StdResult, Storage, Uint256};
+#[allow(deprecated)]
+use cosmwasm_storage::{bucket, bucket_read, Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
+use std::convert::TryInto;
 
-use moneymarket::market::BorrowerInfoResponse;
+use moneymarket::astroport_router::AssetInfo;
+use moneymarket::market::{BorrowerRewardsMode, OriginationFee, ReserveMode};
 
 pub const KEY_CONFIG: &[u8] = b"config";
 pub const KEY_STATE: &[u8] = b"state";
+pub const KEY_FIXED_RATE_CONFIG: &[u8] = b"fixed_rate_config";
+pub const KEY_FIXED_LOAN_STATE: &[u8] = b"fixed_loan_state";
+pub const KEY_FLASH_LOAN_FEE_RATE: &[u8] = b"flash_loan_fee_rate";
+pub const KEY_FLASH_LOAN: &[u8] = b"flash_loan";
+pub const KEY_BORROWER_REWARDS_MODE: &[u8] = b"borrower_rewards_mode";
+pub const KEY_STABLE_ASSET: &[u8] = b"stable_asset";
+pub const KEY_REDEMPTION_QUEUE: &[u8] = b"redemption_queue";
+pub const KEY_RESERVE_MODE: &[u8] = b"reserve_mode";
+pub const KEY_RESERVES_WITHDRAWN: &[u8] = b"reserves_withdrawn";
+pub const KEY_ORIGINATION_FEE: &[u8] = b"origination_fee";
+pub const KEY_FEES_COLLECTED: &[u8] = b"fees_collected";
+pub const KEY_DEBT_INDEX_BACKFILL: &[u8] = b"debt_index_backfill";
+pub const KEY_RECONCILE_SCAN: &[u8] = b"reconcile_scan";
 
 const PREFIX_LIABILITY: &[u8] = b"liability";
+const PREFIX_LIABILITY_BY_DEBT: &[u8] = b"liability_by_debt";
+const PREFIX_FIXED_LOAN: &[u8] = b"fixed_loan";
+const PREFIX_FIXED_LOAN_BY_BORROWER: &[u8] = b"fixed_loan_by_borrower";
+const PREFIX_ALLOWANCE: &[u8] = b"allowance";
+const PREFIX_REDEMPTION: &[u8] = b"redemption";
+const PREFIX_REDEMPTION_BY_REDEEMER: &[u8] = b"redemption_by_redeemer";
+const PREFIX_INTEREST_TIER: &[u8] = b"interest_tier";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -40,6 +63,12 @@ pub struct State {
     pub prev_exchange_rate: Decimal256,
 
     pub prev_borrower_incentives: Uint256,
+    /// Borrow incentives left in the bucket for the borrowers to claim
+    #[serde(default)]
+    pub reserved_borrower_rewards: Uint256,
+    /// Reward index of the incentives reserved in `Claimable` mode
+    #[serde(default)]
+    pub global_claimable_reward_index: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -48,6 +77,180 @@ pub struct BorrowerInfo {
     pub reward_index: Decimal256,
     pub loan_amount: Uint256,
     pub pending_rewards: Decimal256,
+    /// Borrowers without a tier accrue interest at the global interest index
+    #[serde(default)]
+    pub tier: Option<BorrowerTier>,
+    #[serde(default)]
+    pub claimable_reward_index: Decimal256,
+    /// Part of the pending rewards reserved in the bucket, the rest was
+    /// given out as a lower interest in `Subsidize` mode
+    #[serde(default)]
+    pub claimable_rewards: Decimal256,
+}
+
+/// Progress of a paginated `ReconcileLiabilities`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReconcileScan {
+    pub last_borrower: CanonicalAddr,
+    /// Floating rate loans of the borrowers scanned so far
+    pub loans: Decimal256,
+    /// Global interest index the scanned loans are accrued to
+    pub global_interest_index: Decimal256,
+}
+
+impl ReconcileScan {
+    /// Accrue the scanned loans to the current global interest index
+    pub fn accrue(&mut self, global_interest_index: Decimal256) {
+        if global_interest_index != self.global_interest_index {
+            self.loans = self.loans * global_interest_index / self.global_interest_index;
+            self.global_interest_index = global_interest_index;
+        }
+    }
+
+    /// Loan of a borrower accrued to the scan interest index
+    fn loan_amount(&self, liability: &BorrowerInfo) -> Decimal256 {
+        Decimal256::from_ratio(liability.loan_amount, 1u128) * self.global_interest_index
+            / liability.interest_index
+    }
+}
+
+/// Progress of the debt index back-fill, instantiated markets index every loan from the start
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct DebtIndexBackfill {
+    pub done: bool,
+    pub last_borrower: Option<CanonicalAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerTier {
+    pub tier_id: u64,
+    /// Tier interest index at the last update of the liability
+    pub interest_index: Decimal256,
+}
+
+impl BorrowerTier {
+    /// Loan amount normalized by the tier interest index
+    pub fn principal(&self, loan_amount: Uint256) -> Decimal256 {
+        Decimal256::from_ratio(loan_amount, 1u128) / self.interest_index
+    }
+}
+
+/// Borrowers of a tier pay `1 - discount` of the interest
+/// accrued by the global interest index
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InterestTier {
+    pub discount: Decimal256,
+    pub interest_index: Decimal256,
+    /// Global interest index when `interest_index` was last updated
+    pub global_interest_index: Decimal256,
+    /// Loans of the tier borrowers, normalized by their tier interest index
+    #[serde(default)]
+    pub principal: Decimal256,
+}
+
+impl InterestTier {
+    pub fn interest_index_at(&self, global_interest_index: Decimal256) -> Decimal256 {
+        let global_interest_factor =
+            global_interest_index / self.global_interest_index - Decimal256::one();
+        self.interest_index
+            * (Decimal256::one() + global_interest_factor * (Decimal256::one() - self.discount))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct FixedRateConfig {
+    pub premium_rate: Decimal256,
+    pub min_term: u64,
+    pub max_term: u64,
+}
+
+/// Aggregates of all the open fixed rate loans.
+/// Loans accrue simple interest, so the fixed liabilities at height `h`
+/// are `total_principal + total_rate_weight * h - total_start_weight`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct FixedLoanState {
+    pub next_loan_id: u64,
+    pub total_principal: Decimal256,
+    /// sum of principal * rate
+    pub total_rate_weight: Decimal256,
+    /// sum of principal * rate * start_height
+    pub total_start_weight: Decimal256,
+}
+
+impl FixedLoanState {
+    pub fn liabilities_at(&self, block_height: u64) -> Decimal256 {
+        self.total_principal + self.total_rate_weight * Decimal256::from_ratio(block_height, 1u128)
+            - self.total_start_weight
+    }
+
+    pub fn add_loan(&mut self, loan: &FixedLoan) {
+        let rate_weight = loan.rate_weight();
+        self.total_principal += Decimal256::from_ratio(loan.principal, 1u128);
+        self.total_rate_weight += rate_weight;
+        self.total_start_weight += rate_weight * Decimal256::from_ratio(loan.start_height, 1u128);
+    }
+
+    pub fn remove_loan(&mut self, loan: &FixedLoan) {
+        let rate_weight = loan.rate_weight();
+        self.total_principal -= Decimal256::from_ratio(loan.principal, 1u128);
+        self.total_rate_weight -= rate_weight;
+        self.total_start_weight -= rate_weight * Decimal256::from_ratio(loan.start_height, 1u128);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FixedLoan {
+    pub borrower: CanonicalAddr,
+    pub principal: Uint256,
+    /// per block rate locked at origination
+    pub rate: Decimal256,
+    pub start_height: u64,
+    pub maturity_height: u64,
+}
+
+impl FixedLoan {
+    fn rate_weight(&self) -> Decimal256 {
+        Decimal256::from_ratio(self.principal, 1u128) * self.rate
+    }
+
+    /// Principal plus the simple interest accrued since start_height
+    pub fn amount_due(&self, block_height: u64) -> Decimal256 {
+        Decimal256::from_ratio(self.principal, 1u128)
+            + self.rate_weight()
+                * Decimal256::from_ratio(block_height.saturating_sub(self.start_height), 1u128)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct RedemptionQueue {
+    pub next_redemption_id: u64,
+    /// aTerra escrowed by all the queued redemptions
+    pub total_aterra: Uint256,
+}
+
+/// aTerra escrowed until enough stable comes back to the market to redeem it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Redemption {
+    pub redeemer: CanonicalAddr,
+    pub aterra_amount: Uint256,
+}
+
+/// Flash loan in flight, removed once the loan has been paid back
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FlashLoan {
+    pub receiver: CanonicalAddr,
+    pub amount: Uint256,
+    pub fee: Uint256,
+    /// market balance before the loan was sent out
+    pub prev_balance: Uint256,
+    /// stable repaid to the market while the loan is in flight
+    pub repaid: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct FeesCollected {
+    pub origination: Uint256,
+    pub flash_loan: Uint256,
 }
 
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
@@ -66,14 +269,103 @@ pub fn read_state(storage: &dyn Storage) -> StdResult<State> {
     ReadonlySingleton::new(storage, KEY_STATE).load()
 }
 
+#[allow(deprecated)]
 pub fn store_borrower_info(
     storage: &mut dyn Storage,
     borrower: &CanonicalAddr,
     liability: &BorrowerInfo,
 ) -> StdResult<()> {
+    // Move the borrower in the debt index, borrowers without a loan are not indexed
+    let prev_liability = read_borrower_info(storage, borrower);
+    let mut debt_indexer: Bucket<bool> = bucket(storage, PREFIX_LIABILITY_BY_DEBT);
+    debt_indexer.remove(&debt_index_key(prev_liability.loan_amount, borrower));
+    if !liability.loan_amount.is_zero() {
+        debt_indexer.save(&debt_index_key(liability.loan_amount, borrower), &true)?;
+    }
+
+    // Move the loan between the tier principals
+    if let Some(prev_tier) = &prev_liability.tier {
+        let mut tier = read_interest_tier(storage, prev_tier.tier_id)?;
+        tier.principal = tier
+            .principal
+            .checked_sub(prev_tier.principal(prev_liability.loan_amount))
+            .unwrap_or_else(|_| Decimal256::zero());
+        store_interest_tier(storage, prev_tier.tier_id, &tier)?;
+    }
+    if let Some(borrower_tier) = &liability.tier {
+        let mut tier = read_interest_tier(storage, borrower_tier.tier_id)?;
+        tier.principal += borrower_tier.principal(liability.loan_amount);
+        store_interest_tier(storage, borrower_tier.tier_id, &tier)?;
+    }
+
+    // Borrowers already scanned by a reconciliation in progress update its loans
+    if let Some(mut scan) = read_reconcile_scan(storage)? {
+        if borrower.as_slice() <= scan.last_borrower.as_slice() {
+            scan.loans = (scan.loans + scan.loan_amount(liability))
+                .checked_sub(scan.loan_amount(&prev_liability))
+                .unwrap_or_else(|_| Decimal256::zero());
+            store_reconcile_scan(storage, &scan)?;
+        }
+    }
+
     bucket(storage, PREFIX_LIABILITY).save(borrower.as_slice(), liability)
 }
 
+/// Index a page of the liabilities stored before the debt index existed,
+/// resuming after the last borrower indexed
+#[allow(deprecated)]
+pub fn index_borrowers_by_debt(
+    storage: &mut dyn Storage,
+    limit: Option<u32>,
+) -> StdResult<DebtIndexBackfill> {
+    let mut backfill = read_debt_index_backfill(storage)?;
+    if backfill.done {
+        return Ok(backfill);
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let liabilities = read_borrower_infos(storage, backfill.last_borrower.clone(), Some(limit))?;
+
+    let mut debt_indexer: Bucket<bool> = bucket(storage, PREFIX_LIABILITY_BY_DEBT);
+    for (borrower, liability) in liabilities.iter() {
+        if !liability.loan_amount.is_zero() {
+            debt_indexer.save(&debt_index_key(liability.loan_amount, borrower), &true)?;
+        }
+    }
+
+    backfill.done = liabilities.len() < limit as usize;
+    if let Some((borrower, _)) = liabilities.last() {
+        backfill.last_borrower = Some(borrower.clone());
+    }
+    store_debt_index_backfill(storage, &backfill)?;
+
+    Ok(backfill)
+}
+
+#[allow(deprecated)]
+pub fn store_debt_index_backfill(
+    storage: &mut dyn Storage,
+    data: &DebtIndexBackfill,
+) -> StdResult<()> {
+    Singleton::new(storage, KEY_DEBT_INDEX_BACKFILL).save(data)
+}
+
+/// Markets migrated from before the debt index start with an empty index
+#[allow(deprecated)]
+pub fn read_debt_index_backfill(storage: &dyn Storage) -> StdResult<DebtIndexBackfill> {
+    Ok(ReadonlySingleton::new(storage, KEY_DEBT_INDEX_BACKFILL)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+// the loan amount is big endian so that the keys are ordered by debt
+fn debt_index_key(loan_amount: Uint256, borrower: &CanonicalAddr) -> Vec<u8> {
+    let mut key = loan_amount.to_be_bytes().to_vec();
+    key.extend_from_slice(borrower.as_slice());
+    key
+}
+
+#[allow(deprecated)]
 pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> BorrowerInfo {
     match bucket_read(storage, PREFIX_LIABILITY).load(borrower.as_slice()) {
         Ok(v) => v,
@@ -82,20 +374,476 @@ pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> Bo
             reward_index: Decimal256::zero(),
             loan_amount: Uint256::zero(),
             pending_rewards: Decimal256::zero(),
+            tier: None,
+            claimable_reward_index: Decimal256::zero(),
+            claimable_rewards: Decimal256::zero(),
         },
     }
 }
 
+#[allow(deprecated)]
+pub fn store_fixed_rate_config(storage: &mut dyn Storage, data: &FixedRateConfig) -> StdResult<()> {
+    Singleton::new(storage, KEY_FIXED_RATE_CONFIG).save(data)
+}
+
+#[allow(deprecated)]
+pub fn read_fixed_rate_config(storage: &dyn Storage) -> StdResult<FixedRateConfig> {
+    Ok(ReadonlySingleton::new(storage, KEY_FIXED_RATE_CONFIG)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[allow(deprecated)]
+pub fn store_fixed_loan_state(storage: &mut dyn Storage, data: &FixedLoanState) -> StdResult<()> {
+    Singleton::new(storage, KEY_FIXED_LOAN_STATE).save(data)
+}
+
+#[allow(deprecated)]
+pub fn read_fixed_loan_state(storage: &dyn Storage) -> StdResult<FixedLoanState> {
+    Ok(ReadonlySingleton::new(storage, KEY_FIXED_LOAN_STATE)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[allow(deprecated)]
+pub fn store_fixed_loan(
+    storage: &mut dyn Storage,
+    loan_id: u64,
+    loan: &FixedLoan,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_FIXED_LOAN).save(&loan_id.to_be_bytes(), loan)?;
+
+    let mut loan_indexer_by_borrower: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[PREFIX_FIXED_LOAN_BY_BORROWER, loan.borrower.as_slice()],
+    );
+    loan_indexer_by_borrower.save(&loan_id.to_be_bytes(), &true)
+}
+
+#[allow(deprecated)]
+pub fn remove_fixed_loan(storage: &mut dyn Storage, loan_id: u64, loan: &FixedLoan) {
+    bucket::<FixedLoan>(storage, PREFIX_FIXED_LOAN).remove(&loan_id.to_be_bytes());
+
+    let mut loan_indexer_by_borrower: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[PREFIX_FIXED_LOAN_BY_BORROWER, loan.borrower.as_slice()],
+    );
+    loan_indexer_by_borrower.remove(&loan_id.to_be_bytes());
+}
+
+#[allow(deprecated)]
+pub fn read_fixed_loan(storage: &dyn Storage, loan_id: u64) -> StdResult<FixedLoan> {
+    bucket_read(storage, PREFIX_FIXED_LOAN)
+        .load(&loan_id.to_be_bytes())
+        .map_err(|_| StdError::generic_err("No fixed loan with the specified id exists"))
+}
+
+#[allow(deprecated)]
+pub fn read_fixed_loans_by_borrower(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, FixedLoan)>> {
+    let loan_borrower_index: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_FIXED_LOAN_BY_BORROWER, borrower.as_slice()],
+    );
+
+    let limit = limit.map(|l| l.min(MAX_LIMIT) as usize);
+    let start = start_after.map(|id| {
+        let mut v = id.to_be_bytes().to_vec();
+        v.push(1);
+        v
+    });
+
+    loan_borrower_index
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|elem| {
+            let (k, _) = elem?;
+            let loan_id = bytes_to_u64(&k)?;
+            Ok((loan_id, read_fixed_loan(storage, loan_id)?))
+        })
+        .collect()
+}
+
+/// Total amount owed on the fixed rate loans of a borrower
+pub fn read_fixed_loan_amount(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    block_height: u64,
+) -> StdResult<Uint256> {
+    let amount_due = read_fixed_loans_by_borrower(storage, borrower, None, None)?
+        .iter()
+        .fold(Decimal256::zero(), |acc, (_, loan)| {
+            acc + loan.amount_due(block_height)
+        });
+    Ok(amount_due * Uint256::one())
+}
+
+#[allow(deprecated)]
+pub fn store_interest_tier(
+    storage: &mut dyn Storage,
+    tier_id: u64,
+    tier: &InterestTier,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_INTEREST_TIER).save(&tier_id.to_be_bytes(), tier)
+}
+
+#[allow(deprecated)]
+pub fn read_interest_tier(storage: &dyn Storage, tier_id: u64) -> StdResult<InterestTier> {
+    bucket_read(storage, PREFIX_INTEREST_TIER)
+        .load(&tier_id.to_be_bytes())
+        .map_err(|_| StdError::generic_err("No interest tier with the specified id exists"))
+}
+
+/// Borrow rate paid in a tier, given the borrow rate of the default tier
+pub fn read_tier_borrow_rate(
+    storage: &dyn Storage,
+    tier: &Option<BorrowerTier>,
+    borrow_rate: Decimal256,
+) -> StdResult<Decimal256> {
+    match tier {
+        Some(tier) => {
+            let discount = read_interest_tier(storage, tier.tier_id)?.discount;
+            Ok(borrow_rate * (Decimal256::one() - discount))
+        }
+        None => Ok(borrow_rate),
+    }
+}
+
+#[allow(deprecated)]
+pub fn read_all_interest_tiers(storage: &dyn Storage) -> StdResult<Vec<InterestTier>> {
+    bucket_read(storage, PREFIX_INTEREST_TIER)
+        .range(None, None, Order::Ascending)
+        .map(|elem| Ok(elem?.1))
+        .collect()
+}
+
+#[allow(deprecated)]
+pub fn read_interest_tiers(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, InterestTier)>> {
+    let tier_bucket: ReadonlyBucket<InterestTier> = bucket_read(storage, PREFIX_INTEREST_TIER);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|id| {
+        let mut v = id.to_be_bytes().to_vec();
+        v.push(1);
+        v
+    });
+
+    tier_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((bytes_to_u64(&k)?, v))
+        })
+        .collect()
+}
+
+fn bytes_to_u64(data: &[u8]) -> StdResult<u64> {
+    match data[0..8].try_into() {
+        Ok(bytes) => Ok(u64::from_be_bytes(bytes)),
+        Err(_) => Err(StdError::generic_err(
+            "Corrupted data found. 8 byte expected.",
+        )),
+    }
+}
+
+#[allow(deprecated)]
+pub fn store_redemption_queue(storage: &mut dyn Storage, data: &RedemptionQueue) -> StdResult<()> {
+    Singleton::new(storage, KEY_REDEMPTION_QUEUE).save(data)
+}
+
+#[allow(deprecated)]
+pub fn read_redemption_queue(storage: &dyn Storage) -> StdResult<RedemptionQueue> {
+    Ok(ReadonlySingleton::new(storage, KEY_REDEMPTION_QUEUE)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[allow(deprecated)]
+pub fn store_redemption(
+    storage: &mut dyn Storage,
+    redemption_id: u64,
+    redemption: &Redemption,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_REDEMPTION).save(&redemption_id.to_be_bytes(), redemption)?;
+
+    let mut redemption_indexer_by_redeemer: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_REDEMPTION_BY_REDEEMER,
+            redemption.redeemer.as_slice(),
+        ],
+    );
+    redemption_indexer_by_redeemer.save(&redemption_id.to_be_bytes(), &true)
+}
+
+#[allow(deprecated)]
+pub fn remove_redemption(storage: &mut dyn Storage, redemption_id: u64, redemption: &Redemption) {
+    bucket::<Redemption>(storage, PREFIX_REDEMPTION).remove(&redemption_id.to_be_bytes());
+
+    let mut redemption_indexer_by_redeemer: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_REDEMPTION_BY_REDEEMER,
+            redemption.redeemer.as_slice(),
+        ],
+    );
+    redemption_indexer_by_redeemer.remove(&redemption_id.to_be_bytes());
+}
+
+#[allow(deprecated)]
+pub fn read_redemption(storage: &dyn Storage, redemption_id: u64) -> StdResult<Redemption> {
+    bucket_read(storage, PREFIX_REDEMPTION)
+        .load(&redemption_id.to_be_bytes())
+        .map_err(|_| StdError::generic_err("No redemption with the specified id exists"))
+}
+
+/// Queued redemptions, oldest first
+#[allow(deprecated)]
+pub fn read_redemptions(storage: &dyn Storage, limit: usize) -> StdResult<Vec<(u64, Redemption)>> {
+    let redemption_bucket: ReadonlyBucket<Redemption> =
+        ReadonlyBucket::new(storage, PREFIX_REDEMPTION);
+
+    redemption_bucket
+        .range(None, None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((bytes_to_u64(&k)?, v))
+        })
+        .collect()
+}
+
+#[allow(deprecated)]
+pub fn read_redemptions_by_redeemer(
+    storage: &dyn Storage,
+    redeemer: &CanonicalAddr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, Redemption)>> {
+    let redemption_redeemer_index: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_REDEMPTION_BY_REDEEMER, redeemer.as_slice()],
+    );
+
+    let limit = limit.map(|l| l.min(MAX_LIMIT) as usize);
+    let start = start_after.map(|id| {
+        let mut v = id.to_be_bytes().to_vec();
+        v.push(1);
+        v
+    });
+
+    redemption_redeemer_index
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|elem| {
+            let (k, _) = elem?;
+            let redemption_id = bytes_to_u64(&k)?;
+            Ok((redemption_id, read_redemption(storage, redemption_id)?))
+        })
+        .collect()
+}
+
+#[allow(deprecated)]
+pub fn store_flash_loan_fee_rate(storage: &mut dyn Storage, data: &Decimal256) -> StdResult<()> {
+    Singleton::new(storage, KEY_FLASH_LOAN_FEE_RATE).save(data)
+}
+
+#[allow(deprecated)]
+pub fn read_flash_loan_fee_rate(storage: &dyn Storage) -> StdResult<Decimal256> {
+    Ok(ReadonlySingleton::new(storage, KEY_FLASH_LOAN_FEE_RATE)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[allow(deprecated)]
+pub fn store_borrower_rewards_mode(
+    storage: &mut dyn Storage,
+    data: &BorrowerRewardsMode,
+) -> StdResult<()> {
+    Singleton::new(storage, KEY_BORROWER_REWARDS_MODE).save(data)
+}
+
+#[allow(deprecated)]
+pub fn read_borrower_rewards_mode(storage: &dyn Storage) -> StdResult<BorrowerRewardsMode> {
+    Ok(ReadonlySingleton::new(storage, KEY_BORROWER_REWARDS_MODE)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[allow(deprecated)]
+pub fn store_reserve_mode(storage: &mut dyn Storage, data: &ReserveMode) -> StdResult<()> {
+    Singleton::new(storage, KEY_RESERVE_MODE).save(data)
+}
+
+#[allow(deprecated)]
+pub fn read_reserve_mode(storage: &dyn Storage) -> StdResult<ReserveMode> {
+    Ok(ReadonlySingleton::new(storage, KEY_RESERVE_MODE)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[allow(deprecated)]
+pub fn store_reserves_withdrawn(storage: &mut dyn Storage, data: &Uint256) -> StdResult<()> {
+    Singleton::new(storage, KEY_RESERVES_WITHDRAWN).save(data)
+}
+
+/// Total reserves sent out of the market, the accrued reserves
+/// are the reserves held plus the withdrawn ones
+#[allow(deprecated)]
+pub fn read_reserves_withdrawn(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(ReadonlySingleton::new(storage, KEY_RESERVES_WITHDRAWN)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[allow(deprecated)]
+pub fn store_origination_fee(storage: &mut dyn Storage, data: &OriginationFee) -> StdResult<()> {
+    Singleton::new(storage, KEY_ORIGINATION_FEE).save(data)
+}
+
+#[allow(deprecated)]
+pub fn read_origination_fee(storage: &dyn Storage) -> StdResult<OriginationFee> {
+    Ok(ReadonlySingleton::new(storage, KEY_ORIGINATION_FEE)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[allow(deprecated)]
+pub fn store_fees_collected(storage: &mut dyn Storage, data: &FeesCollected) -> StdResult<()> {
+    Singleton::new(storage, KEY_FEES_COLLECTED).save(data)
+}
+
+/// Fees collected since the ledger was introduced
+#[allow(deprecated)]
+pub fn read_fees_collected(storage: &dyn Storage) -> StdResult<FeesCollected> {
+    Ok(ReadonlySingleton::new(storage, KEY_FEES_COLLECTED)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[allow(deprecated)]
+pub fn store_stable_asset(storage: &mut dyn Storage, data: &AssetInfo) -> StdResult<()> {
+    Singleton::new(storage, KEY_STABLE_ASSET).save(data)
+}
+
+/// Markets without a stored stable asset lend the native stable denom
+#[allow(deprecated)]
+pub fn read_stable_asset(storage: &dyn Storage, config: &Config) -> StdResult<AssetInfo> {
+    Ok(ReadonlySingleton::new(storage, KEY_STABLE_ASSET)
+        .may_load()?
+        .unwrap_or_else(|| AssetInfo::NativeToken {
+            denom: config.stable_denom.clone(),
+        }))
+}
+
+#[allow(deprecated)]
+pub fn store_reconcile_scan(storage: &mut dyn Storage, data: &ReconcileScan) -> StdResult<()> {
+    Singleton::new(storage, KEY_RECONCILE_SCAN).save(data)
+}
+
+#[allow(deprecated)]
+pub fn remove_reconcile_scan(storage: &mut dyn Storage) {
+    Singleton::<ReconcileScan>::new(storage, KEY_RECONCILE_SCAN).remove()
+}
+
+#[allow(deprecated)]
+pub fn read_reconcile_scan(storage: &dyn Storage) -> StdResult<Option<ReconcileScan>> {
+    ReadonlySingleton::new(storage, KEY_RECONCILE_SCAN).may_load()
+}
+
+#[allow(deprecated)]
+pub fn store_flash_loan(storage: &mut dyn Storage, data: &FlashLoan) -> StdResult<()> {
+    Singleton::new(storage, KEY_FLASH_LOAN).save(data)
+}
+
+#[allow(deprecated)]
+pub fn remove_flash_loan(storage: &mut dyn Storage) {
+    Singleton::<FlashLoan>::new(storage, KEY_FLASH_LOAN).remove()
+}
+
+#[allow(deprecated)]
+pub fn read_flash_loan(storage: &dyn Storage) -> StdResult<Option<FlashLoan>> {
+    ReadonlySingleton::new(storage, KEY_FLASH_LOAN).may_load()
+}
+
+#[allow(deprecated)]
+pub fn store_allowance(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    delegate: &CanonicalAddr,
+    allowance: &Uint256,
+) -> StdResult<()> {
+    let mut allowance_bucket: Bucket<Uint256> =
+        Bucket::multilevel(storage, &[PREFIX_ALLOWANCE, borrower.as_slice()]);
+    allowance_bucket.save(delegate.as_slice(), allowance)
+}
+
+#[allow(deprecated)]
+pub fn remove_allowance(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    delegate: &CanonicalAddr,
+) {
+    let mut allowance_bucket: Bucket<Uint256> =
+        Bucket::multilevel(storage, &[PREFIX_ALLOWANCE, borrower.as_slice()]);
+    allowance_bucket.remove(delegate.as_slice())
+}
+
+#[allow(deprecated)]
+pub fn read_allowance(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    delegate: &CanonicalAddr,
+) -> Uint256 {
+    let allowance_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_ALLOWANCE, borrower.as_slice()]);
+    allowance_bucket
+        .load(delegate.as_slice())
+        .unwrap_or_else(|_| Uint256::zero())
+}
+
+#[allow(deprecated)]
+pub fn read_allowances(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(CanonicalAddr, Uint256)>> {
+    let allowance_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_ALLOWANCE, borrower.as_slice()]);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    allowance_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 1000;
 const DEFAULT_LIMIT: u32 = 10;
+#[allow(deprecated)]
 pub fn read_borrower_infos(
-    deps: Deps,
+    storage: &dyn Storage,
     start_after: Option<CanonicalAddr>,
     limit: Option<u32>,
-) -> StdResult<Vec<BorrowerInfoResponse>> {
-    let liability_bucket: ReadonlyBucket<BorrowerInfo> =
-        bucket_read(deps.storage, PREFIX_LIABILITY);
+) -> StdResult<Vec<(CanonicalAddr, BorrowerInfo)>> {
+    let liability_bucket: ReadonlyBucket<BorrowerInfo> = bucket_read(storage, PREFIX_LIABILITY);
 
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = calc_range_start(start_after);
@@ -105,14 +853,37 @@ pub fn read_borrower_infos(
         .take(limit)
         .map(|elem| {
             let (k, v) = elem?;
-            let borrower = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
-            Ok(BorrowerInfoResponse {
-                borrower,
-                interest_index: v.interest_index,
-                reward_index: v.reward_index,
-                loan_amount: v.loan_amount,
-                pending_rewards: v.pending_rewards,
-            })
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
+/// Borrowers from the largest stored loan to the smallest,
+/// paginated by the last borrower of the previous page
+#[allow(deprecated)]
+pub fn read_borrowers_by_debt(
+    storage: &dyn Storage,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(CanonicalAddr, BorrowerInfo)>> {
+    let debt_indexer: ReadonlyBucket<bool> = bucket_read(storage, PREFIX_LIABILITY_BY_DEBT);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let end = start_after.map(|borrower| {
+        debt_index_key(
+            read_borrower_info(storage, &borrower).loan_amount,
+            &borrower,
+        )
+    });
+
+    debt_indexer
+        .range(None, end.as_deref(), Order::Descending)
+        .take(limit)
+        .map(|elem| {
+            let (k, _) = elem?;
+            let borrower = CanonicalAddr::from(k[32..].to_vec());
+            let liability = read_borrower_info(storage, &borrower);
+            Ok((borrower, liability))
         })
         .collect()
 }
@@ -20,12 +20,17 @@ fn proper_compute_borrower_interest() {
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
         prev_borrower_incentives: Uint256::zero(),
+        reserved_borrower_rewards: Uint256::zero(),
+        global_claimable_reward_index: Decimal256::zero(),
     };
     let mut liability1 = BorrowerInfo {
         interest_index: Decimal256::one(),
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::zero(),
         pending_rewards: Decimal256::zero(),
+        tier: None,
+        claimable_reward_index: Decimal256::zero(),
+        claimable_rewards: Decimal256::zero(),
     };
     compute_borrower_interest(&mock_state, &mut liability1);
     let liability2 = BorrowerInfo {
@@ -33,6 +38,9 @@ fn proper_compute_borrower_interest() {
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::zero(),
         pending_rewards: Decimal256::zero(),
+        tier: None,
+        claimable_reward_index: Decimal256::zero(),
+        claimable_rewards: Decimal256::zero(),
     };
     assert_eq!(liability1, liability2);
 
@@ -47,12 +55,17 @@ fn proper_compute_borrower_interest() {
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
         prev_borrower_incentives: Uint256::zero(),
+        reserved_borrower_rewards: Uint256::zero(),
+        global_claimable_reward_index: Decimal256::zero(),
     };
     let mut liability3 = BorrowerInfo {
         interest_index: Decimal256::from_ratio(4u128, 1u128),
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::from(80u128),
         pending_rewards: Decimal256::zero(),
+        tier: None,
+        claimable_reward_index: Decimal256::zero(),
+        claimable_rewards: Decimal256::zero(),
     };
     compute_borrower_interest(&mock_state2, &mut liability3);
     let liability4 = BorrowerInfo {
@@ -60,6 +73,9 @@ fn proper_compute_borrower_interest() {
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::from(40u128),
         pending_rewards: Decimal256::zero(),
+        tier: None,
+        claimable_reward_index: Decimal256::zero(),
+        claimable_rewards: Decimal256::zero(),
     };
     assert_eq!(liability3, liability4);
 }
@@ -107,6 +123,8 @@ fn proper_compute_interest() {
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
         prev_borrower_incentives: Uint256::zero(),
+        reserved_borrower_rewards: Uint256::zero(),
+        global_claimable_reward_index: Decimal256::zero(),
     };
     store_state(&mut deps.storage, &mock_state).unwrap();
 
@@ -133,6 +151,8 @@ fn proper_compute_interest() {
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         }
     );
 
@@ -162,6 +182,8 @@ fn proper_compute_interest() {
                 Uint256::from(10000u128)
             ),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         }
     );
 
@@ -177,6 +199,8 @@ fn proper_compute_interest() {
         prev_aterra_supply: Uint256::from(2000000u128),
         prev_exchange_rate: Decimal256::one(),
         prev_borrower_incentives: Uint256::zero(),
+        reserved_borrower_rewards: Uint256::zero(),
+        global_claimable_reward_index: Decimal256::zero(),
     };
     store_state(&mut deps.storage, &mock_state).unwrap();
 
@@ -213,6 +237,8 @@ fn proper_compute_interest() {
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::from_ratio(2u64, 1u128),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         }
     );
 }
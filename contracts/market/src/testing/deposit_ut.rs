@@ -43,6 +43,8 @@ fn proper_compute_exchange_rate() {
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
         prev_borrower_incentives: Uint256::zero(),
+        reserved_borrower_rewards: Uint256::zero(),
+        global_claimable_reward_index: Decimal256::zero(),
     };
     let mock_deposit_amount = Some(Uint256::from(1000000u128));
 
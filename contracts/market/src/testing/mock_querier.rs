@@ -14,7 +14,7 @@ use cosmwasm_std::{
 use cosmwasm_storage::to_length_prefixed;
 use std::collections::HashMap;
 
-use cw20::TokenInfoResponse;
+use cw20::{BalanceResponse, TokenInfoResponse};
 use moneymarket::interest_model::BorrowRateResponse;
 use moneymarket::overseer::{BorrowLimitResponse, ConfigResponse};
 
@@ -31,6 +31,7 @@ pub enum QueryMsg {
     BorrowLimit {
         borrower: String,
         block_time: Option<u64>,
+        market: Option<String>,
     },
     /// Query ANC emission rate to distribution model contract
     BorrowerIncentivesRate {
@@ -43,6 +44,8 @@ pub enum QueryMsg {
     Config {},
     /// Query cw20 Token Info
     TokenInfo {},
+    /// Query cw20 Balance
+    Balance { address: String },
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -184,6 +187,7 @@ impl WasmMockQuerier {
                     QueryMsg::BorrowLimit {
                         borrower,
                         block_time: _,
+                        market: _,
                     } => match self.borrow_limit_querier.borrow_limit.get(&borrower) {
                         Some(v) => SystemResult::Ok(ContractResult::from(to_json_binary(
                             &BorrowLimitResponse {
@@ -258,6 +262,19 @@ impl WasmMockQuerier {
                             total_supply,
                         })))
                     }
+                    QueryMsg::Balance { address } => {
+                        let balance = self
+                            .token_querier
+                            .balances
+                            .get(contract_addr)
+                            .and_then(|balances| balances.get(&address))
+                            .cloned()
+                            .unwrap_or_default();
+
+                        SystemResult::Ok(ContractResult::from(to_json_binary(&BalanceResponse {
+                            balance,
+                        })))
+                    }
                 }
             }
             QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
@@ -1,21 +1,31 @@
 use crate::contract::{execute, instantiate, query, reply, INITIAL_DEPOSIT_AMOUNT};
 use crate::error::ContractError;
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{read_borrower_infos, read_state, store_state, State};
+use crate::state::{
+    read_borrower_info, read_borrower_infos, read_borrowers_by_debt, read_fees_collected,
+    read_state, store_debt_index_backfill, store_state, DebtIndexBackfill, State,
+};
 use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_std::SubMsgResult;
+#[allow(deprecated)]
+use cosmwasm_storage::bucket;
 use moneymarket::bucket::ExecuteMsg as BucketExecuteMsg;
 
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, from_json, to_json_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Decimal256, Reply,
-    SubMsg, SubMsgResponse, Uint128, Uint256, WasmMsg,
+    attr, from_json, to_json_binary, Addr, Api, BankMsg, Coin, CosmosMsg, Decimal, Decimal256,
+    Deps, Reply, StdError, SubMsg, SubMsgResponse, Uint128, Uint256, WasmMsg,
 };
 use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use cw20_base::msg::{InstantiateMarketingInfo, InstantiateMsg as TokenInstantiateMsg};
+use moneymarket::astroport_router::AssetInfo;
 use moneymarket::market::{
-    BorrowerInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
-    StateResponse,
+    AllowanceResponse, AllowancesResponse, BorrowerInfoResponse, BorrowerInfosResponse,
+    BorrowerRewardsMode, ConfigResponse, Cw20HookMsg, ExecuteMsg, FeesCollectedResponse,
+    FixedLoanResponse, FixedLoansResponse, FixedRateConfigMsg, FlashLoanReceiveMsg, InstantiateMsg,
+    InterestTierResponse, InterestTiersResponse, LiabilitiesDriftResponse, LiabilitiesScan,
+    OriginationFee, QueryMsg, RedemptionResponse, RedemptionsResponse, ReserveMode,
+    ReservesResponse, StateResponse,
 };
 use protobuf::Message;
 use std::str::FromStr;
@@ -36,6 +46,7 @@ fn proper_initialization() {
         max_borrow_subsidy_rate: Decimal256::zero(),
 
         initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
 
     let info = mock_info(
@@ -169,6 +180,7 @@ fn update_config() {
         max_borrow_subsidy_rate: Decimal256::zero(),
 
         initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
 
     let info = mock_info(
@@ -214,6 +226,11 @@ fn update_config() {
         distribution_model: None,
         max_borrow_factor: None,
         max_borrow_subsidy_rate: None,
+        fixed_rate: None,
+        flash_loan_fee_rate: None,
+        borrower_rewards_mode: None,
+        reserve_mode: None,
+        origination_fee: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -232,6 +249,11 @@ fn update_config() {
         distribution_model: Some("distribution2".to_string()),
         max_borrow_factor: Some(Decimal256::percent(100)),
         max_borrow_subsidy_rate: None,
+        fixed_rate: None,
+        flash_loan_fee_rate: None,
+        borrower_rewards_mode: None,
+        reserve_mode: None,
+        origination_fee: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -253,6 +275,11 @@ fn update_config() {
         distribution_model: None,
         max_borrow_factor: None,
         max_borrow_subsidy_rate: None,
+        fixed_rate: None,
+        flash_loan_fee_rate: None,
+        borrower_rewards_mode: None,
+        reserve_mode: None,
+        origination_fee: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -278,6 +305,7 @@ fn deposit_stable_huge_amount() {
         max_borrow_subsidy_rate: Decimal256::zero(),
 
         initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
 
     let info = mock_info(
@@ -438,6 +466,7 @@ fn deposit_stable() {
         max_borrow_subsidy_rate: Decimal256::zero(),
 
         initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
 
     let info = mock_info(
@@ -549,7 +578,9 @@ fn deposit_stable() {
             reserves_rate_used_for_borrowers: Decimal256::zero(),
             prev_aterra_supply: Uint256::from(1000000u64),
             prev_exchange_rate: Decimal256::one(),
-            prev_borrower_incentives: Uint256::zero()
+            prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         }
     );
 
@@ -590,6 +621,8 @@ fn deposit_stable() {
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -641,6 +674,8 @@ fn deposit_stable() {
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -669,6 +704,8 @@ fn deposit_stable() {
             prev_aterra_supply: Uint256::from(INITIAL_DEPOSIT_AMOUNT + 1818181),
             prev_exchange_rate: Decimal256::from_ratio(55u64, 100u64),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         }
     );
 }
@@ -689,6 +726,7 @@ fn deposit_stablewith_incentives() {
         max_borrow_subsidy_rate: Decimal256::zero(),
 
         initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
 
     let info = mock_info(
@@ -777,6 +815,8 @@ fn deposit_stablewith_incentives() {
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -828,6 +868,8 @@ fn deposit_stablewith_incentives() {
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -876,6 +918,7 @@ fn redeem_stable() {
         max_borrow_subsidy_rate: Decimal256::zero(),
 
         initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
 
     let info = mock_info(
@@ -994,6 +1037,8 @@ fn redeem_stable() {
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::one(),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -1064,6 +1109,7 @@ fn borrow_stable() {
         max_borrow_subsidy_rate: Decimal256::zero(),
 
         initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
 
     let info = mock_info(
@@ -1121,6 +1167,8 @@ fn borrow_stable() {
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -1183,6 +1231,8 @@ fn borrow_stable() {
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         }
     );
 
@@ -1210,6 +1260,8 @@ fn borrow_stable() {
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         }
     );
 
@@ -1232,6 +1284,10 @@ fn borrow_stable() {
             reward_index: Decimal256::from_str("0").unwrap(),
             loan_amount: Uint256::from(500000u64),
             pending_rewards: Decimal256::zero(),
+            claimable_rewards: Decimal256::zero(),
+            fixed_loan_amount: Uint256::zero(),
+            interest_tier: None,
+            borrow_rate: Decimal256::percent(1),
         }
     );
 
@@ -1254,6 +1310,10 @@ fn borrow_stable() {
             reward_index: Decimal256::from_str("0").unwrap(),
             loan_amount: Uint256::from(500000u64),
             pending_rewards: Decimal256::zero(),
+            claimable_rewards: Decimal256::zero(),
+            fixed_loan_amount: Uint256::zero(),
+            interest_tier: None,
+            borrow_rate: Decimal256::percent(1),
         }
     );
 
@@ -1278,6 +1338,10 @@ fn borrow_stable() {
             reward_index: Decimal256::from_str("0").unwrap(),
             loan_amount: Uint256::from(1000000u64),
             pending_rewards: Decimal256::zero(),
+            claimable_rewards: Decimal256::zero(),
+            fixed_loan_amount: Uint256::zero(),
+            interest_tier: None,
+            borrow_rate: Decimal256::percent(1),
         }
     );
 
@@ -1293,6 +1357,168 @@ fn borrow_stable() {
     }
 }
 
+#[test]
+fn borrow_stable_on_behalf() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    // No allowance yet
+    let msg = ExecuteMsg::BorrowStableOnBehalf {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(300000u64),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("vault", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::AllowanceExceeded(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let approve_msg = ExecuteMsg::ApproveDelegate {
+        delegate: "vault".to_string(),
+        amount: Uint256::from(500000u64),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        approve_msg,
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("vault", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "vault".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(300000u128),
+            }]
+        }))]
+    );
+
+    // The debt is recorded on the borrower
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let borrower_info: BorrowerInfoResponse = from_json(&res).unwrap();
+    assert_eq!(borrower_info.loan_amount, Uint256::from(300000u64));
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Allowances {
+            borrower: "addr0000".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let allowances: AllowancesResponse = from_json(&res).unwrap();
+    assert_eq!(
+        allowances.allowances,
+        vec![AllowanceResponse {
+            borrower: "addr0000".to_string(),
+            delegate: "vault".to_string(),
+            allowance: Uint256::from(200000u64),
+        }]
+    );
+
+    let revoke_msg = ExecuteMsg::RevokeDelegate {
+        delegate: "vault".to_string(),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        revoke_msg,
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Allowance {
+            borrower: "addr0000".to_string(),
+            delegate: "vault".to_string(),
+        },
+    )
+    .unwrap();
+    let allowance: AllowanceResponse = from_json(&res).unwrap();
+    assert_eq!(allowance.allowance, Uint256::zero());
+
+    let res = execute(deps.as_mut(), env, mock_info("vault", &[]), msg);
+    match res {
+        Err(ContractError::AllowanceExceeded(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
 #[test]
 fn assert_max_borrow_factor() {
     let mut deps = mock_dependencies(&[Coin {
@@ -1313,6 +1539,7 @@ fn assert_max_borrow_factor() {
         max_borrow_subsidy_rate: Decimal256::zero(),
 
         initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
 
     let info = mock_info(
@@ -1369,6 +1596,8 @@ fn assert_max_borrow_factor() {
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -1429,6 +1658,7 @@ fn repay_stable() {
         max_borrow_subsidy_rate: Decimal256::zero(),
 
         initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
 
     let info = mock_info(
@@ -1486,6 +1716,8 @@ fn repay_stable() {
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -1545,10 +1777,11 @@ fn repay_stable() {
     );
 
     //Loan amount and Total liability have decreased according to the repayment
-    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
+    let res_loan = read_borrower_infos(deps.as_ref().storage, None, None)
         .unwrap()
         .get(0)
         .unwrap()
+        .1
         .loan_amount;
     assert_eq!(res_loan, Uint256::from(400000u128));
     assert_eq!(
@@ -1571,10 +1804,11 @@ fn repay_stable() {
     );
 
     //Loan amount and Total liability have decreased according to the repayment
-    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
+    let res_loan = read_borrower_infos(deps.as_ref().storage, None, None)
         .unwrap()
         .get(0)
         .unwrap()
+        .1
         .loan_amount;
     assert_eq!(res_loan, Uint256::zero());
     assert_eq!(
@@ -1595,27 +1829,21 @@ fn repay_stable() {
 }
 
 #[test]
-fn repay_stable_from_liquidation() {
+fn repay_stable_with_aterra() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
     }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
 
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         stable_denom: "uusd".to_string(),
         aterra_code_id: 123u64,
-        //anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
         max_borrow_subsidy_rate: Decimal256::zero(),
-
         initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
-
     let info = mock_info(
         "addr0000",
         &[Coin {
@@ -1623,8 +1851,6 @@ fn repay_stable_from_liquidation() {
             amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
         }],
     );
-
-    // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     // Register anchor token contract
@@ -1639,7 +1865,6 @@ fn repay_stable_from_liquidation() {
     };
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-    // Register overseer contract
     let msg = ExecuteMsg::RegisterContracts {
         overseer_contract: "overseer".to_string(),
         interest_model: "interest".to_string(),
@@ -1648,127 +1873,116 @@ fn repay_stable_from_liquidation() {
         borrow_reserves_bucket_contract: "bucket".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let mut env = mock_env();
-    let info = mock_info("owner", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
 
-    let info = mock_info("addr0000", &[]);
     deps.querier
         .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
     deps.querier
         .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
-
-    store_state(
-        deps.as_mut().storage,
-        &State {
-            total_liabilities: Decimal256::from_ratio(1000000u128, 1u128),
-            total_reserves: Decimal256::zero(),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::one(),
-            global_reward_index: Decimal256::zero(),
-            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-            prev_borrower_incentives: Uint256::zero(),
-        },
-    )
-    .unwrap();
+    deps.querier.with_token_balances(&[(
+        &"at-uusd".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(2000000u128))],
+    )]);
 
     let msg = ExecuteMsg::BorrowStable {
         borrow_amount: Uint256::from(500000u64),
         to: None,
     };
-
-    env.block.height += 100;
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    // update balance to make repay
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
     deps.querier.update_balance(
         MOCK_CONTRACT_ADDR.to_string(),
-        vec![
-            Coin {
-                denom: "ukrw".to_string(),
-                amount: Uint128::from(100000u128),
-            },
-            Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
-            },
-        ],
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT - 500000u128),
+        }],
     );
 
-    let msg = ExecuteMsg::RepayStableFromLiquidation {
-        borrower: "addr0000".to_string(),
-        prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
-    };
+    let mut state = read_state(deps.as_ref().storage).unwrap();
+    state.prev_aterra_supply = Uint256::from(2000000u64);
+    store_state(deps.as_mut().storage, &state).unwrap();
 
-    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    // exchange rate is (500000 + 500000) / 2000000 = 0.5
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(600000u128),
+        msg: to_json_binary(&Cw20HookMsg::RepayStableWithAterra { borrower: None }).unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
     match res {
         Err(ContractError::Unauthorized {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let info = mock_info("overseer", &[]);
-
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    let _uusd_string = "uusd";
-    match res {
-        Err(ContractError::ZeroRepay(__uusd_string)) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-
-    // update balance to make repay
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
-        }],
+    let info = mock_info("at-uusd", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "at-uusd".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(600000u128),
+            })
+            .unwrap()
+        }))]
     );
-
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "repay_stable"),
+            attr("action", "repay_stable_with_aterra"),
             attr("borrower", "addr0000"),
-            attr("repay_amount", "100000"),
+            attr("burn_amount", "600000"),
+            attr("repay_amount", "300000"),
         ]
     );
 
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 500000u128),
-        }],
-    );
-
+    // Overpayment is refunded in aTerra
+    deps.querier.with_token_balances(&[(
+        &"at-uusd".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(1400000u128))],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_json_binary(&Cw20HookMsg::RepayStableWithAterra { borrower: None }).unwrap(),
+    });
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
     assert_eq!(
-        res.attributes,
+        res.messages,
         vec![
-            attr("action", "repay_stable"),
-            attr("borrower", "addr0000"),
-            attr("repay_amount", "400000"),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "at-uusd".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(400000u128),
+                })
+                .unwrap()
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "at-uusd".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(600000u128),
+                })
+                .unwrap()
+            }))
         ]
     );
-
     assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::from(100000u128),
-            }]
-        }))]
+        read_state(deps.as_ref().storage).unwrap().total_liabilities,
+        Decimal256::zero()
     );
 }
-/*
+
 #[test]
-fn claim_rewards() {
+fn repay_stable_from_liquidation() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
@@ -1784,6 +1998,10 @@ fn claim_rewards() {
         aterra_code_id: 123u64,
         //anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
 
     let info = mock_info(
@@ -1815,112 +2033,2822 @@ fn claim_rewards() {
         interest_model: "interest".to_string(),
         distribution_model: "distribution".to_string(),
         collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let info = mock_info("addr0000", &[]);
     let mut env = mock_env();
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_ratio(1000000u128, 1u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // update balance to make repay
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![
+            Coin {
+                denom: "ukrw".to_string(),
+                amount: Uint128::from(100000u128),
+            },
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            },
+        ],
+    );
+
+    let msg = ExecuteMsg::RepayStableFromLiquidation {
+        borrower: "addr0000".to_string(),
+        prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+    };
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("overseer", &[]);
+
+    // The queue filled nothing, the whole collateral is left to the Dutch
+    // auction and the market balance did not move
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "0"),
+        ]
+    );
+    assert_eq!(res.messages, vec![]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let borrower_info: BorrowerInfoResponse = from_json(&res).unwrap();
+    assert_eq!(borrower_info.loan_amount, Uint256::from(500000u64));
+
+    // The auction lot bought later repays the loan of the borrower
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(
+            "liquidation",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(50000u128),
+            }],
+        ),
+        ExecuteMsg::RepayStableFromAuction {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "50000"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let borrower_info: BorrowerInfoResponse = from_json(&res).unwrap();
+    assert_eq!(borrower_info.loan_amount, Uint256::from(450000u64));
+
+    // update balance to make repay
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
+        }],
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "100000"),
+        ]
+    );
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 500000u128),
+        }],
+    );
+
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "350000"),
+        ]
+    );
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(150000u128),
+            }]
+        }))]
+    );
+}
+#[test]
+fn cw20_stable() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "stable".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: Some(AssetInfo::Token {
+            contract_addr: Addr::unchecked("stable"),
+        }),
+    };
+    let info = mock_info("addr0000", &[]);
+
+    // The borrower incentives are only paid in the native stable
+    let res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        InstantiateMsg {
+            initial_borrower_incentives: Decimal256::percent(10),
+            ..msg.clone()
+        },
+    );
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
+            msg,
+            "borrower incentives are not supported with a CW20 stable"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The initial deposit must be transferred before the instantiation
+    let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::InitialFundsNotDeposited(_, _)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_token_balances(&[
+        (
+            &"stable".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            )],
+        ),
+        (
+            &"at-stable".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            )],
+        ),
+    ]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-stable".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let config: ConfigResponse =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config.stable_asset,
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("stable"),
+        }
+    );
+
+    // Native funds are not accepted
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "stable".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DepositStable {},
+    );
+    match res {
+        Err(ContractError::ZeroDeposit(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_json_binary(&Cw20HookMsg::DepositStable {}).unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("other", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_token_balances(&[
+        (
+            &"stable".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2000000u128))],
+        ),
+        (
+            &"at-stable".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            )],
+        ),
+    ]);
+    let res = execute(deps.as_mut(), mock_env(), mock_info("stable", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "at-stable".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(1000000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // Borrowed funds are transferred
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "stable".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(500000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // The repay amount left is transferred back
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(600000u128),
+        msg: to_json_binary(&Cw20HookMsg::RepayStable { borrower: None }).unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("stable", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "500000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "stable".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(100000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // The native stable held by the bucket is not given out as incentives
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier.update_balance(
+        "bucket".to_string(),
+        vec![Coin {
+            denom: "stable".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.height += 10;
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100000u64),
+        to: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "stable".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(100000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_borrow_subsidy_rate: None,
+        fixed_rate: None,
+        flash_loan_fee_rate: None,
+        borrower_rewards_mode: Some(BorrowerRewardsMode::Claimable),
+        reserve_mode: None,
+        origination_fee: None,
+    };
+    let res = execute(deps.as_mut(), env, mock_info("owner", &[]), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
+            msg,
+            "claimable borrower rewards are not supported with a CW20 stable"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn redemption_queue() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0001".to_string(), &Uint256::from(1000000u64))]);
+    deps.querier.with_token_balances(&[(
+        &"at-uusd".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(1000000u128))],
+    )]);
+
+    // The whole liquidity is borrowed
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(1000000u64),
+        to: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR.to_string(), vec![]);
+
+    let mut state = read_state(deps.as_ref().storage).unwrap();
+    state.prev_aterra_supply = Uint256::from(1000000u64);
+    store_state(deps.as_mut().storage, &state).unwrap();
+
+    // Only aTerra can be queued
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(600000u128),
+        msg: to_json_binary(&Cw20HookMsg::QueueRedemption {}).unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Nothing can be redeemed yet, the aTerra stays in the queue
+    let res = execute(deps.as_mut(), env.clone(), mock_info("at-uusd", &[]), msg).unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "queue_redemption"),
+            attr("redeemer", "addr0000"),
+            attr("redemption_id", "0"),
+            attr("aterra_amount", "600000"),
+        ]
+    );
+
+    // Repayments fill the queue at the exchange rate of 1
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(400000u128),
+        }],
+    );
+    let msg = ExecuteMsg::RepayStable { borrower: None };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(400000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "at-uusd".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(400000u128),
+                })
+                .unwrap()
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(400000u128),
+                }],
+            })),
+        ]
+    );
+    assert_eq!(
+        read_state(deps.as_ref().storage)
+            .unwrap()
+            .prev_aterra_supply,
+        Uint256::from(600000u64)
+    );
+
+    let res: RedemptionsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Redemptions {
+                redeemer: "addr0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.redemptions,
+        vec![RedemptionResponse {
+            redemption_id: 0,
+            redeemer: "addr0000".to_string(),
+            aterra_amount: Uint256::from(200000u64),
+        }]
+    );
+
+    // Only the redeemer can cancel, the rest of the aTerra is sent back
+    let msg = ExecuteMsg::CancelRedemption { redemption_id: 0 };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "at-uusd".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(200000u128),
+            })
+            .unwrap()
+        }))]
+    );
+
+    let res: RedemptionsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Redemptions {
+                redeemer: "addr0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.redemptions, vec![]);
+}
+
+#[test]
+fn fixed_rate_loans() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    // Fixed rate loans are disabled until the owner sets the terms
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::BorrowFixed {
+        borrow_amount: Uint256::from(500000u64),
+        term: 100u64,
+        to: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::InvalidFixedTerm(0, 0)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg_config = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_borrow_subsidy_rate: None,
+        fixed_rate: Some(FixedRateConfigMsg {
+            premium_rate: Some(Decimal256::from_str("0.005").unwrap()),
+            min_term: Some(10u64),
+            max_term: Some(1000u64),
+        }),
+        flash_loan_fee_rate: None,
+        borrower_rewards_mode: None,
+        reserve_mode: None,
+        origination_fee: None,
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        msg_config,
+    )
+    .unwrap();
+
+    // borrow rate 1% + premium 0.5%
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "borrow_fixed"),
+            attr("borrower", "addr0000"),
+            attr("loan_id", "0"),
+            attr("borrow_amount", "500000"),
+            attr("rate", "0.015"),
+            attr("maturity_height", (env.block.height + 100).to_string()),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(500000u128),
+            }]
+        }))]
+    );
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT - 500000u128),
+        }],
+    );
+
+    // Cannot roll over before maturity
+    env.block.height += 50;
+    let msg = ExecuteMsg::RolloverFixed {
+        loan_id: 0u64,
+        term: 100u64,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::FixedLoanNotMatured(0)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The loan accrues at its own rate and counts in the liabilities
+    env.block.height += 50;
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::FixedLoan {
+            loan_id: 0u64,
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let loan: FixedLoanResponse = from_json(&res).unwrap();
+    assert_eq!(loan.amount_due, Uint256::from(1250000u64));
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let borrower_info: BorrowerInfoResponse = from_json(&res).unwrap();
+    assert_eq!(borrower_info.loan_amount, Uint256::zero());
+    assert_eq!(borrower_info.fixed_loan_amount, Uint256::from(1250000u64));
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::State { block_height: None },
+    )
+    .unwrap();
+    let state: StateResponse = from_json(&res).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_ratio(1250000u128, 1u128)
+    );
+
+    // Partial repayment, the amount left due becomes the new principal
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT - 250000u128),
+        }],
+    );
+    let msg = ExecuteMsg::RepayFixed { loan_id: 0u64 };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(250000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::FixedLoan {
+            loan_id: 0u64,
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let loan: FixedLoanResponse = from_json(&res).unwrap();
+    assert_eq!(loan.principal, Uint256::from(1000000u64));
+    assert_eq!(loan.start_height, env.block.height);
+
+    // A Dutch auction lot covers the fixed rate loans like the liquidation
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(
+            "liquidation",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(250000u128),
+            }],
+        ),
+        ExecuteMsg::RepayStableFromAuction {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "250000"),
+        ]
+    );
+    assert_eq!(res.messages, vec![]);
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::FixedLoan {
+            loan_id: 0u64,
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let loan: FixedLoanResponse = from_json(&res).unwrap();
+    assert_eq!(loan.principal, Uint256::from(750000u64));
+
+    // Full repayment, the excess is returned
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 750000u128),
+        }],
+    );
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1100000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(350000u128),
+            }]
+        }))]
+    );
+    assert_eq!(
+        read_state(deps.as_ref().storage).unwrap().total_liabilities,
+        Decimal256::zero()
+    );
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::FixedLoans {
+            borrower: "addr0000".to_string(),
+            start_after: None,
+            limit: None,
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let loans: FixedLoansResponse = from_json(&res).unwrap();
+    assert_eq!(loans.loans, vec![]);
+}
+
+#[test]
+fn flash_loan() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_borrow_subsidy_rate: None,
+        fixed_rate: None,
+        flash_loan_fee_rate: Some(Decimal256::permille(1)),
+        borrower_rewards_mode: None,
+        reserve_mode: None,
+        origination_fee: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // Cannot lend more than the market balance
+    let info = mock_info("receiver", &[]);
+    let msg = ExecuteMsg::FlashLoan {
+        amount: Uint256::from(INITIAL_DEPOSIT_AMOUNT + 1u128),
+        msg: to_json_binary("callback").unwrap(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::NoStableAvailable(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::FlashLoan {
+        amount: Uint256::from(500000u64),
+        msg: to_json_binary("callback").unwrap(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            FlashLoanReceiveMsg {
+                amount: Uint256::from(500000u64),
+                fee: Uint256::from(500u64),
+                msg: to_json_binary("callback").unwrap(),
+            }
+            .into_cosmos_msg(
+                "receiver",
+                vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(500000u128),
+                }]
+            )
+            .unwrap(),
+            2
+        )]
+    );
+
+    // Nothing else can run while the loan is in flight
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::FlashLoanInProgress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let flash_loan_reply = Reply {
+        id: 2,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    // The fee was not paid back
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let res = reply(deps.as_mut(), env.clone(), flash_loan_reply.clone());
+    match res {
+        Err(ContractError::FlashLoanNotRepaid(amount)) => {
+            assert_eq!(amount, Uint128::from(500500u128))
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 500u128),
+        }],
+    );
+    let _res = reply(deps.as_mut(), env, flash_loan_reply).unwrap();
+    assert_eq!(
+        read_state(deps.as_ref().storage).unwrap().total_reserves,
+        Decimal256::from_ratio(500u128, 1u128)
+    );
+    assert_eq!(
+        read_fees_collected(deps.as_ref().storage)
+            .unwrap()
+            .flash_loan,
+        Uint256::from(500u64)
+    );
+}
+
+#[test]
+fn flash_loan_repay_in_callback() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_borrow_subsidy_rate: None,
+        fixed_rate: None,
+        flash_loan_fee_rate: Some(Decimal256::permille(1)),
+        borrower_rewards_mode: None,
+        reserve_mode: None,
+        origination_fee: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(300000u64),
+        to: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT - 300000u128),
+        }],
+    );
+
+    let msg = ExecuteMsg::FlashLoan {
+        amount: Uint256::from(500000u64),
+        msg: to_json_binary("callback").unwrap(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("receiver", &[]), msg).unwrap();
+
+    // Deposits can't run inside the callback
+    let msg = ExecuteMsg::DepositStable {};
+    let info = mock_info(
+        "receiver",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::FlashLoanInProgress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The receiver repays the borrower loan with the flash loan
+    let msg = ExecuteMsg::RepayStable {
+        borrower: Some("addr0000".to_string()),
+    };
+    let info = mock_info(
+        "receiver",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(300000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("repay_amount", "300000")));
+
+    let flash_loan_reply = Reply {
+        id: 2,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    // The repayment does not count as returning the loan
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT - 299500u128),
+        }],
+    );
+    let res = reply(deps.as_mut(), env.clone(), flash_loan_reply.clone());
+    match res {
+        Err(ContractError::FlashLoanNotRepaid(amount)) => {
+            assert_eq!(amount, Uint128::from(500500u128))
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 500u128),
+        }],
+    );
+    let _res = reply(deps.as_mut(), env.clone(), flash_loan_reply).unwrap();
+
+    let res: BorrowerInfoResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, Uint256::zero());
+}
+
+#[test]
+fn claim_borrower_rewards() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_borrow_subsidy_rate: None,
+        fixed_rate: None,
+        flash_loan_fee_rate: None,
+        borrower_rewards_mode: Some(BorrowerRewardsMode::Claimable),
+        reserve_mode: None,
+        origination_fee: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let res: ConfigResponse =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(res.borrower_rewards_mode, BorrowerRewardsMode::Claimable);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    deps.querier.update_balance(
+        "bucket".to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Nothing accrued yet
+    let msg = ExecuteMsg::ClaimBorrowerRewards {
+        to: Some("addr0001".to_string()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    // interest_factor = 1% * 10 blocks = 0.1, not lowered by the incentives
+    // the 1000 incentives are reserved, global_reward_index = 1000 / 550000
+    // pending_rewards = 550000 / 1.1 * 1000 / 550000 = 909.09
+    env.block.height += 10;
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "bucket".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&BucketExecuteMsg::SendTo {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(909u128),
+                recipient: "addr0001".to_string(),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_ratio(550000u128, 1u128)
+    );
+    assert_eq!(state.reserved_borrower_rewards, Uint256::from(91u64));
+
+    let res: BorrowerInfoResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.pending_rewards < Decimal256::one());
+}
+
+#[test]
+fn claim_borrower_rewards_mode_switch() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier.with_borrow_limit(&[
+        (&"addr0000".to_string(), &Uint256::from(1000000u64)),
+        (&"addr0001".to_string(), &Uint256::from(1000000u64)),
+    ]);
+    deps.querier.update_balance(
+        "bucket".to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+
+    // addr0000 borrows in subsidize mode
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // the 1000 incentives of the first 10 blocks lower the interest of addr0000
+    env.block.height += 10;
+    let msg = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_borrow_subsidy_rate: None,
+        fixed_rate: None,
+        flash_loan_fee_rate: None,
+        borrower_rewards_mode: Some(BorrowerRewardsMode::Claimable),
+        reserve_mode: None,
+        origination_fee: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "bucket".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&BucketExecuteMsg::Send {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // addr0001 borrows in claimable mode, the bucket is refilled
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+
+    // the 1000 incentives of the next 10 blocks are reserved for both borrowers
+    // total_liabilities = 549000 * 1.1 + 550000 = 1153900
+    // addr0000 claims 500000 * 1000 / 1153900 = 433.31, its subsidized rewards are not claimable
+    // addr0001 claims 550000 / 1.2078 * 1000 / 1153900 = 394.63
+    env.block.height += 10;
+    let msg = ExecuteMsg::ClaimBorrowerRewards { to: None };
+    for (borrower, amount) in [("addr0000", 433u128), ("addr0001", 394u128)] {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(borrower, &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "bucket".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&BucketExecuteMsg::SendTo {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(amount),
+                    recipient: borrower.to_string(),
+                })
+                .unwrap(),
+            }))]
+        );
+    }
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.reserved_borrower_rewards, Uint256::from(173u64));
+
+    let res: BorrowerInfoResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    // the 500000 * 1000 / 549000 = 910.74 subsidized rewards stay pending
+    assert!(res.claimable_rewards < Decimal256::one());
+    assert_eq!(res.pending_rewards * Uint256::one(), Uint256::from(911u64));
+
+    // Nothing left to claim
+    let res = execute(deps.as_mut(), env, mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(res.messages, vec![]);
+}
+
+/*
+#[test]
+fn claim_rewards() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        //anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let mut env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_ratio(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::zero(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    // zero loan claim, will return empty messages
+    let msg = ExecuteMsg::ClaimRewards { to: None };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // zero block passed
+    let msg = ExecuteMsg::ClaimRewards {
+        to: Some("addr0001".to_string()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    // 100 blocks passed
+    env.block.height += 100;
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "distributor".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&FaucetExecuteMsg::Spend {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(33u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let res: BorrowerInfoResponse = from_json(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.pending_rewards,
+        Decimal256::from_str("0.333333333333").unwrap()
+    );
+    assert_eq!(
+        res.reward_index,
+        Decimal256::from_str("0.000066666666666666").unwrap()
+    );
+}
+*/
+
+#[test]
+fn execute_epoch_operations() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        //anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let mut info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_ratio(1000000u128, 1u128),
+            total_reserves: Decimal256::from_ratio(3000u128, 1u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    env.block.height += 100;
+
+    // reserve == 3000
+    let msg = ExecuteMsg::ExecuteEpochOperations {
+        deposit_rate: Decimal256::one(),
+        target_deposit_rate: Decimal256::one(),
+        threshold_deposit_rate: Decimal256::one(),
+        distributed_interest: Uint256::zero(),
+    };
+
+    // only overseer can execute this
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    info.sender = Addr::unchecked("overseer");
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "collector".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(3000u128), // No tax fee
+            }],
+        }))]
+    );
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state,
+        State {
+            total_liabilities: Decimal256::from_ratio(2000000u128, 1u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::from_ratio(2u64, 1u128),
+            global_reward_index: Decimal256::from_str("0").unwrap(),
+            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
+        }
+    );
+
+    // When there is not enough balance to cover reserve
+    // no message will be sent and reserve will be left as same
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(2999u128),
+        }],
+    );
+
+    let mut env = mock_env();
+    let info = mock_info("overseer", &[]);
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_ratio(1000000u128, 1u128),
+            total_reserves: Decimal256::from_ratio(3000u128, 1u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    env.block.height += 100;
+
+    // reserve == 3000
+    let msg = ExecuteMsg::ExecuteEpochOperations {
+        deposit_rate: Decimal256::one(),
+        target_deposit_rate: Decimal256::one(),
+        threshold_deposit_rate: Decimal256::one(),
+        distributed_interest: Uint256::zero(),
+    };
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state,
+        State {
+            total_liabilities: Decimal256::from_ratio(2000000u128, 1u128),
+            total_reserves: Decimal256::from_ratio(3000u128, 1u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::from_ratio(2u64, 1u128),
+            global_reward_index: Decimal256::from_str("0").unwrap(),
+            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
+        }
+    );
+}
+
+#[test]
+fn reserve_factor() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    let update_config = |reserve_mode| ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_borrow_subsidy_rate: None,
+        fixed_rate: None,
+        flash_loan_fee_rate: None,
+        borrower_rewards_mode: None,
+        reserve_mode: Some(reserve_mode),
+        origination_fee: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        update_config(ReserveMode::ReserveFactor {
+            reserve_factor: Decimal256::percent(101),
+        }),
+    );
+    match res {
+        Err(ContractError::InvalidReserveFactor {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        update_config(ReserveMode::ReserveFactor {
+            reserve_factor: Decimal256::percent(10),
+        }),
+    )
+    .unwrap();
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_ratio(1000000u128, 1u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    env.block.height += 100;
+
+    // 10% of the 1000000 interest goes to the reserves, which are not swept
+    let msg = ExecuteMsg::ExecuteEpochOperations {
+        deposit_rate: Decimal256::one(),
+        target_deposit_rate: Decimal256::one(),
+        threshold_deposit_rate: Decimal256::one(),
+        distributed_interest: Uint256::zero(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("overseer", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Reserves { block_height: None },
+    )
+    .unwrap();
+    let reserves: ReservesResponse = from_json(res).unwrap();
+    assert_eq!(
+        reserves,
+        ReservesResponse {
+            reserve_mode: ReserveMode::ReserveFactor {
+                reserve_factor: Decimal256::percent(10),
+            },
+            total_reserves: Decimal256::from_ratio(100000u128, 1u128),
+            total_withdrawn: Uint256::zero(),
+            total_accrued: Decimal256::from_ratio(100000u128, 1u128),
+        }
+    );
+
+    let msg = ExecuteMsg::WithdrawReserves {
+        amount: Uint256::from(200000u64),
+        to: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg);
+    match res {
+        Err(ContractError::WithdrawReservesTooLarge(amount)) => {
+            assert_eq!(amount, Uint128::from(100000u128))
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Part of the reserves is sent to the collector
+    let msg = ExecuteMsg::WithdrawReserves {
+        amount: Uint256::from(40000u64),
+        to: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "collector".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(40000u128),
+            }],
+        }))]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Reserves { block_height: None },
+    )
+    .unwrap();
+    let reserves: ReservesResponse = from_json(res).unwrap();
+    assert_eq!(
+        reserves.total_reserves,
+        Decimal256::from_ratio(60000u128, 1u128)
+    );
+    assert_eq!(reserves.total_withdrawn, Uint256::from(40000u64));
+    assert_eq!(
+        reserves.total_accrued,
+        Decimal256::from_ratio(100000u128, 1u128)
+    );
+}
+
+#[test]
+fn interest_tiers() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::SetInterestTier {
+        tier_id: 1,
+        discount: Decimal256::percent(50),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetInterestTier {
+            tier_id: 1,
+            discount: Decimal256::percent(101),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidDiscount {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::InterestTiers {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let tiers: InterestTiersResponse = from_json(res).unwrap();
+    assert_eq!(
+        tiers.tiers,
+        vec![InterestTierResponse {
+            tier_id: 1,
+            discount: Decimal256::percent(50),
+        }]
+    );
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::SetBorrowerTier {
+        borrower: "addr0000".to_string(),
+        tier_id: Some(1),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // interest_factor is 100%, the borrower pays half of it
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: Some(env.block.height + 100),
+        },
+    )
+    .unwrap();
+    let borrower_info: BorrowerInfoResponse = from_json(res).unwrap();
+    assert_eq!(borrower_info.loan_amount, Uint256::from(750000u64));
+    assert_eq!(borrower_info.interest_tier, Some(1));
+    assert_eq!(borrower_info.borrow_rate, Decimal256::permille(5));
+
+    // The waived interest is taken off the total liabilities as it accrues
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::State {
+            block_height: Some(env.block.height + 100),
+        },
+    )
+    .unwrap();
+    let state: StateResponse = from_json(res).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_ratio(750000u128, 1u128)
+    );
+
+    env.block.height += 100;
+    let msg = ExecuteMsg::SetBorrowerTier {
+        borrower: "addr0000".to_string(),
+        tier_id: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_ratio(750000u128, 1u128)
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let borrower_info: BorrowerInfoResponse = from_json(res).unwrap();
+    assert_eq!(borrower_info.loan_amount, Uint256::from(750000u64));
+    assert_eq!(borrower_info.interest_tier, None);
+    assert_eq!(borrower_info.borrow_rate, Decimal256::percent(1));
+}
+
+#[test]
+fn origination_fee() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
 
     deps.querier
         .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
     deps.querier
         .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
 
-    store_state(
-        deps.as_mut().storage,
-        &State {
-            total_liabilities: Decimal256::from_ratio(1000000u128),
-            total_reserves: Decimal256::zero(),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::one(),
-            global_reward_index: Decimal256::zero(),
-            anc_emission_rate: Decimal256::zero(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    let update_config = |origination_fee| ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_borrow_subsidy_rate: None,
+        fixed_rate: None,
+        flash_loan_fee_rate: None,
+        borrower_rewards_mode: None,
+        reserve_mode: None,
+        origination_fee: Some(origination_fee),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        update_config(OriginationFee {
+            rate: Decimal256::one(),
+            deduct_from_disbursement: false,
+            receiver: None,
+        }),
+    );
+    match res {
+        Err(ContractError::InvalidOriginationFee {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The fee is added to the loan and goes to the reserves
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        update_config(OriginationFee {
+            rate: Decimal256::percent(1),
+            deduct_from_disbursement: false,
+            receiver: None,
+        }),
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100000u64),
+        to: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100000u128),
+            }]
+        }))]
+    );
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_ratio(101000u128, 1u128)
+    );
+    assert_eq!(
+        state.total_reserves,
+        Decimal256::from_ratio(1000u128, 1u128)
+    );
+
+    // The fee is kept out of the sent amount and goes to the receiver
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        update_config(OriginationFee {
+            rate: Decimal256::percent(1),
+            deduct_from_disbursement: true,
+            receiver: Some("fee_receiver".to_string()),
+        }),
+    )
+    .unwrap();
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(99000u128),
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee_receiver".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(1000u128),
+                }]
+            }))
+        ]
+    );
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let borrower_info: BorrowerInfoResponse = from_json(res).unwrap();
+    assert_eq!(borrower_info.loan_amount, Uint256::from(201000u64));
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.total_reserves,
+        Decimal256::from_ratio(1000u128, 1u128)
+    );
+
+    // Fixed rate loans pay the fee the same way, added to the principal
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        update_config(OriginationFee {
+            rate: Decimal256::percent(1),
+            deduct_from_disbursement: false,
+            receiver: None,
+        }),
+    )
+    .unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            owner_addr: None,
+            interest_model: None,
+            distribution_model: None,
+            max_borrow_factor: None,
+            max_borrow_subsidy_rate: None,
+            fixed_rate: Some(FixedRateConfigMsg {
+                premium_rate: Some(Decimal256::from_str("0.005").unwrap()),
+                min_term: Some(10u64),
+                max_term: Some(1000u64),
+            }),
+            flash_loan_fee_rate: None,
+            borrower_rewards_mode: None,
+            reserve_mode: None,
+            origination_fee: None,
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowFixed {
+        borrow_amount: Uint256::from(100000u64),
+        term: 100u64,
+        to: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    assert!(res.attributes.contains(&attr("origination_fee", "1000")));
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100000u128),
+            }]
+        }))]
+    );
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::FixedLoan {
+            loan_id: 0u64,
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let loan: FixedLoanResponse = from_json(res).unwrap();
+    assert_eq!(loan.principal, Uint256::from(101000u64));
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.total_reserves,
+        Decimal256::from_ratio(2000u128, 1u128)
+    );
+
+    let res = query(deps.as_ref(), env, QueryMsg::FeesCollected {}).unwrap();
+    let fees_collected: FeesCollectedResponse = from_json(res).unwrap();
+    assert_eq!(
+        fees_collected,
+        FeesCollectedResponse {
+            origination: Uint256::from(3000u64),
+            flash_loan: Uint256::zero(),
+        }
+    );
+}
+
+#[test]
+fn top_borrowers() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        borrow_reserves_bucket_contract: "bucket".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier.with_borrow_limit(&[
+        (&"addr0000".to_string(), &Uint256::from(1000000u64)),
+        (&"addr0001".to_string(), &Uint256::from(1000000u64)),
+        (&"addr0002".to_string(), &Uint256::from(1000000u64)),
+    ]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    for (borrower, amount) in [
+        ("addr0000", 100000u64),
+        ("addr0001", 300000u64),
+        ("addr0002", 200000u64),
+    ] {
+        let msg = ExecuteMsg::BorrowStable {
+            borrow_amount: Uint256::from(amount),
+            to: None,
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(borrower, &[]), msg).unwrap();
+    }
+
+    let query_top_borrowers = |deps: Deps, start_after: Option<&str>, limit: Option<u32>| {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::TopBorrowers {
+                start_after: start_after.map(|borrower| borrower.to_string()),
+                limit,
+                block_height: None,
+            },
+        )
+        .unwrap();
+        let res: BorrowerInfosResponse = from_json(res).unwrap();
+        res.borrower_infos
+            .into_iter()
+            .map(|info| (info.borrower, info.loan_amount))
+            .collect::<Vec<(String, Uint256)>>()
+    };
+    assert_eq!(
+        query_top_borrowers(deps.as_ref(), None, None),
+        vec![
+            ("addr0001".to_string(), Uint256::from(300000u64)),
+            ("addr0002".to_string(), Uint256::from(200000u64)),
+            ("addr0000".to_string(), Uint256::from(100000u64)),
+        ]
+    );
+    assert_eq!(
+        query_top_borrowers(deps.as_ref(), Some("addr0001"), Some(1)),
+        vec![("addr0002".to_string(), Uint256::from(200000u64))]
+    );
+
+    // Repaid borrowers leave the index
+    let msg = ExecuteMsg::RepayStable { borrower: None };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(300000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        query_top_borrowers(deps.as_ref(), None, None),
+        vec![
+            ("addr0002".to_string(), Uint256::from(200000u64)),
+            ("addr0000".to_string(), Uint256::from(100000u64)),
+        ]
+    );
+
+    // interest_factor is 100% after 100 blocks
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerInfos {
+            start_after: None,
+            limit: None,
+            block_height: Some(env.block.height + 100),
+        },
+    )
+    .unwrap();
+    let res: BorrowerInfosResponse = from_json(res).unwrap();
+    assert_eq!(
+        res.borrower_infos
+            .into_iter()
+            .map(|info| info.loan_amount)
+            .collect::<Vec<Uint256>>(),
+        vec![
+            Uint256::from(200000u64),
+            Uint256::zero(),
+            Uint256::from(400000u64)
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::BorrowerInfos {
+            start_after: None,
+            limit: None,
+            block_height: None,
         },
     )
     .unwrap();
+    let res: BorrowerInfosResponse = from_json(res).unwrap();
+    assert_eq!(res.borrower_infos[0].loan_amount, Uint256::from(100000u64));
+}
 
-    // zero loan claim, will return empty messages
-    let msg = ExecuteMsg::ClaimRewards { to: None };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-    assert_eq!(res.messages.len(), 0);
+#[test]
+#[allow(deprecated)]
+fn index_borrowers_by_debt() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
 
-    let msg = ExecuteMsg::BorrowStable {
-        borrow_amount: Uint256::from(500000u64),
-        to: None,
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        max_borrow_factor: Decimal256::one(),
+        max_borrow_subsidy_rate: Decimal256::zero(),
+        initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // zero block passed
-    let msg = ExecuteMsg::ClaimRewards {
-        to: Some("addr0001".to_string()),
-    };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
-    assert_eq!(res.messages.len(), 0);
+    // Liabilities stored before the debt index existed
+    for (borrower, amount) in [
+        ("addr0000", 100000u64),
+        ("addr0001", 300000u64),
+        ("addr0002", 200000u64),
+    ] {
+        let borrower_raw = deps.api.addr_canonicalize(borrower).unwrap();
+        let mut liability = read_borrower_info(deps.as_ref().storage, &borrower_raw);
+        liability.loan_amount = Uint256::from(amount);
+        bucket(deps.as_mut().storage, b"liability")
+            .save(borrower_raw.as_slice(), &liability)
+            .unwrap();
+    }
+    store_debt_index_backfill(deps.as_mut().storage, &DebtIndexBackfill::default()).unwrap();
+    assert_eq!(
+        read_borrowers_by_debt(deps.as_ref().storage, None, None).unwrap(),
+        vec![]
+    );
 
-    // 100 blocks passed
-    env.block.height += 100;
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let msg = ExecuteMsg::IndexBorrowersByDebt { limit: Some(2) };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        msg.clone(),
+    )
+    .unwrap();
     assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "distributor".to_string(),
-            funds: vec![],
-            msg: to_json_binary(&FaucetExecuteMsg::Spend {
-                recipient: "addr0001".to_string(),
-                amount: Uint128::from(33u128),
-            })
-            .unwrap(),
-        }))]
+        res.attributes,
+        vec![
+            attr("action", "index_borrowers_by_debt"),
+            attr("last_borrower", "addr0001"),
+            attr("done", "false"),
+        ]
+    );
+    assert_eq!(
+        read_borrowers_by_debt(deps.as_ref().storage, None, None)
+            .unwrap()
+            .len(),
+        2
     );
 
-    let res: BorrowerInfoResponse = from_json(
-        &query(
-            deps.as_ref(),
-            mock_env(),
-            QueryMsg::BorrowerInfo {
-                borrower: "addr0000".to_string(),
-                block_height: None,
-            },
-        )
-        .unwrap(),
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        msg.clone(),
     )
     .unwrap();
     assert_eq!(
-        res.pending_rewards,
-        Decimal256::from_str("0.333333333333").unwrap()
+        res.attributes,
+        vec![
+            attr("action", "index_borrowers_by_debt"),
+            attr("last_borrower", "addr0002"),
+            attr("done", "true"),
+        ]
     );
+    let borrowers: Vec<Uint256> = read_borrowers_by_debt(deps.as_ref().storage, None, None)
+        .unwrap()
+        .into_iter()
+        .map(|(_, liability)| liability.loan_amount)
+        .collect();
     assert_eq!(
-        res.reward_index,
-        Decimal256::from_str("0.000066666666666666").unwrap()
+        borrowers,
+        vec![
+            Uint256::from(300000u64),
+            Uint256::from(200000u64),
+            Uint256::from(100000u64),
+        ]
     );
+
+    // Nothing left to index
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(res.attributes[2], attr("done", "true"));
 }
-*/
 
 #[test]
-fn execute_epoch_operations() {
+fn reconcile_liabilities() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
     }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
 
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         stable_denom: "uusd".to_string(),
         aterra_code_id: 123u64,
-        //anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
         max_borrow_subsidy_rate: Decimal256::zero(),
-
         initial_borrower_incentives: Decimal256::zero(),
+        stable_asset: None,
     };
-
     let info = mock_info(
         "addr0000",
         &[Coin {
@@ -1928,11 +4856,8 @@ fn execute_epoch_operations() {
             amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
         }],
     );
-
-    // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // Register anchor token contract
     let mut token_inst_res = MsgInstantiateContractResponse::new();
     token_inst_res.set_contract_address("at-uusd".to_string());
     let reply_msg = Reply {
@@ -1944,7 +4869,6 @@ fn execute_epoch_operations() {
     };
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-    // Register overseer contract
     let msg = ExecuteMsg::RegisterContracts {
         overseer_contract: "overseer".to_string(),
         interest_model: "interest".to_string(),
@@ -1953,20 +4877,21 @@ fn execute_epoch_operations() {
         borrow_reserves_bucket_contract: "bucket".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let mut env = mock_env();
-    let mut info = mock_info("owner", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
 
     deps.querier
         .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
-    deps.querier
-        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    deps.querier.with_borrow_limit(&[
+        (&"addr0000".to_string(), &Uint256::from(1000000u64)),
+        (&"addr0001".to_string(), &Uint256::from(1000000u64)),
+    ]);
 
     store_state(
         deps.as_mut().storage,
         &State {
-            total_liabilities: Decimal256::from_ratio(1000000u128, 1u128),
-            total_reserves: Decimal256::from_ratio(3000u128, 1u128),
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
             last_interest_updated: env.block.height,
             last_reward_updated: env.block.height,
             global_interest_index: Decimal256::one(),
@@ -1975,115 +4900,150 @@ fn execute_epoch_operations() {
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
             prev_borrower_incentives: Uint256::zero(),
+            reserved_borrower_rewards: Uint256::zero(),
+            global_claimable_reward_index: Decimal256::zero(),
         },
     )
     .unwrap();
 
-    env.block.height += 100;
+    for borrower in ["addr0000", "addr0001"] {
+        let msg = ExecuteMsg::BorrowStable {
+            borrow_amount: Uint256::from(100000u64),
+            to: None,
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(borrower, &[]), msg).unwrap();
+    }
 
-    // reserve == 3000
-    let msg = ExecuteMsg::ExecuteEpochOperations {
-        deposit_rate: Decimal256::one(),
-        target_deposit_rate: Decimal256::one(),
-        threshold_deposit_rate: Decimal256::one(),
-        distributed_interest: Uint256::zero(),
-    };
+    // Rounding dust left in the liabilities
+    let mut state = read_state(deps.as_ref().storage).unwrap();
+    state.total_liabilities += Decimal256::from_str("0.5").unwrap();
+    state.total_reserves = Decimal256::from_ratio(10u128, 1u128);
+    store_state(deps.as_mut().storage, &state).unwrap();
 
-    // only overseer can execute this
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::LiabilitiesDrift {
+            start_after: None,
+            limit: Some(1),
+            block_height: None,
+            scanned: None,
+        },
+    )
+    .unwrap();
+    let drift: LiabilitiesDriftResponse = from_json(res).unwrap();
+    assert_eq!(drift.last_borrower, Some("addr0000".to_string()));
+    assert_eq!(drift.scanned.loans, Uint256::from(100000u64));
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::LiabilitiesDrift {
+            start_after: drift.last_borrower,
+            limit: Some(1),
+            block_height: None,
+            scanned: Some(drift.scanned),
+        },
+    )
+    .unwrap();
+    let drift: LiabilitiesDriftResponse = from_json(res).unwrap();
+    assert_eq!(
+        drift,
+        LiabilitiesDriftResponse {
+            floating_liabilities: Decimal256::from_str("200000.5").unwrap(),
+            scanned: LiabilitiesScan {
+                loans: Uint256::from(200000u64),
+            },
+            last_borrower: Some("addr0001".to_string()),
+            excess_liabilities: Decimal256::from_str("0.5").unwrap(),
+            missing_liabilities: Decimal256::zero(),
+        }
+    );
+
+    let msg = ExecuteMsg::ReconcileLiabilities {
+        max_drift: Decimal256::from_str("0.1").unwrap(),
+        limit: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
     match res {
         Err(ContractError::Unauthorized {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg);
+    match res {
+        Err(ContractError::LiabilitiesDriftTooLarge(drift)) => {
+            assert_eq!(drift, Decimal256::from_str("0.5").unwrap())
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    info.sender = Addr::unchecked("overseer");
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    // One borrower per page, the liabilities are only adjusted after the final page
+    let msg = ExecuteMsg::ReconcileLiabilities {
+        max_drift: Decimal256::one(),
+        limit: Some(1),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        msg.clone(),
+    )
+    .unwrap();
     assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "collector".to_string(),
-            amount: vec![Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::from(3000u128), // No tax fee
-            }],
-        }))]
+        res.attributes,
+        vec![
+            attr("action", "reconcile_liabilities"),
+            attr("last_borrower", "addr0000"),
+        ]
     );
-
     let state = read_state(deps.as_ref().storage).unwrap();
     assert_eq!(
-        state,
-        State {
-            total_liabilities: Decimal256::from_ratio(2000000u128, 1u128),
-            total_reserves: Decimal256::zero(),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::from_ratio(2u64, 1u128),
-            global_reward_index: Decimal256::from_str("0").unwrap(),
-            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-            prev_borrower_incentives: Uint256::zero(),
-        }
-    );
-
-    // When there is not enough balance to cover reserve
-    // no message will be sent and reserve will be left as same
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(2999u128),
-        }],
+        state.total_liabilities,
+        Decimal256::from_str("200000.5").unwrap()
     );
 
-    let mut env = mock_env();
-    let info = mock_info("overseer", &[]);
-    store_state(
-        deps.as_mut().storage,
-        &State {
-            total_liabilities: Decimal256::from_ratio(1000000u128, 1u128),
-            total_reserves: Decimal256::from_ratio(3000u128, 1u128),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::one(),
-            global_reward_index: Decimal256::zero(),
-            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-            prev_borrower_incentives: Uint256::zero(),
-        },
+    // A scanned borrower updates the scan in progress
+    let borrow_msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(50000u64),
+        to: None,
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        borrow_msg,
     )
     .unwrap();
 
-    env.block.height += 100;
-
-    // reserve == 3000
-    let msg = ExecuteMsg::ExecuteEpochOperations {
-        deposit_rate: Decimal256::one(),
-        target_deposit_rate: Decimal256::one(),
-        threshold_deposit_rate: Decimal256::one(),
-        distributed_interest: Uint256::zero(),
-    };
-
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-    assert_eq!(res.messages.len(), 0);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1], attr("last_borrower", "addr0001"));
 
+    let res = execute(deps.as_mut(), env, mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "reconcile_liabilities"),
+            attr("excess_liabilities", "0.5"),
+            attr("missing_liabilities", "0"),
+        ]
+    );
     let state = read_state(deps.as_ref().storage).unwrap();
     assert_eq!(
-        state,
-        State {
-            total_liabilities: Decimal256::from_ratio(2000000u128, 1u128),
-            total_reserves: Decimal256::from_ratio(3000u128, 1u128),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::from_ratio(2u64, 1u128),
-            global_reward_index: Decimal256::from_str("0").unwrap(),
-            reserves_rate_used_for_borrowers: Decimal256::from_str("0.1").unwrap(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-            prev_borrower_incentives: Uint256::zero(),
-        }
+        state.total_liabilities,
+        Decimal256::from_ratio(250000u128, 1u128)
     );
+    assert_eq!(state.total_reserves, Decimal256::from_str("9.5").unwrap());
 }
 
 // #[test]
@@ -0,0 +1,119 @@
+use cosmwasm_std::{attr, Addr, Decimal256, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use moneymarket::market::{InterestTierResponse, InterestTiersResponse};
+
+use crate::borrow::{compute_borrower_reward, compute_interest, compute_tiered_borrower_interest};
+use crate::error::ContractError;
+use crate::state::{
+    read_borrower_info, read_config, read_interest_tier, read_interest_tiers, read_state,
+    store_borrower_info, store_interest_tier, store_state, BorrowerInfo, BorrowerTier, Config,
+    InterestTier, State,
+};
+
+/// Create an interest tier or update its discount.
+/// The tier index is brought up to date with the previous discount first
+/// Executor: owner
+pub fn set_interest_tier(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tier_id: u64,
+    discount: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if discount > Decimal256::one() {
+        return Err(ContractError::InvalidDiscount {});
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    let messages = compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    store_state(deps.storage, &state)?;
+
+    let (interest_index, principal) = match read_interest_tier(deps.storage, tier_id) {
+        Ok(tier) => (
+            tier.interest_index_at(state.global_interest_index),
+            tier.principal,
+        ),
+        Err(_) => (Decimal256::one(), Decimal256::zero()),
+    };
+    store_interest_tier(
+        deps.storage,
+        tier_id,
+        &InterestTier {
+            discount,
+            interest_index,
+            global_interest_index: state.global_interest_index,
+            principal,
+        },
+    )?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "set_interest_tier"),
+        attr("tier_id", tier_id.to_string()),
+        attr("discount", discount.to_string()),
+    ]))
+}
+
+/// Move a borrower to an interest tier, or back to the default tier.
+/// The interest accrued so far is applied with the previous tier
+/// Executor: owner
+pub fn set_borrower_tier(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    tier_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    let mut state: State = read_state(deps.storage)?;
+    let messages = compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_tiered_borrower_interest(deps.storage, &state, &mut liability)?;
+    compute_borrower_reward(&state, &mut liability);
+
+    liability.tier = match tier_id {
+        Some(tier_id) => Some(BorrowerTier {
+            tier_id,
+            interest_index: read_interest_tier(deps.storage, tier_id)?
+                .interest_index_at(state.global_interest_index),
+        }),
+        None => None,
+    };
+
+    store_state(deps.storage, &state)?;
+    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "set_borrower_tier"),
+        attr("borrower", borrower),
+        attr(
+            "tier_id",
+            tier_id.map_or_else(|| "none".to_string(), |tier_id| tier_id.to_string()),
+        ),
+    ]))
+}
+
+pub fn query_interest_tiers(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<InterestTiersResponse> {
+    let tiers = read_interest_tiers(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(tier_id, tier)| InterestTierResponse {
+            tier_id,
+            discount: tier.discount,
+        })
+        .collect();
+
+    Ok(InterestTiersResponse { tiers })
+}
@@ -6,18 +6,22 @@ use cosmwasm_std::{
 use std::convert::TryInto;
 
 use crate::error::ContractError;
-use crate::querier::{query_borrower_info, query_liquidation_amount};
+use crate::markets::{
+    compute_market_borrow_limit, query_market_loans, query_stable_price, read_all_markets,
+    read_market, MarketLoan,
+};
+use crate::querier::query_liquidation_amount;
 use crate::state::{
     read_all_collaterals, read_collaterals, read_config, read_whitelist_elem, store_collaterals,
     Config, WhitelistElem,
 };
 
-use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
+use moneymarket::custody::{ExecuteMsg as CustodyExecuteMsg, LiquidationMarket};
 use moneymarket::liquidation_queue::LiquidationAmountResponse;
-use moneymarket::market::{BorrowerInfoResponse, ExecuteMsg as MarketExecuteMsg};
+use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 use moneymarket::oracle::PriceResponse;
 use moneymarket::overseer::{AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse};
-use moneymarket::querier::{query_balance, query_price, TimeConstraints};
+use moneymarket::querier::{query_all_token_types_balance, query_price, TimeConstraints};
 use moneymarket::tokens::{Tokens, TokensHuman, TokensMath, TokensToHuman, TokensToRaw};
 
 pub fn lock_collateral(
@@ -67,11 +71,21 @@ pub fn unlock_collateral(
     env: Env,
     info: MessageInfo,
     collaterals_human: TokensHuman,
+) -> Result<Response, ContractError> {
+    _unlock_collateral(deps, env, info.sender, collaterals_human, false)
+}
+
+/// Unlock the collaterals, the custody contracts also send them
+/// back to the borrower when `withdraw` is set
+pub(crate) fn _unlock_collateral(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
+    collaterals_human: TokensHuman,
+    withdraw: bool,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
-    let market = deps.api.addr_humanize(&config.market_contract)?;
 
-    let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
     let collaterals: Tokens = collaterals_human.to_raw(deps.as_ref())?;
@@ -87,9 +101,15 @@ pub fn unlock_collateral(
         &cur_collaterals,
         Some(env.block.time.seconds()),
     )?;
-    let borrow_amount_res: BorrowerInfoResponse =
-        query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
-    if borrow_limit < borrow_amount_res.loan_amount {
+    let loans: Vec<MarketLoan> = query_market_loans(
+        deps.as_ref(),
+        &config,
+        read_all_markets(deps.as_ref(), &config)?,
+        &borrower,
+        env.block.height,
+        Some(env.block.time.seconds()),
+    )?;
+    if borrow_limit < compute_loans_value(&loans) {
         return Err(ContractError::UnlockTooLarge(borrow_limit.try_into()?));
     }
 
@@ -104,10 +124,17 @@ pub fn unlock_collateral(
                 .addr_humanize(&whitelist_elem.custody_contract)?
                 .to_string(),
             funds: vec![],
-            msg: to_json_binary(&CustodyExecuteMsg::UnlockCollateral {
-                borrower: borrower.to_string(),
-                amount: collateral.1,
-            })?,
+            msg: if withdraw {
+                to_json_binary(&CustodyExecuteMsg::UnlockAndWithdrawCollateral {
+                    borrower: borrower.to_string(),
+                    amount: collateral.1,
+                })?
+            } else {
+                to_json_binary(&CustodyExecuteMsg::UnlockCollateral {
+                    borrower: borrower.to_string(),
+                    amount: collateral.1,
+                })?
+            },
         })));
     }
 
@@ -131,9 +158,11 @@ pub fn liquidate_collateral(
     env: Env,
     info: MessageInfo,
     borrower: Addr,
+    market: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
-    let market = deps.api.addr_humanize(&config.market_contract)?;
+    let primary_market = deps.api.addr_humanize(&config.market_contract)?;
+    let market_contract = market.unwrap_or_else(|| primary_market.clone());
 
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
@@ -144,21 +173,41 @@ pub fn liquidate_collateral(
         &cur_collaterals,
         Some(env.block.time.seconds()),
     )?;
-    let borrow_amount_res: BorrowerInfoResponse =
-        query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
-    let borrow_amount = borrow_amount_res.loan_amount;
+    let (mut loans, other_loans): (Vec<MarketLoan>, Vec<MarketLoan>) = query_market_loans(
+        deps.as_ref(),
+        &config,
+        read_all_markets(deps.as_ref(), &config)?,
+        &borrower,
+        env.block.height,
+        Some(env.block.time.seconds()),
+    )?
+    .into_iter()
+    .partition(|loan| loan.market_contract == market_contract);
+    let loan: MarketLoan = match loans.pop() {
+        Some(loan) => loan,
+        None => return Err(StdError::generic_err("Market is not registered").into()),
+    };
 
     // borrow limit is equal or bigger than loan amount
     // cannot liquidation collaterals
-    if borrow_limit >= borrow_amount {
+    if borrow_limit >= loan.value() + compute_loans_value(&other_loans) {
         return Err(ContractError::CannotLiquidateSafeLoan {});
     }
 
+    // The liquidation repays the loan in the given market,
+    // amounts and prices are expressed in its stable asset
+    let market_borrow_limit =
+        compute_market_borrow_limit(borrow_limit, &other_loans, loan.stable_price);
+    let collateral_prices: Vec<Decimal256> = collateral_prices
+        .into_iter()
+        .map(|price| price / loan.stable_price)
+        .collect();
+
     let liquidation_amount_res: LiquidationAmountResponse = query_liquidation_amount(
         deps.as_ref(),
-        deps.api.addr_humanize(&config.liquidation_contract)?,
-        borrow_amount,
-        borrow_limit,
+        deps.api.addr_humanize(&loan.market.liquidation_contract)?,
+        loan.loan_amount,
+        market_borrow_limit,
         &cur_collaterals.to_human(deps.as_ref())?,
         collateral_prices,
     )?;
@@ -169,9 +218,24 @@ pub fn liquidate_collateral(
     cur_collaterals.sub(liquidation_amount.clone())?;
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
 
-    let market_contract = deps.api.addr_humanize(&config.market_contract)?;
-    let prev_balance: Uint256 =
-        query_balance(deps.as_ref(), market_contract.clone(), config.stable_denom)?;
+    let prev_balance: Uint256 = query_all_token_types_balance(
+        deps.as_ref(),
+        market_contract.clone(),
+        loan.market.stable_asset.clone(),
+    )?;
+
+    // The custody contracts liquidate to the market of their config by default
+    let liquidation_market = if market_contract == primary_market {
+        None
+    } else {
+        Some(LiquidationMarket {
+            market_contract: market_contract.to_string(),
+            liquidation_contract: deps
+                .api
+                .addr_humanize(&loan.market.liquidation_contract)?
+                .to_string(),
+        })
+    };
 
     let liquidation_messages: Vec<CosmosMsg> = liquidation_amount
         .iter()
@@ -194,6 +258,7 @@ pub fn liquidate_collateral(
                     liquidator: info.sender.to_string(),
                     borrower: borrower.to_string(),
                     amount: collateral.1,
+                    market: liquidation_market.clone(),
                 })?,
             }))
         })
@@ -213,6 +278,7 @@ pub fn liquidate_collateral(
         .add_attributes(vec![
             attr("action", "liquidate_collateral"),
             attr("borrower", borrower),
+            attr("market", market_contract),
         ]))
 }
 
@@ -286,7 +352,9 @@ pub(crate) fn compute_borrow_limit(
 pub fn query_borrow_limit(
     deps: Deps,
     borrower: Addr,
+    block_height: u64,
     block_time: Option<u64>,
+    market: Option<Addr>,
 ) -> StdResult<BorrowLimitResponse> {
     let collaterals = read_collaterals(
         deps.storage,
@@ -294,10 +362,36 @@ pub fn query_borrow_limit(
     );
 
     // Compute borrow limit with collaterals
-    let (borrow_limit, _) = compute_borrow_limit(deps, &collaterals, block_time)?;
+    let (mut borrow_limit, _) = compute_borrow_limit(deps, &collaterals, block_time)?;
+
+    if let Some(market_contract) = market {
+        let config: Config = read_config(deps.storage)?;
+        let market = read_market(deps, &config, &market_contract)?;
+        let other_markets = read_all_markets(deps, &config)?
+            .into_iter()
+            .filter(|(other_market, _)| *other_market != market_contract)
+            .collect();
+        let other_loans = query_market_loans(
+            deps,
+            &config,
+            other_markets,
+            &borrower,
+            block_height,
+            block_time,
+        )?;
+        let stable_price = query_stable_price(deps, &config, &market.stable_asset, block_time)?;
+        borrow_limit = compute_market_borrow_limit(borrow_limit, &other_loans, stable_price);
+    }
 
     Ok(BorrowLimitResponse {
         borrower: borrower.to_string(),
         borrow_limit,
     })
 }
+
+/// Total value of the loans in the common unit
+pub(crate) fn compute_loans_value(loans: &[MarketLoan]) -> Uint256 {
+    loans
+        .iter()
+        .fold(Uint256::zero(), |acc, loan| acc + loan.value())
+}
@@ -2,8 +2,8 @@ use crate::state::PlatformFee;
 use crate::state::DEFAULT_LIMIT;
 use crate::state::MAX_LIMIT;
 use cosmwasm_std::{
-    attr, to_json_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal256, Deps, DepsMut, Env,
-    MessageInfo, Response, StdResult, Uint128, Uint256, WasmMsg,
+    attr, to_json_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Decimal256, Deps,
+    DepsMut, Env, MessageInfo, Response, StdResult, Uint128, Uint256, WasmMsg,
 };
 use cosmwasm_std::{entry_point, StdError};
 use moneymarket::overseer::DynrateState;
@@ -17,12 +17,20 @@ use crate::collateral::{
     query_collaterals, unlock_collateral,
 };
 use crate::error::ContractError;
-use crate::querier::query_epoch_state;
+use crate::leverage::{check_deleverage, deleverage, leverage, lock_leveraged};
+use crate::markets::{
+    query_markets, query_stable_price, read_all_markets, register_market, update_market,
+};
+use crate::querier::{
+    query_epoch_state, query_liquidation_fees_collected, query_market_fees_collected,
+};
+use crate::router::{deposit_and_borrow, lock_and_borrow, repay_and_withdraw, unlock_and_withdraw};
 
 use crate::state::{
-    read_config, read_dynrate_config, read_dynrate_state, read_epoch_state, read_whitelist,
-    read_whitelist_elem, store_config, store_dynrate_config, store_dynrate_state,
-    store_epoch_state, store_whitelist_elem, Config, DynrateConfig, WhitelistElem,
+    read_config, read_dynrate_config, read_dynrate_state, read_epoch_state,
+    read_platform_fees_collected, read_whitelist, read_whitelist_elem, store_config,
+    store_dynrate_config, store_dynrate_state, store_epoch_state, store_platform_fees_collected,
+    store_whitelist_elem, Config, DynrateConfig, WhitelistElem,
 };
 
 use moneymarket::common::optional_addr_validate;
@@ -30,8 +38,8 @@ use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
 use moneymarket::market::EpochStateResponse;
 use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 use moneymarket::overseer::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, WhitelistResponse,
-    WhitelistResponseElem,
+    ConfigResponse, ExecuteMsg, FeesCollectedResponse, InstantiateMsg, MigrateMsg, QueryMsg,
+    WhitelistResponse, WhitelistResponseElem,
 };
 use moneymarket::querier::query_balance;
 
@@ -189,6 +197,32 @@ pub fn execute(
                 max_ltv,
             )
         }
+        ExecuteMsg::RegisterMarket {
+            market_contract,
+            liquidation_contract,
+            stable_asset,
+        } => {
+            let api = deps.api;
+            register_market(
+                deps,
+                info,
+                api.addr_validate(&market_contract)?,
+                api.addr_validate(&liquidation_contract)?,
+                stable_asset,
+            )
+        }
+        ExecuteMsg::UpdateMarket {
+            market_contract,
+            liquidation_contract,
+        } => {
+            let api = deps.api;
+            update_market(
+                deps,
+                info,
+                api.addr_validate(&market_contract)?,
+                optional_addr_validate(api, liquidation_contract)?,
+            )
+        }
         ExecuteMsg::ExecuteEpochOperations {} => execute_epoch_operations(deps, env),
         ExecuteMsg::UpdateEpochState {
             interest_buffer,
@@ -198,9 +232,141 @@ pub fn execute(
         ExecuteMsg::UnlockCollateral { collaterals } => {
             unlock_collateral(deps, env, info, collaterals)
         }
-        ExecuteMsg::LiquidateCollateral { borrower } => {
+        ExecuteMsg::DepositAndBorrow {
+            collateral_token,
+            amount,
+            borrow_amount,
+            market,
+            to,
+        } => {
             let api = deps.api;
-            liquidate_collateral(deps, env, info, api.addr_validate(&borrower)?)
+            deposit_and_borrow(
+                deps,
+                env,
+                info,
+                api.addr_validate(&collateral_token)?,
+                amount,
+                borrow_amount,
+                optional_addr_validate(api, market)?,
+                optional_addr_validate(api, to)?,
+            )
+        }
+        ExecuteMsg::RepayAndWithdraw {
+            collaterals,
+            market,
+        } => {
+            let api = deps.api;
+            repay_and_withdraw(
+                deps,
+                env,
+                info,
+                collaterals,
+                optional_addr_validate(api, market)?,
+            )
+        }
+        ExecuteMsg::LockAndBorrow {
+            borrower,
+            collateral_token,
+            amount,
+            prev_balance,
+            borrow_amount,
+            market,
+            to,
+        } => {
+            let api = deps.api;
+            lock_and_borrow(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                api.addr_validate(&collateral_token)?,
+                amount,
+                prev_balance,
+                borrow_amount,
+                api.addr_validate(&market)?,
+                api.addr_validate(&to)?,
+            )
+        }
+        ExecuteMsg::UnlockAndWithdraw {
+            borrower,
+            collaterals,
+        } => {
+            let api = deps.api;
+            unlock_and_withdraw(deps, env, info, api.addr_validate(&borrower)?, collaterals)
+        }
+        ExecuteMsg::Leverage {
+            collateral_token,
+            target_ltv,
+            max_slippage,
+        } => {
+            let api = deps.api;
+            leverage(
+                deps,
+                env,
+                info,
+                api.addr_validate(&collateral_token)?,
+                target_ltv,
+                max_slippage,
+            )
+        }
+        ExecuteMsg::Deleverage {
+            collateral_token,
+            target_ltv,
+            max_slippage,
+        } => {
+            let api = deps.api;
+            deleverage(
+                deps,
+                env,
+                info,
+                api.addr_validate(&collateral_token)?,
+                target_ltv,
+                max_slippage,
+            )
+        }
+        ExecuteMsg::LockLeveraged {
+            borrower,
+            collateral_token,
+            target_ltv,
+            max_slippage,
+            prev_balance,
+            round,
+        } => {
+            let api = deps.api;
+            lock_leveraged(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                api.addr_validate(&collateral_token)?,
+                target_ltv,
+                max_slippage,
+                prev_balance,
+                round,
+            )
+        }
+        ExecuteMsg::CheckDeleverage {
+            borrower,
+            collateral_token,
+        } => {
+            let api = deps.api;
+            check_deleverage(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                api.addr_validate(&collateral_token)?,
+            )
+        }
+        ExecuteMsg::LiquidateCollateral { borrower, market } => {
+            let api = deps.api;
+            liquidate_collateral(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                optional_addr_validate(api, market)?,
+            )
         }
         ExecuteMsg::FundReserve {} => fund_reserve(deps, info),
     }
@@ -520,6 +686,9 @@ pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, Con
     // This might become variable in the future for more automatism on the platform
     let platform_fees: Uint256 = accrued_buffer * config.platform_fee.rate;
     if !platform_fees.is_zero() {
+        let platform_fees_collected = read_platform_fees_collected(deps.storage)? + platform_fees;
+        store_platform_fees_collected(deps.storage, &platform_fees_collected)?;
+
         messages.push(CosmosMsg::Bank(BankMsg::Send {
             to_address: config.platform_fee.receiver.to_string(),
             amount: vec![Coin {
@@ -745,7 +914,7 @@ pub fn fund_reserve(deps: DepsMut, info: MessageInfo) -> Result<Response, Contra
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
         QueryMsg::EpochState {} => to_json_binary(&query_state(deps)?),
@@ -771,12 +940,21 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::BorrowLimit {
             borrower,
             block_time,
+            market,
         } => to_json_binary(&query_borrow_limit(
             deps,
             deps.api.addr_validate(&borrower)?,
+            env.block.height,
             block_time,
+            optional_addr_validate(deps.api, market)?,
+        )?),
+        QueryMsg::Markets { start_after, limit } => to_json_binary(&query_markets(
+            deps,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
         )?),
         QueryMsg::DynrateState {} => to_json_binary(&query_dynrate_state(deps)?),
+        QueryMsg::FeesCollected {} => to_json_binary(&query_fees_collected(deps)?),
     }
 }
 
@@ -818,6 +996,40 @@ pub fn query_dynrate_state(deps: Deps) -> StdResult<DynrateState> {
     read_dynrate_state(deps.storage)
 }
 
+/// The origination and flash loan fees are kept by the markets, the bid fees
+/// by their liquidation contracts. The fees of each market are valued in the
+/// stable denom of the config
+pub fn query_fees_collected(deps: Deps) -> StdResult<FeesCollectedResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let mut fees_collected = FeesCollectedResponse {
+        origination: Uint256::zero(),
+        flash_loan: Uint256::zero(),
+        liquidation: Uint256::zero(),
+        platform: read_platform_fees_collected(deps.storage)?,
+    };
+
+    let mut liquidation_contracts: Vec<CanonicalAddr> = vec![];
+    for (market_contract, market) in read_all_markets(deps, &config)? {
+        let stable_price = query_stable_price(deps, &config, &market.stable_asset, None)?;
+        let market_fees = query_market_fees_collected(deps, market_contract)?;
+        fees_collected.origination += market_fees.origination * stable_price;
+        fees_collected.flash_loan += market_fees.flash_loan * stable_price;
+
+        // markets of the same stable asset can share a liquidation contract
+        if liquidation_contracts.contains(&market.liquidation_contract) {
+            continue;
+        }
+        let liquidation_fees = query_liquidation_fees_collected(
+            deps,
+            deps.api.addr_humanize(&market.liquidation_contract)?,
+        )?;
+        fees_collected.liquidation += liquidation_fees.bid_fee * stable_price;
+        liquidation_contracts.push(market.liquidation_contract);
+    }
+
+    Ok(fees_collected)
+}
+
 pub fn query_whitelist(
     deps: Deps,
     collateral_token: Option<Addr>,
@@ -38,4 +38,22 @@ pub enum ContractError {
 
     #[error("Too much collaterals were already registered")]
     TooMuchCollaterals {},
+
+    #[error("Market is already registered")]
+    MarketAlreadyRegistered {},
+
+    #[error("Collateral was not deposited to the custody contract")]
+    CollateralNotDeposited {},
+
+    #[error("Target LTV should leave the leverage safety margin below the max LTV")]
+    InvalidTargetLTV {},
+
+    #[error("Target LTV is already reached")]
+    TargetLTVAlreadyReached {},
+
+    #[error("Leverage brings the loan liability above the safe borrow limit")]
+    UnsafeLeverage {},
+
+    #[error("Deleverage leaves the loan liability above the borrow limit")]
+    UnsafeDeleverage {},
 }
@@ -0,0 +1,404 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, CanonicalAddr, CosmosMsg, Decimal256, Deps, DepsMut, Env,
+    MessageInfo, Response, StdResult, Uint256, WasmMsg,
+};
+
+use crate::collateral::{compute_borrow_limit, compute_loans_value, lock_collateral};
+use crate::error::ContractError;
+use crate::markets::{query_market_loans, read_all_markets};
+use crate::querier::query_custody_borrower;
+use crate::state::{
+    read_collaterals, read_config, read_whitelist_elem, store_collaterals, Config, WhitelistElem,
+};
+
+use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
+use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
+use moneymarket::oracle::PriceResponse;
+use moneymarket::overseer::ExecuteMsg;
+use moneymarket::querier::{query_price, TimeConstraints};
+use moneymarket::tokens::{Tokens, TokensMath};
+
+/// Bounds the borrow-swap-lock rounds of a leverage
+const MAX_LEVERAGE_ROUNDS: u32 = 10;
+
+/// A leveraged position stays this far below the max LTV of its collaterals
+const LEVERAGE_SAFETY_MARGIN: Decimal256 = Decimal256::percent(5);
+
+/// Position of a borrower in the common unit
+struct Position {
+    collaterals: Tokens,
+    collaterals_value: Uint256,
+    borrow_limit: Uint256,
+    /// Borrow limit with the safety margin taken off the max LTVs
+    safe_borrow_limit: Uint256,
+    loans_value: Uint256,
+    /// Price of the leveraged collateral token
+    price: Decimal256,
+}
+
+fn query_position(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    borrower: &Addr,
+    collateral_token: &CanonicalAddr,
+) -> StdResult<Position> {
+    let collaterals: Tokens = read_collaterals(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    );
+    let (borrow_limit, collateral_prices) =
+        compute_borrow_limit(deps, &collaterals, Some(env.block.time.seconds()))?;
+
+    let mut collaterals_value = Uint256::zero();
+    let mut safe_borrow_limit = Uint256::zero();
+    for (collateral, price) in collaterals.iter().zip(collateral_prices) {
+        let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        let collateral_value = collateral.1 * price;
+        collaterals_value += collateral_value;
+        if elem.max_ltv > LEVERAGE_SAFETY_MARGIN {
+            safe_borrow_limit += collateral_value * (elem.max_ltv - LEVERAGE_SAFETY_MARGIN);
+        }
+    }
+
+    let loans = query_market_loans(
+        deps,
+        config,
+        read_all_markets(deps, config)?,
+        borrower,
+        env.block.height,
+        Some(env.block.time.seconds()),
+    )?;
+
+    let price: PriceResponse = query_price(
+        deps,
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        deps.api.addr_humanize(collateral_token)?.to_string(),
+        config.stable_denom.to_string(),
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        }),
+    )?;
+
+    Ok(Position {
+        collaterals,
+        collaterals_value,
+        borrow_limit,
+        safe_borrow_limit,
+        loans_value: compute_loans_value(&loans),
+        price: price.rate,
+    })
+}
+
+/// Borrow, swap the stable to the collateral token and deposit it,
+/// then lock it in `LockLeveraged` which starts the next round
+/// Executor: borrower
+pub fn leverage(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: Addr,
+    target_ltv: Decimal256,
+    max_slippage: Decimal256,
+) -> Result<Response, ContractError> {
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    if max_slippage >= Decimal256::one()
+        || target_ltv + LEVERAGE_SAFETY_MARGIN > whitelist_elem.max_ltv
+    {
+        return Err(ContractError::InvalidTargetLTV {});
+    }
+
+    let borrower = info.sender;
+    let prev_balance = query_custody_borrower(
+        deps.as_ref(),
+        deps.api.addr_humanize(&whitelist_elem.custody_contract)?,
+        borrower.clone(),
+    )?
+    .balance;
+
+    let messages = leverage_round(
+        deps.as_ref(),
+        &env,
+        &borrower,
+        &collateral_token,
+        target_ltv,
+        max_slippage,
+        prev_balance,
+        0,
+    )?;
+    if messages.is_empty() {
+        return Err(ContractError::TargetLTVAlreadyReached {});
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "leverage"),
+        attr("borrower", borrower),
+        attr("collateral_token", collateral_token),
+        attr("target_ltv", target_ltv.to_string()),
+    ]))
+}
+
+/// Messages of a leverage round, none once the target LTV is reached.
+/// The borrowed amount is bounded by the safe borrow limit,
+/// as the bought collateral is only locked at the end of the round
+#[allow(clippy::too_many_arguments)]
+fn leverage_round(
+    deps: Deps,
+    env: &Env,
+    borrower: &Addr,
+    collateral_token: &Addr,
+    target_ltv: Decimal256,
+    max_slippage: Decimal256,
+    prev_balance: Uint256,
+    round: u32,
+) -> StdResult<Vec<CosmosMsg>> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let position = query_position(deps, env, &config, borrower, &collateral_token_raw)?;
+
+    // (loans + borrow) = target_ltv * (collaterals + borrow * (1 - max_slippage))
+    let target_loans_value = position.collaterals_value * target_ltv;
+    if target_loans_value <= position.loans_value {
+        return Ok(vec![]);
+    }
+    let swap_ratio = Decimal256::one() - max_slippage;
+    let borrow_amount = Decimal256::from_ratio(target_loans_value - position.loans_value, 1u128)
+        / (Decimal256::one() - target_ltv * swap_ratio)
+        * Uint256::one();
+
+    let safe_borrow_amount = if position.safe_borrow_limit > position.loans_value {
+        position.safe_borrow_limit - position.loans_value
+    } else {
+        Uint256::zero()
+    };
+    let borrow_amount = std::cmp::min(borrow_amount, safe_borrow_amount);
+    if borrow_amount.is_zero() {
+        return Ok(vec![]);
+    }
+
+    let minimum_receive =
+        Decimal256::from_ratio(borrow_amount * swap_ratio, 1u128) / position.price * Uint256::one();
+
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    let custody_contract = deps.api.addr_humanize(&whitelist_elem.custody_contract)?;
+    Ok(vec![
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.market_contract)?.to_string(),
+            funds: vec![],
+            msg: to_json_binary(&MarketExecuteMsg::BorrowStableFromOverseer {
+                borrower: borrower.to_string(),
+                borrow_amount,
+                to: Some(custody_contract.to_string()),
+            })?,
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: custody_contract.to_string(),
+            funds: vec![],
+            msg: to_json_binary(&CustodyExecuteMsg::BuyCollateral {
+                borrower: borrower.to_string(),
+                amount: borrow_amount,
+                minimum_receive,
+            })?,
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_json_binary(&ExecuteMsg::LockLeveraged {
+                borrower: borrower.to_string(),
+                collateral_token: collateral_token.to_string(),
+                target_ltv,
+                max_slippage,
+                prev_balance,
+                round,
+            })?,
+        }),
+    ])
+}
+
+/// Lock the collateral bought in the round and start the next one
+/// Executor: overseer
+#[allow(clippy::too_many_arguments)]
+pub fn lock_leveraged(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    collateral_token: Addr,
+    target_ltv: Decimal256,
+    max_slippage: Decimal256,
+    prev_balance: Uint256,
+    round: u32,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    let cur_balance = query_custody_borrower(
+        deps.as_ref(),
+        deps.api.addr_humanize(&whitelist_elem.custody_contract)?,
+        borrower.clone(),
+    )?
+    .balance;
+
+    let res = lock_collateral(
+        deps.branch(),
+        MessageInfo {
+            sender: borrower.clone(),
+            funds: vec![],
+        },
+        vec![(collateral_token.to_string(), cur_balance - prev_balance)],
+    )?;
+
+    let position = query_position(
+        deps.as_ref(),
+        &env,
+        &config,
+        &borrower,
+        &collateral_token_raw,
+    )?;
+    if position.loans_value > position.safe_borrow_limit {
+        return Err(ContractError::UnsafeLeverage {});
+    }
+
+    if round + 1 >= MAX_LEVERAGE_ROUNDS {
+        return Ok(res);
+    }
+
+    let messages = leverage_round(
+        deps.as_ref(),
+        &env,
+        &borrower,
+        &collateral_token,
+        target_ltv,
+        max_slippage,
+        cur_balance,
+        round + 1,
+    )?;
+    Ok(res.add_messages(messages))
+}
+
+/// Sell locked collateral so that the repaid loan
+/// brings the LTV of the borrower down to `target_ltv`
+/// Executor: borrower
+pub fn deleverage(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: Addr,
+    target_ltv: Decimal256,
+    max_slippage: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    if max_slippage >= Decimal256::one() || target_ltv + max_slippage >= Decimal256::one() {
+        return Err(ContractError::InvalidTargetLTV {});
+    }
+
+    let borrower = info.sender;
+    let mut position = query_position(
+        deps.as_ref(),
+        &env,
+        &config,
+        &borrower,
+        &collateral_token_raw,
+    )?;
+
+    // (loans - sold * (1 - max_slippage)) = target_ltv * (collaterals - sold)
+    let target_loans_value = position.collaterals_value * target_ltv;
+    if position.loans_value <= target_loans_value {
+        return Err(ContractError::TargetLTVAlreadyReached {});
+    }
+    let swap_ratio = Decimal256::one() - max_slippage;
+    let sell_value = Decimal256::from_ratio(position.loans_value - target_loans_value, 1u128)
+        / (swap_ratio - target_ltv);
+
+    let locked_amount = position
+        .collaterals
+        .iter()
+        .find(|c| c.0 == collateral_token_raw)
+        .map(|c| c.1)
+        .unwrap_or_default();
+    let sell_amount = std::cmp::min(sell_value / position.price * Uint256::one(), locked_amount);
+    if sell_amount.is_zero() {
+        return Err(ContractError::TargetLTVAlreadyReached {});
+    }
+
+    position
+        .collaterals
+        .sub(vec![(collateral_token_raw, sell_amount)])?;
+    store_collaterals(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+        &position.collaterals,
+    )?;
+
+    // the custody repays the loan with the sale before the check
+    let minimum_receive = sell_amount * position.price * swap_ratio;
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps
+                    .api
+                    .addr_humanize(&whitelist_elem.custody_contract)?
+                    .to_string(),
+                funds: vec![],
+                msg: to_json_binary(&CustodyExecuteMsg::SellCollateral {
+                    borrower: borrower.to_string(),
+                    amount: sell_amount,
+                    minimum_receive,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_json_binary(&ExecuteMsg::CheckDeleverage {
+                    borrower: borrower.to_string(),
+                    collateral_token: collateral_token.to_string(),
+                })?,
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "deleverage"),
+            attr("borrower", borrower),
+            attr("collateral_token", collateral_token),
+            attr("sell_amount", sell_amount),
+        ]))
+}
+
+/// The sold collateral is unlocked before the sale,
+/// the repaid loan must fit the borrow limit of the collaterals left
+/// Executor: overseer
+pub fn check_deleverage(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    collateral_token: Addr,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let position = query_position(
+        deps.as_ref(),
+        &env,
+        &config,
+        &borrower,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+    if position.loans_value > position.borrow_limit {
+        return Err(ContractError::UnsafeDeleverage {});
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "check_deleverage"),
+        attr("borrower", borrower),
+    ]))
+}
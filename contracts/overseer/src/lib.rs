@@ -1,7 +1,10 @@
 pub mod collateral;
 pub mod contract;
 pub mod error;
+pub mod leverage;
+pub mod markets;
 pub mod querier;
+pub mod router;
 pub mod state;
 
 #[cfg(test)]
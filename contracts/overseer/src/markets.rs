@@ -0,0 +1,217 @@
+use cosmwasm_std::{
+    attr, Addr, Decimal256, Deps, DepsMut, MessageInfo, Response, StdResult, Uint256,
+};
+
+use crate::error::ContractError;
+use crate::querier::query_borrower_info;
+use crate::state::{
+    read_config, read_market_elem, read_market_elems, read_markets, store_market_elem, Config,
+    MarketElem,
+};
+
+use moneymarket::astroport_router::AssetInfo;
+use moneymarket::market::BorrowerInfoResponse;
+use moneymarket::overseer::{MarketResponseElem, MarketsResponse};
+use moneymarket::querier::{query_price, TimeConstraints};
+
+/// Loan of a borrower in one of the markets
+pub(crate) struct MarketLoan {
+    pub market_contract: Addr,
+    pub market: MarketElem,
+    /// Price of the stable asset of the market in the common unit
+    pub stable_price: Decimal256,
+    pub loan_amount: Uint256,
+}
+
+impl MarketLoan {
+    /// Loan amount in the common unit
+    pub fn value(&self) -> Uint256 {
+        self.loan_amount * self.stable_price
+    }
+}
+
+pub fn register_market(
+    deps: DepsMut,
+    info: MessageInfo,
+    market_contract: Addr,
+    liquidation_contract: Addr,
+    stable_asset: AssetInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let market_contract_raw = deps.api.addr_canonicalize(market_contract.as_str())?;
+    if market_contract_raw == config.market_contract
+        || read_market_elem(deps.storage, &market_contract_raw).is_ok()
+    {
+        return Err(ContractError::MarketAlreadyRegistered {});
+    }
+
+    store_market_elem(
+        deps.storage,
+        &market_contract_raw,
+        &MarketElem {
+            stable_asset,
+            liquidation_contract: deps.api.addr_canonicalize(liquidation_contract.as_str())?,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_market"),
+        attr("market_contract", market_contract),
+        attr("liquidation_contract", liquidation_contract),
+    ]))
+}
+
+pub fn update_market(
+    deps: DepsMut,
+    info: MessageInfo,
+    market_contract: Addr,
+    liquidation_contract: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let market_contract_raw = deps.api.addr_canonicalize(market_contract.as_str())?;
+    let mut market_elem: MarketElem = read_market_elem(deps.storage, &market_contract_raw)?;
+
+    if let Some(liquidation_contract) = liquidation_contract {
+        market_elem.liquidation_contract =
+            deps.api.addr_canonicalize(liquidation_contract.as_str())?;
+    }
+
+    store_market_elem(deps.storage, &market_contract_raw, &market_elem)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_market"),
+        attr("market_contract", market_contract),
+        attr(
+            "liquidation_contract",
+            deps.api.addr_humanize(&market_elem.liquidation_contract)?,
+        ),
+    ]))
+}
+
+pub fn query_markets(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<MarketsResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    let elems: Vec<MarketResponseElem> = read_markets(deps, start_after, limit)?;
+    Ok(MarketsResponse { elems })
+}
+
+/// The market of the config lends the stable denom of the config
+pub(crate) fn read_market(
+    deps: Deps,
+    config: &Config,
+    market_contract: &Addr,
+) -> StdResult<MarketElem> {
+    let market_contract_raw = deps.api.addr_canonicalize(market_contract.as_str())?;
+    if market_contract_raw == config.market_contract {
+        Ok(MarketElem {
+            stable_asset: AssetInfo::NativeToken {
+                denom: config.stable_denom.clone(),
+            },
+            liquidation_contract: config.liquidation_contract.clone(),
+        })
+    } else {
+        read_market_elem(deps.storage, &market_contract_raw)
+    }
+}
+
+pub(crate) fn read_all_markets(deps: Deps, config: &Config) -> StdResult<Vec<(Addr, MarketElem)>> {
+    let market_contract = deps.api.addr_humanize(&config.market_contract)?;
+    let mut markets = vec![(
+        market_contract.clone(),
+        read_market(deps, config, &market_contract)?,
+    )];
+    for (market_contract, market_elem) in read_market_elems(deps.storage)? {
+        markets.push((deps.api.addr_humanize(&market_contract)?, market_elem));
+    }
+
+    Ok(markets)
+}
+
+/// Price of a stable asset in the common unit, the stable denom of the config
+pub(crate) fn query_stable_price(
+    deps: Deps,
+    config: &Config,
+    stable_asset: &AssetInfo,
+    block_time: Option<u64>,
+) -> StdResult<Decimal256> {
+    let stable_asset = match stable_asset {
+        AssetInfo::NativeToken { denom } => denom.to_string(),
+        AssetInfo::Token { contract_addr } => contract_addr.to_string(),
+    };
+    if stable_asset == config.stable_denom {
+        return Ok(Decimal256::one());
+    }
+
+    Ok(query_price(
+        deps,
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        stable_asset,
+        config.stable_denom.to_string(),
+        block_time.map(|block_time| TimeConstraints {
+            block_time,
+            valid_timeframe: config.price_timeframe,
+        }),
+    )?
+    .rate)
+}
+
+/// Floating and fixed rate loans of the borrower in each of the markets
+pub(crate) fn query_market_loans(
+    deps: Deps,
+    config: &Config,
+    markets: Vec<(Addr, MarketElem)>,
+    borrower: &Addr,
+    block_height: u64,
+    block_time: Option<u64>,
+) -> StdResult<Vec<MarketLoan>> {
+    markets
+        .into_iter()
+        .map(|(market_contract, market)| {
+            let borrower_info: BorrowerInfoResponse = query_borrower_info(
+                deps,
+                market_contract.clone(),
+                borrower.clone(),
+                block_height,
+            )?;
+            Ok(MarketLoan {
+                stable_price: query_stable_price(deps, config, &market.stable_asset, block_time)?,
+                market_contract,
+                market,
+                loan_amount: borrower_info.loan_amount + borrower_info.fixed_loan_amount,
+            })
+        })
+        .collect()
+}
+
+/// Borrow limit in the stable asset of a market.
+/// The loans in the other markets are deducted from the borrow limit in the common unit
+pub(crate) fn compute_market_borrow_limit(
+    borrow_limit: Uint256,
+    other_loans: &[MarketLoan],
+    stable_price: Decimal256,
+) -> Uint256 {
+    let other_loans_value = other_loans
+        .iter()
+        .fold(Uint256::zero(), |acc, loan| acc + loan.value());
+    if borrow_limit <= other_loans_value {
+        return Uint256::zero();
+    }
+
+    Decimal256::from_ratio(borrow_limit - other_loans_value, 1u128) / stable_price * Uint256::one()
+}
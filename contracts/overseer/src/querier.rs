@@ -2,9 +2,14 @@ use cosmwasm_std::{
     to_json_binary, Addr, Decimal256, Deps, QueryRequest, StdResult, Uint256, WasmQuery,
 };
 
-use moneymarket::liquidation_queue::{LiquidationAmountResponse, QueryMsg as LiquidationQueryMsg};
+use moneymarket::custody::{BorrowerResponse, QueryMsg as CustodyQueryMsg};
+use moneymarket::liquidation_queue::{
+    FeesCollectedResponse as LiquidationFeesCollectedResponse, LiquidationAmountResponse,
+    QueryMsg as LiquidationQueryMsg,
+};
 use moneymarket::market::{
-    BorrowerInfoResponse, EpochStateResponse, QueryMsg as MarketQueryMsg, StateResponse,
+    BorrowerInfoResponse, EpochStateResponse, FeesCollectedResponse as MarketFeesCollectedResponse,
+    QueryMsg as MarketQueryMsg, StateResponse,
 };
 use moneymarket::tokens::TokensHuman;
 
@@ -60,6 +65,23 @@ pub fn query_borrower_info(
     Ok(borrower_amount)
 }
 
+/// Query the collateral balance of a borrower from a custody contract
+pub fn query_custody_borrower(
+    deps: Deps,
+    custody_addr: Addr,
+    borrower: Addr,
+) -> StdResult<BorrowerResponse> {
+    let borrower_res: BorrowerResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: custody_addr.to_string(),
+            msg: to_json_binary(&CustodyQueryMsg::Borrower {
+                address: borrower.to_string(),
+            })?,
+        }))?;
+
+    Ok(borrower_res)
+}
+
 #[allow(clippy::ptr_arg)]
 pub fn query_liquidation_amount(
     deps: Deps,
@@ -82,3 +104,23 @@ pub fn query_liquidation_amount(
 
     Ok(liquidation_amount_res)
 }
+
+pub fn query_market_fees_collected(
+    deps: Deps,
+    market_addr: Addr,
+) -> StdResult<MarketFeesCollectedResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: market_addr.to_string(),
+        msg: to_json_binary(&MarketQueryMsg::FeesCollected {})?,
+    }))
+}
+
+pub fn query_liquidation_fees_collected(
+    deps: Deps,
+    liquidation_contract: Addr,
+) -> StdResult<LiquidationFeesCollectedResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: liquidation_contract.to_string(),
+        msg: to_json_binary(&LiquidationQueryMsg::FeesCollected {})?,
+    }))
+}
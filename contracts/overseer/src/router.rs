@@ -0,0 +1,199 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, CosmosMsg, DepsMut, Env, MessageInfo, Response, Uint256, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use std::convert::TryInto;
+
+use crate::collateral::{_unlock_collateral, lock_collateral};
+use crate::error::ContractError;
+use crate::markets::read_market;
+use crate::querier::query_custody_borrower;
+use crate::state::{read_config, read_whitelist_elem, Config, WhitelistElem};
+
+use moneymarket::custody::Cw20HookMsg as CustodyCw20HookMsg;
+use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
+use moneymarket::overseer::ExecuteMsg;
+use moneymarket::tokens::TokensHuman;
+
+/// Deposit the collateral to the custody contract with the allowance
+/// given to the overseer, then lock it and borrow in `LockAndBorrow`
+/// Executor: borrower
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_and_borrow(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: Addr,
+    amount: Uint256,
+    borrow_amount: Uint256,
+    market: Option<Addr>,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let market = match market {
+        Some(market) => market,
+        None => deps.api.addr_humanize(&config.market_contract)?,
+    };
+    read_market(deps.as_ref(), &config, &market)?;
+
+    let borrower = info.sender;
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+    let custody_contract = deps.api.addr_humanize(&whitelist_elem.custody_contract)?;
+    let prev_balance =
+        query_custody_borrower(deps.as_ref(), custody_contract.clone(), borrower.clone())?.balance;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: collateral_token.to_string(),
+                funds: vec![],
+                msg: to_json_binary(&Cw20ExecuteMsg::SendFrom {
+                    owner: borrower.to_string(),
+                    contract: custody_contract.to_string(),
+                    amount: amount.try_into()?,
+                    msg: to_json_binary(&CustodyCw20HookMsg::DepositCollateral {
+                        borrower: Some(borrower.to_string()),
+                    })?,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_json_binary(&ExecuteMsg::LockAndBorrow {
+                    borrower: borrower.to_string(),
+                    collateral_token: collateral_token.to_string(),
+                    amount,
+                    prev_balance,
+                    borrow_amount,
+                    market: market.to_string(),
+                    to: to.unwrap_or_else(|| borrower.clone()).to_string(),
+                })?,
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "deposit_and_borrow"),
+            attr("borrower", borrower),
+            attr("collateral_token", collateral_token),
+            attr("amount", amount),
+            attr("borrow_amount", borrow_amount),
+        ]))
+}
+
+/// Lock the collateral deposited by `DepositAndBorrow`
+/// once the custody balance is checked, and borrow against it
+/// Executor: overseer
+#[allow(clippy::too_many_arguments)]
+pub fn lock_and_borrow(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    collateral_token: Addr,
+    amount: Uint256,
+    prev_balance: Uint256,
+    borrow_amount: Uint256,
+    market: Addr,
+    to: Addr,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+    let custody_contract = deps.api.addr_humanize(&whitelist_elem.custody_contract)?;
+    let cur_balance =
+        query_custody_borrower(deps.as_ref(), custody_contract, borrower.clone())?.balance;
+    if cur_balance < prev_balance + amount {
+        return Err(ContractError::CollateralNotDeposited {});
+    }
+
+    let res = lock_collateral(
+        deps,
+        MessageInfo {
+            sender: borrower.clone(),
+            funds: vec![],
+        },
+        vec![(collateral_token.to_string(), amount)],
+    )?;
+
+    if borrow_amount.is_zero() {
+        return Ok(res);
+    }
+
+    Ok(res.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: market.to_string(),
+        funds: vec![],
+        msg: to_json_binary(&MarketExecuteMsg::BorrowStableFromOverseer {
+            borrower: borrower.to_string(),
+            borrow_amount,
+            to: Some(to.to_string()),
+        })?,
+    })))
+}
+
+/// Repay the loan in the market with the sent stable,
+/// then unlock and withdraw the collaterals in `UnlockAndWithdraw`
+/// Executor: borrower
+pub fn repay_and_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collaterals: TokensHuman,
+    market: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let market = match market {
+        Some(market) => market,
+        None => deps.api.addr_humanize(&config.market_contract)?,
+    };
+    read_market(deps.as_ref(), &config, &market)?;
+
+    let borrower = info.sender;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !info.funds.is_empty() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: market.to_string(),
+            funds: info.funds,
+            msg: to_json_binary(&MarketExecuteMsg::RepayStable {
+                borrower: Some(borrower.to_string()),
+            })?,
+        }));
+    }
+
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_json_binary(&ExecuteMsg::UnlockAndWithdraw {
+            borrower: borrower.to_string(),
+            collaterals,
+        })?,
+    }));
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "repay_and_withdraw"),
+        attr("borrower", borrower),
+        attr("market", market),
+    ]))
+}
+
+/// Unlock and withdraw the collaterals once the loan is repaid
+/// Executor: overseer
+pub fn unlock_and_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    collaterals: TokensHuman,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    _unlock_collateral(deps, env, borrower, collaterals, true)
+}
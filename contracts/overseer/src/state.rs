@@ -6,16 +6,21 @@ use serde::{Deserialize, Serialize};
 use cosmwasm_std::{CanonicalAddr, Decimal256, Deps, Order, StdError, StdResult, Storage, Uint256};
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 
-use moneymarket::overseer::{CollateralsResponse, DynrateState, EpochState, WhitelistResponseElem};
+use moneymarket::astroport_router::AssetInfo;
+use moneymarket::overseer::{
+    CollateralsResponse, DynrateState, EpochState, MarketResponseElem, WhitelistResponseElem,
+};
 use moneymarket::tokens::Tokens;
 
 const KEY_CONFIG: &[u8] = b"config";
 const KEY_DYNRATE_CONFIG: &[u8] = b"dynrate_config";
 const KEY_EPOCH_STATE: &[u8] = b"epoch_state";
 const KEY_DYNRATE_STATE: &[u8] = b"dynrate_state";
+const KEY_PLATFORM_FEES_COLLECTED: &[u8] = b"platform_fees_collected";
 
 const PREFIX_WHITELIST: &[u8] = b"whitelist";
 const PREFIX_COLLATERALS: &[u8] = b"collateral";
+const PREFIX_MARKET: &[u8] = b"market";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct OldConfig {
@@ -73,6 +78,12 @@ pub struct WhitelistElem {
     pub custody_contract: CanonicalAddr,
 }
 
+#[cw_serde]
+pub struct MarketElem {
+    pub stable_asset: AssetInfo,
+    pub liquidation_contract: CanonicalAddr,
+}
+
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
     Singleton::new(storage, KEY_CONFIG).save(data)
 }
@@ -109,6 +120,19 @@ pub fn read_dynrate_state(storage: &dyn Storage) -> StdResult<DynrateState> {
     ReadonlySingleton::new(storage, KEY_DYNRATE_STATE).load()
 }
 
+#[allow(deprecated)]
+pub fn store_platform_fees_collected(storage: &mut dyn Storage, data: &Uint256) -> StdResult<()> {
+    Singleton::new(storage, KEY_PLATFORM_FEES_COLLECTED).save(data)
+}
+
+/// Platform fees collected since the ledger was introduced
+#[allow(deprecated)]
+pub fn read_platform_fees_collected(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(ReadonlySingleton::new(storage, KEY_PLATFORM_FEES_COLLECTED)
+        .may_load()?
+        .unwrap_or_default())
+}
+
 pub fn store_whitelist_elem(
     storage: &mut dyn Storage,
     collateral_token: &CanonicalAddr,
@@ -163,6 +187,68 @@ pub fn read_whitelist(
         .collect()
 }
 
+#[allow(deprecated)]
+pub fn store_market_elem(
+    storage: &mut dyn Storage,
+    market_contract: &CanonicalAddr,
+    market_elem: &MarketElem,
+) -> StdResult<()> {
+    let mut market_bucket: Bucket<MarketElem> = Bucket::new(storage, PREFIX_MARKET);
+    market_bucket.save(market_contract.as_slice(), market_elem)?;
+
+    Ok(())
+}
+
+#[allow(deprecated)]
+pub fn read_market_elem(
+    storage: &dyn Storage,
+    market_contract: &CanonicalAddr,
+) -> StdResult<MarketElem> {
+    let market_bucket: ReadonlyBucket<MarketElem> = ReadonlyBucket::new(storage, PREFIX_MARKET);
+    match market_bucket.load(market_contract.as_slice()) {
+        Ok(v) => Ok(v),
+        _ => Err(StdError::generic_err("Market is not registered")),
+    }
+}
+
+#[allow(deprecated)]
+pub fn read_market_elems(storage: &dyn Storage) -> StdResult<Vec<(CanonicalAddr, MarketElem)>> {
+    let market_bucket: ReadonlyBucket<MarketElem> = ReadonlyBucket::new(storage, PREFIX_MARKET);
+    market_bucket
+        .range(None, None, Order::Ascending)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
+#[allow(deprecated)]
+pub fn read_markets(
+    deps: Deps,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<MarketResponseElem>> {
+    let market_bucket: ReadonlyBucket<MarketElem> =
+        ReadonlyBucket::new(deps.storage, PREFIX_MARKET);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    market_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok(MarketResponseElem {
+                market_contract: deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string(),
+                liquidation_contract: deps.api.addr_humanize(&v.liquidation_contract)?.to_string(),
+                stable_asset: v.stable_asset,
+            })
+        })
+        .collect()
+}
+
 #[allow(clippy::ptr_arg)]
 pub fn store_collaterals(
     storage: &mut dyn Storage,
@@ -10,6 +10,7 @@ use cosmwasm_std::{
 };
 use std::collections::HashMap;
 
+use moneymarket::custody::BorrowerResponse;
 use moneymarket::liquidation_queue::LiquidationAmountResponse;
 use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse, StateResponse};
 use moneymarket::oracle::PriceResponse;
@@ -41,6 +42,18 @@ pub enum QueryMsg {
         collaterals: TokensHuman,
         collateral_prices: Vec<Decimal256>,
     },
+    /// Query collateral balance to custody contract
+    Borrower { address: String },
+    /// Query fees collected to market and liquidation contracts
+    FeesCollected {},
+}
+
+/// Fees collected response of both the market and the liquidation contracts
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeesCollectedResponse {
+    pub origination: Uint256,
+    pub flash_loan: Uint256,
+    pub bid_fee: Uint256,
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -66,6 +79,8 @@ pub struct WasmMockQuerier {
     loan_amount_querier: LoanAmountQuerier,
     liquidation_percent_querier: LiquidationPercentQuerier,
     liquidation_bids_querier: LiquidationBidsQuerier,
+    custody_balance_querier: LoanAmountQuerier,
+    fees_collected_querier: LoanAmountQuerier,
 }
 
 #[derive(Clone, Default)]
@@ -234,6 +249,8 @@ impl WasmMockQuerier {
                                     prev_aterra_supply: Uint256::zero(),
                                     prev_exchange_rate: Decimal256::zero(),
                                     prev_borrower_incentives: Uint256::zero(),
+                                    reserved_borrower_rewards: Uint256::zero(),
+                                    global_claimable_reward_index: Decimal256::zero(),
                                 })))
                             }
                             None => SystemResult::Err(SystemError::InvalidRequest {
@@ -271,6 +288,10 @@ impl WasmMockQuerier {
                                 reward_index: Decimal256::zero(),
                                 loan_amount: *v,
                                 pending_rewards: Decimal256::zero(),
+                                claimable_rewards: Decimal256::zero(),
+                                fixed_loan_amount: Uint256::zero(),
+                                interest_tier: None,
+                                borrow_rate: Decimal256::zero(),
                             },
                         ))),
                         None => SystemResult::Err(SystemError::InvalidRequest {
@@ -344,6 +365,34 @@ impl WasmMockQuerier {
                             }),
                         }
                     }
+                    QueryMsg::Borrower { address } => {
+                        let balance = self
+                            .custody_balance_querier
+                            .borrower_amount
+                            .get(&address)
+                            .cloned()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::from(to_json_binary(&BorrowerResponse {
+                            borrower: address,
+                            balance,
+                            spendable: balance,
+                        })))
+                    }
+                    QueryMsg::FeesCollected {} => {
+                        let fees = self
+                            .fees_collected_querier
+                            .borrower_amount
+                            .get(contract_addr)
+                            .cloned()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::from(to_json_binary(
+                            &FeesCollectedResponse {
+                                origination: fees,
+                                flash_loan: fees,
+                                bid_fee: fees,
+                            },
+                        )))
+                    }
                 }
             }
             _ => self.base.handle_query(request),
@@ -360,6 +409,8 @@ impl WasmMockQuerier {
             loan_amount_querier: LoanAmountQuerier::default(),
             liquidation_percent_querier: LiquidationPercentQuerier::default(),
             liquidation_bids_querier: LiquidationBidsQuerier::default(),
+            custody_balance_querier: LoanAmountQuerier::default(),
+            fees_collected_querier: LoanAmountQuerier::default(),
         }
     }
 
@@ -385,6 +436,14 @@ impl WasmMockQuerier {
         self.loan_amount_querier = LoanAmountQuerier::new(loan_amount);
     }
 
+    pub fn with_custody_balance(&mut self, custody_balance: &[(&String, &Uint256)]) {
+        self.custody_balance_querier = LoanAmountQuerier::new(custody_balance);
+    }
+
+    pub fn with_fees_collected(&mut self, fees_collected: &[(&String, &Uint256)]) {
+        self.fees_collected_querier = LoanAmountQuerier::new(fees_collected);
+    }
+
     pub fn with_liquidation_percent(&mut self, liquidation_percent: &[(&String, &Decimal256)]) {
         self.liquidation_percent_querier = LiquidationPercentQuerier::new(liquidation_percent);
     }
@@ -10,11 +10,14 @@ use cosmwasm_std::{
     attr, from_json, to_json_binary, Addr, Api, BankMsg, CanonicalAddr, Coin, CosmosMsg, Decimal,
     Decimal256, DepsMut, SubMsg, Uint128, Uint256, WasmMsg,
 };
-use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
+use cw20::Cw20ExecuteMsg;
+use moneymarket::astroport_router::AssetInfo;
+use moneymarket::custody::{Cw20HookMsg as CustodyCw20HookMsg, ExecuteMsg as CustodyExecuteMsg};
 use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 use moneymarket::overseer::{
     AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ConfigResponse, ExecuteMsg,
-    InstantiateMsg, QueryMsg, WhitelistResponse, WhitelistResponseElem,
+    FeesCollectedResponse, InstantiateMsg, MarketResponseElem, MarketsResponse, QueryMsg,
+    WhitelistResponse, WhitelistResponseElem,
 };
 
 use std::str::FromStr;
@@ -1066,6 +1069,7 @@ fn unlock_collateral() {
         QueryMsg::BorrowLimit {
             borrower: "addr0000".to_string(),
             block_time: None,
+            market: None,
         },
     )
     .unwrap();
@@ -1153,6 +1157,675 @@ fn unlock_collateral() {
     );
 }
 
+#[test]
+fn multiple_markets() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        borrow_reserves_bucket_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+        platform_fee: PlatformFeeInstantiateMsg {
+            rate: Decimal256::from_str("0").unwrap(),
+            receiver: "cavernperson".to_string(),
+        },
+    };
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterMarket {
+        market_contract: "market2".to_string(),
+        liquidation_contract: "liquidation2".to_string(),
+        stable_asset: AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::MarketAlreadyRegistered {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res: MarketsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Markets {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.elems,
+        vec![MarketResponseElem {
+            market_contract: "market2".to_string(),
+            liquidation_contract: "liquidation2".to_string(),
+            stable_asset: AssetInfo::NativeToken {
+                denom: "ukrw".to_string(),
+            },
+        }]
+    );
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::one(),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("ukrw".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::percent(50),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // 100000 borrowed in each market
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(100000u64))]);
+
+    // borrow_limit = 1000000 * 0.6 = 600000 uusd
+    let borrow_limit = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, market: Option<&str>| {
+        let res: BorrowLimitResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::BorrowLimit {
+                    borrower: "addr0000".to_string(),
+                    block_time: None,
+                    market: market.map(|m| m.to_string()),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.borrow_limit
+    };
+    assert_eq!(borrow_limit(&deps, None), Uint256::from(600000u64));
+    // (600000 - 100000 * 0.5) uusd
+    assert_eq!(
+        borrow_limit(&deps, Some("market")),
+        Uint256::from(550000u64)
+    );
+    // (600000 - 100000) / 0.5 ukrw
+    assert_eq!(
+        borrow_limit(&deps, Some("market2")),
+        Uint256::from(1000000u64)
+    );
+
+    // loans are worth 150000 uusd, the collaterals left must cover them
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(750001u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockTooLarge(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(750000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // the fees of each market are valued in uusd
+    deps.querier.with_fees_collected(&[
+        (&"market".to_string(), &Uint256::from(1000u64)),
+        (&"liquidation".to_string(), &Uint256::from(300u64)),
+        (&"market2".to_string(), &Uint256::from(2000u64)),
+        (&"liquidation2".to_string(), &Uint256::from(400u64)),
+    ]);
+    let res: FeesCollectedResponse =
+        from_json(query(deps.as_ref(), env, QueryMsg::FeesCollected {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        FeesCollectedResponse {
+            origination: Uint256::from(2000u64),
+            flash_loan: Uint256::from(2000u64),
+            liquidation: Uint256::from(500u64),
+            platform: Uint256::zero(),
+        }
+    );
+}
+
+#[test]
+fn deposit_and_borrow() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        borrow_reserves_bucket_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+        platform_fee: PlatformFeeInstantiateMsg {
+            rate: Decimal256::from_str("0").unwrap(),
+            receiver: "cavernperson".to_string(),
+        },
+    };
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // The collateral is pulled with the allowance of the overseer
+    let msg = ExecuteMsg::DepositAndBorrow {
+        collateral_token: "bluna".to_string(),
+        amount: Uint256::from(1000000u64),
+        borrow_amount: Uint256::from(500000u64),
+        market: None,
+        to: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    let lock_and_borrow = ExecuteMsg::LockAndBorrow {
+        borrower: "addr0000".to_string(),
+        collateral_token: "bluna".to_string(),
+        amount: Uint256::from(1000000u64),
+        prev_balance: Uint256::zero(),
+        borrow_amount: Uint256::from(500000u64),
+        market: "market".to_string(),
+        to: "addr0000".to_string(),
+    };
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "bluna".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&Cw20ExecuteMsg::SendFrom {
+                    owner: "addr0000".to_string(),
+                    contract: "custody_bluna".to_string(),
+                    amount: Uint128::from(1000000u128),
+                    msg: to_json_binary(&CustodyCw20HookMsg::DepositCollateral {
+                        borrower: Some("addr0000".to_string()),
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_json_binary(&lock_and_borrow).unwrap(),
+            })),
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        lock_and_borrow.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The custody balance must have increased by the deposited amount
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        lock_and_borrow.clone(),
+    );
+    match res {
+        Err(ContractError::CollateralNotDeposited {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_custody_balance(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        lock_and_borrow,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&CustodyExecuteMsg::LockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(1000000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&MarketExecuteMsg::BorrowStableFromOverseer {
+                    borrower: "addr0000".to_string(),
+                    borrow_amount: Uint256::from(500000u64),
+                    to: Some("addr0000".to_string()),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // Repay with the sent stable, then unlock and withdraw
+    let collaterals = vec![("bluna".to_string(), Uint256::from(1000000u64))];
+    let msg = ExecuteMsg::RepayAndWithdraw {
+        collaterals: collaterals.clone(),
+        market: None,
+    };
+    let funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(500000u128),
+    }];
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &funds),
+        msg,
+    )
+    .unwrap();
+    let unlock_and_withdraw = ExecuteMsg::UnlockAndWithdraw {
+        borrower: "addr0000".to_string(),
+        collaterals,
+    };
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds,
+                msg: to_json_binary(&MarketExecuteMsg::RepayStable {
+                    borrower: Some("addr0000".to_string()),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_json_binary(&unlock_and_withdraw).unwrap(),
+            })),
+        ]
+    );
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        unlock_and_withdraw,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_json_binary(&CustodyExecuteMsg::UnlockAndWithdrawCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(1000000u64),
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn leverage() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        borrow_reserves_bucket_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+        platform_fee: PlatformFeeInstantiateMsg {
+            rate: Decimal256::from_str("0").unwrap(),
+            receiver: "cavernperson".to_string(),
+        },
+    };
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+    deps.querier
+        .with_custody_balance(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    // The target must leave the safety margin below the max LTV
+    let msg = ExecuteMsg::Leverage {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(56),
+        max_slippage: Decimal256::percent(1),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::InvalidTargetLTV {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The first round is bounded by the safe borrow limit, 55% of the collaterals
+    let msg = ExecuteMsg::Leverage {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(50),
+        max_slippage: Decimal256::percent(1),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    let lock_leveraged = ExecuteMsg::LockLeveraged {
+        borrower: "addr0000".to_string(),
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(50),
+        max_slippage: Decimal256::percent(1),
+        prev_balance: Uint256::from(1000000u64),
+        round: 0,
+    };
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&MarketExecuteMsg::BorrowStableFromOverseer {
+                    borrower: "addr0000".to_string(),
+                    borrow_amount: Uint256::from(550000u64),
+                    to: Some("custody_bluna".to_string()),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&CustodyExecuteMsg::BuyCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(550000u64),
+                    minimum_receive: Uint256::from(544500u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_json_binary(&lock_leveraged).unwrap(),
+            })),
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        lock_leveraged.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The bought collateral is locked and the next round starts
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(550000u64))]);
+    deps.querier
+        .with_custody_balance(&[(&"addr0000".to_string(), &Uint256::from(1544500u64))]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        lock_leveraged,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&CustodyExecuteMsg::LockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(544500u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&MarketExecuteMsg::BorrowStableFromOverseer {
+                    borrower: "addr0000".to_string(),
+                    borrow_amount: Uint256::from(299475u64),
+                    to: Some("custody_bluna".to_string()),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&CustodyExecuteMsg::BuyCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(299475u64),
+                    minimum_receive: Uint256::from(296480u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_json_binary(&ExecuteMsg::LockLeveraged {
+                    borrower: "addr0000".to_string(),
+                    collateral_token: "bluna".to_string(),
+                    target_ltv: Decimal256::percent(50),
+                    max_slippage: Decimal256::percent(1),
+                    prev_balance: Uint256::from(1544500u64),
+                    round: 1,
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let msg = ExecuteMsg::Deleverage {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(50),
+        max_slippage: Decimal256::percent(1),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::TargetLTVAlreadyReached {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The sold collateral repays enough to reach the target LTV
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(900000u64))]);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    let check_deleverage = ExecuteMsg::CheckDeleverage {
+        borrower: "addr0000".to_string(),
+        collateral_token: "bluna".to_string(),
+    };
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_json_binary(&CustodyExecuteMsg::SellCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(260714u64),
+                    minimum_receive: Uint256::from(258106u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_json_binary(&check_deleverage).unwrap(),
+            })),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_json(res).unwrap();
+    assert_eq!(
+        collaterals_res.collaterals,
+        vec![("bluna".to_string(), Uint256::from(1283786u64))]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        check_deleverage.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // borrow_limit = 1283786 * 0.6 = 770271, a sale repaying too little reverts
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        check_deleverage.clone(),
+    );
+    match res {
+        Err(ContractError::UnsafeDeleverage {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(641894u64))]);
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        check_deleverage,
+    )
+    .unwrap();
+}
+
 #[test]
 fn liquidate_collateral() {
     let mut deps = mock_dependencies(&[]);
@@ -1262,6 +1935,7 @@ fn liquidate_collateral() {
 
     let msg = ExecuteMsg::LiquidateCollateral {
         borrower: "addr0000".to_string(),
+        market: None,
     };
     let info = mock_info("addr0001", &[]);
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
@@ -1283,6 +1957,7 @@ fn liquidate_collateral() {
                     liquidator: "addr0001".to_string(),
                     borrower: "addr0000".to_string(),
                     amount: Uint256::from(100000u64),
+                    market: None,
                 })
                 .unwrap(),
             })),
@@ -1293,6 +1968,7 @@ fn liquidate_collateral() {
                     liquidator: "addr0001".to_string(),
                     borrower: "addr0000".to_string(),
                     amount: Uint256::from(10000u64),
+                    market: None,
                 })
                 .unwrap(),
             })),
@@ -1745,6 +2421,7 @@ fn partial_liquidate_collateral() {
 
     let msg = ExecuteMsg::LiquidateCollateral {
         borrower: "addr0000".to_string(),
+        market: None,
     };
     let info = mock_info("addr0001", &[]);
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
@@ -1761,6 +2438,7 @@ fn partial_liquidate_collateral() {
                     liquidator: "addr0001".to_string(),
                     borrower: "addr0000".to_string(),
                     amount: Uint256::from(10000u64),
+                    market: None,
                 })
                 .unwrap(),
             })),
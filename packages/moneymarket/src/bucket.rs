@@ -19,6 +19,12 @@ pub enum ExecuteMsg {
         denom: String,
         amount: Uint128,
     },
+    /// Pay out claimed borrower rewards
+    SendTo {
+        denom: String,
+        amount: Uint128,
+        recipient: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -67,6 +67,13 @@ pub struct LSDInstantiateMsg {
     pub known_tokens: Vec<String>,
 }
 
+/// A market registered in the overseer and its liquidation queue
+#[cw_serde]
+pub struct LiquidationMarket {
+    pub market_contract: String,
+    pub liquidation_contract: String,
+}
+
 #[cw_serde]
 pub struct Asset {
     pub amount: Uint128,
@@ -80,6 +87,15 @@ impl AssetInfo {
             AssetInfo::NativeToken { denom } => *denom == c.denom,
         }
     }
+
+    /// Amount of the asset sent along with a message, CW20 tokens are never part of the funds
+    pub fn funds_amount(&self, funds: &[Coin]) -> Uint256 {
+        funds
+            .iter()
+            .find(|c| self.is_same_asset(c))
+            .map(|c| Uint256::from(c.amount))
+            .unwrap_or_else(Uint256::zero)
+    }
 }
 
 impl Asset {
@@ -125,6 +141,9 @@ pub enum ExecuteMsg {
     LockCollateral { borrower: String, amount: Uint256 },
     /// Make specified amount of collateral tokens spendable
     UnlockCollateral { borrower: String, amount: Uint256 },
+    /// Make specified amount of collateral tokens spendable
+    /// and send them back to the borrower
+    UnlockAndWithdrawCollateral { borrower: String, amount: Uint256 },
     /// Claim bAsset rewards and distribute claimed rewards
     /// to market and overseer contracts
     DistributeRewards {},
@@ -134,6 +153,36 @@ pub enum ExecuteMsg {
         liquidator: String,
         borrower: String,
         amount: Uint256,
+        /// Market to repay with the liquidated collateral,
+        /// the one of the custody config if not set
+        market: Option<LiquidationMarket>,
+    },
+
+    /// Swap the stable sent by the market to collateral tokens
+    /// and deposit them for the borrower
+    BuyCollateral {
+        borrower: String,
+        amount: Uint256,
+        minimum_receive: Uint256,
+    },
+    /// Swap locked collateral tokens of the borrower to stable
+    /// and repay its loan in the market
+    SellCollateral {
+        borrower: String,
+        amount: Uint256,
+        minimum_receive: Uint256,
+    },
+
+    ////////////////////
+    /// Internal operations
+    ////////////////////
+    DepositBoughtCollateral {
+        borrower: String,
+        prev_balance: Uint256,
+    },
+    RepayWithSoldCollateral {
+        borrower: String,
+        prev_balance: Uint256,
     },
 
     ////////////////////
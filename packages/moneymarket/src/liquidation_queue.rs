@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use cosmwasm_std::{Decimal256, Uint128, Uint256};
 use cw20::Cw20ReceiveMsg;
 
+use crate::astroport_router::AssetInfo;
 use crate::tokens::TokensHuman;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -29,6 +30,10 @@ pub struct InstantiateMsg {
     /// Time period that needs to pass for a bid to be activated (seconds)
     pub waiting_period: u64,
     pub overseer: String,
+    /// Stable asset of the bids, defaults to the native `stable_denom`.
+    /// For a CW20 stable, `stable_denom` must be the token address
+    #[serde(default)]
+    pub stable_asset: Option<AssetInfo>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -52,11 +57,17 @@ pub enum ExecuteMsg {
         bid_threshold: Uint256,
         max_slot: u8,
         premium_rate_per_slot: Decimal256,
+        /// Premium rate of each slot from 0 to `max_slot`, replacing
+        /// `slot * premium_rate_per_slot`
+        premium_rates: Option<Vec<Decimal256>>,
     },
     UpdateCollateralInfo {
         collateral_token: String,
         bid_threshold: Option<Uint256>,
         max_slot: Option<u8>,
+        /// New premium rates of the slots, empty to go back to the linear
+        /// premium. The existing bid pools are updated
+        premium_rates: Option<Vec<Decimal256>>,
     },
     RemoveCollateral {
         collateral_token: String,
@@ -71,6 +82,11 @@ pub enum ExecuteMsg {
         bid_idx: Uint128,
         amount: Option<Uint256>,
     },
+    /// Move a bid, with its unclaimed collateral, to another wallet
+    TransferBid {
+        bid_idx: Uint128,
+        recipient: String,
+    },
     /// After waiting_period expires, user can activate the bid
     ActivateBids {
         collateral_token: String,
@@ -81,6 +97,108 @@ pub enum ExecuteMsg {
         collateral_token: String,
         bids_idx: Option<Vec<Uint128>>,
     },
+    /// Owner operation to set the DEX routers used to sell auto-claimed collateral
+    UpdateSwapConfig {
+        astroport_addr: String,
+        phoenix_addr: String,
+        terraswap_addr: String,
+        /// Max slippage of a swap versus the oracle price
+        max_slippage: Decimal256,
+    },
+    /// Owner operation to sell the collateral the queue can't fill in a
+    /// Dutch auction, `None` to require the queue to fill the liquidations
+    UpdateDutchAuction {
+        collateral_token: String,
+        dutch_auction: Option<DutchAuction>,
+    },
+    /// Owner operation to market sell on the configured DEX the collateral
+    /// the queue can't fill, within the max slippage of the swap config
+    UpdateDexFallback {
+        collateral_token: String,
+        enabled: bool,
+    },
+    /// Owner operation to fill the oldest active bids of a slot first instead
+    /// of pro-rata. Enabling requires the collateral to have no active bids
+    /// and a minimum bid amount
+    UpdateAgePriority {
+        collateral_token: String,
+        enabled: bool,
+        min_bid_amount: Option<Uint256>,
+    },
+    /// Buy collateral of an auction lot with the sent stable at the
+    /// current discount, the excess stable is refunded
+    BuyAuction {
+        collateral_token: String,
+        auction_idx: Uint128,
+    },
+    /// Set how the liquidated collateral of a bid is delivered without
+    /// claiming it, `None` to go back to `ClaimLiquidations`
+    SetAutoClaim {
+        bid_idx: Uint128,
+        auto_claim: Option<AutoClaim>,
+    },
+    /// Deliver the liquidated collateral of the auto-claim bids of a bidder
+    ClaimFor {
+        bidder: String,
+        collateral_token: String,
+    },
+    /// Deposit stable in the vault of a premium slot in exchange of vault shares.
+    /// The vault bids on behalf of its depositors and compounds the liquidated collateral
+    DepositVault {
+        collateral_token: String,
+        premium_slot: u8,
+    },
+    /// Redeem vault shares for their part of the remaining stable and of the
+    /// collateral not sold yet
+    WithdrawVault {
+        collateral_token: String,
+        premium_slot: u8,
+        shares: Option<Uint256>,
+    },
+    /// Owner operation to instantiate a CW20 share token for a vault without
+    /// shares. Deposits then mint the token, which is redeemed with the
+    /// `WithdrawVault` hook
+    CreateVaultToken {
+        collateral_token: String,
+        premium_slot: u8,
+        token_code_id: u64,
+        name: String,
+        symbol: String,
+    },
+    /// Activate the waiting vault bids and sell the liquidated collateral,
+    /// the proceeds are bid again in the same premium slot
+    CompoundVault {
+        collateral_token: String,
+        premium_slot: u8,
+    },
+
+    ////////////////////
+    /// Internal operations
+    ////////////////////
+    RebidVault {
+        collateral_token: String,
+        premium_slot: u8,
+        prev_balance: Uint256,
+    },
+}
+
+/// Delivery of the liquidated collateral of an auto-claim bid
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoClaim {
+    /// Transfer the collateral to the bidder
+    Collateral,
+    /// Sell the collateral for the stable asset on the best configured DEX
+    Stable,
+}
+
+/// Discount versus the oracle price of the collateral of an auction lot,
+/// growing each block from the start of the auction up to `max_discount`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DutchAuction {
+    pub start_discount: Decimal256,
+    pub discount_per_block: Decimal256,
+    pub max_discount: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -91,6 +209,29 @@ pub enum Cw20HookMsg {
         liquidator: String, // Legacy parameter, ignored
         fee_address: Option<String>,
         repay_address: Option<String>,
+        /// Borrower of the liquidated loan, the Dutch auction
+        /// lots repay their loan in the market of `repay_address`
+        borrower: Option<String>,
+    },
+    /// Submit a new bid with the sent CW20 stable
+    SubmitBid {
+        collateral_token: String,
+        premium_slot: u8,
+    },
+    /// Deposit the sent CW20 stable in the vault of a premium slot
+    DepositVault {
+        collateral_token: String,
+        premium_slot: u8,
+    },
+    /// Redeem the sent vault share tokens
+    WithdrawVault {
+        collateral_token: String,
+        premium_slot: u8,
+    },
+    /// Buy collateral of an auction lot with the sent CW20 stable
+    BuyAuction {
+        collateral_token: String,
+        auction_idx: Uint128,
     },
 }
 
@@ -125,6 +266,39 @@ pub enum QueryMsg {
         start_after: Option<u8>,
         limit: Option<u8>,
     },
+    FeesCollected {},
+    Vault {
+        collateral_token: String,
+        premium_slot: u8,
+    },
+    VaultShares {
+        collateral_token: String,
+        premium_slot: u8,
+        depositor: String,
+    },
+    DutchAuction {
+        collateral_token: String,
+    },
+    DexFallback {
+        collateral_token: String,
+    },
+    AgePriority {
+        collateral_token: String,
+    },
+    Auctions {
+        collateral_token: String,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    },
+    /// Simulates the liquidation of `collateral_amount` against the bid pools
+    LiquidationDepth {
+        collateral_token: String,
+        collateral_amount: Uint256,
+    },
+    /// Bids of `bidder` aggregated by collateral
+    ClaimableByUser {
+        bidder: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -143,6 +317,103 @@ pub struct ConfigResponse {
     pub price_timeframe: u64,
     pub waiting_period: u64,
     pub overseer: String,
+    pub stable_asset: AssetInfo,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeesCollectedResponse {
+    pub bid_fee: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VaultResponse {
+    pub total_shares: Uint256,
+    /// Remaining stable of the vault bids
+    pub bid_amount: Uint256,
+    /// Liquidated collateral of the vault, to be sold on the next compounding
+    pub collateral_amount: Uint256,
+    pub share_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VaultSharesResponse {
+    pub shares: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DutchAuctionResponse {
+    pub dutch_auction: Option<DutchAuction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DexFallbackResponse {
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AgePriorityResponse {
+    pub enabled: bool,
+    pub min_bid_amount: Option<Uint256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionResponse {
+    pub idx: Uint128,
+    pub collateral_token: String,
+    /// Borrower whose loan is repaid with the proceeds
+    pub borrower: String,
+    /// Collateral left in the lot
+    pub amount: Uint256,
+    pub start_height: u64,
+    /// Discount at the current block
+    pub discount: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionsResponse {
+    pub auctions: Vec<AuctionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationDepthResponse {
+    pub collateral_token: String,
+    pub collateral_amount: Uint256,
+    /// Stable sent to the repay address, net of the bid and liquidator fees
+    pub repay_amount: Uint256,
+    /// Discount of the repay amount to the oracle value of the filled collateral
+    pub effective_discount: Decimal256,
+    pub slots: Vec<SlotDepthResponse>,
+    /// Collateral the bid pools can't fill
+    pub unfilled_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SlotDepthResponse {
+    pub premium_slot: u8,
+    pub premium_rate: Decimal256,
+    /// Stable consumed from the bids of the slot
+    pub bid_amount: Uint256,
+    pub collateral_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimableByUserResponse {
+    pub bidder: String,
+    /// Whitelisted collaterals with bids of the user
+    pub collaterals: Vec<ClaimableCollateralResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimableCollateralResponse {
+    pub collateral_token: String,
+    /// Liquidated collateral of the bids, as reported by `BidsByUser`
+    pub pending_collateral: Uint256,
+    /// Collateral sent by claiming every activated bid, pool residues included
+    pub claimable_collateral: Uint256,
+    /// Remaining stable of the activated bids
+    pub bid_amount: Uint256,
+    /// Stable of the bids waiting for activation
+    pub waiting_bid_amount: Uint256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -163,6 +434,7 @@ pub struct BidResponse {
     pub wait_end: Option<u64>,
     pub epoch_snapshot: Uint128,
     pub scale_snapshot: Uint128,
+    pub auto_claim: Option<AutoClaim>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -186,6 +458,7 @@ pub struct CollateralInfoResponse {
     pub bid_threshold: Uint256,
     pub max_slot: u8,
     pub premium_rate_per_slot: Decimal256,
+    pub premium_rates: Option<Vec<Decimal256>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -1,9 +1,13 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Decimal256, Uint256};
+use cosmwasm_std::{
+    to_json_binary, Binary, Coin, CosmosMsg, Decimal256, StdResult, Uint256, WasmMsg,
+};
 use cw20::Cw20ReceiveMsg;
 
+use crate::astroport_router::AssetInfo;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct InstantiateMsg {
@@ -23,10 +27,17 @@ pub struct InstantiateMsg {
 
     /// Initial rate of rewards used for borrowers
     pub initial_borrower_incentives: Decimal256,
+
+    /// Stable asset lent by the market, defaults to the native `stable_denom`.
+    /// For a CW20 stable, `stable_denom` must be the token address and the initial
+    /// deposit must be transferred to the market address before instantiation
+    #[serde(default)]
+    pub stable_asset: Option<AssetInfo>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
 
@@ -57,6 +68,45 @@ pub enum ExecuteMsg {
         interest_model: Option<String>,
         max_borrow_subsidy_rate: Option<Decimal256>,
         distribution_model: Option<String>,
+        fixed_rate: Option<FixedRateConfigMsg>,
+        flash_loan_fee_rate: Option<Decimal256>,
+        borrower_rewards_mode: Option<BorrowerRewardsMode>,
+        reserve_mode: Option<ReserveMode>,
+        origination_fee: Option<OriginationFee>,
+    },
+
+    /// Send part of the reserves, by default to the collector contract
+    WithdrawReserves {
+        amount: Uint256,
+        to: Option<String>,
+    },
+
+    /// Create or update an interest tier,
+    /// its borrowers pay `1 - discount` of the borrow interest
+    SetInterestTier {
+        tier_id: u64,
+        discount: Decimal256,
+    },
+
+    /// Move a borrower to an interest tier, `None` for the default tier
+    SetBorrowerTier {
+        borrower: String,
+        tier_id: Option<u64>,
+    },
+
+    /// Book the floating rate liabilities at the sum of the borrower loans,
+    /// the difference is taken from or added to the reserves.
+    /// Scans a page of borrowers per execution, the liabilities
+    /// are adjusted once a page comes back shorter than `limit`
+    ReconcileLiabilities {
+        max_drift: Decimal256,
+        limit: Option<u32>,
+    },
+
+    /// Index a page of borrowers by debt, for the loans
+    /// of a market migrated from before the debt index
+    IndexBorrowersByDebt {
+        limit: Option<u32>,
     },
 
     ////////////////////
@@ -67,6 +117,13 @@ pub enum ExecuteMsg {
         borrower: String,
         prev_balance: Uint256,
     },
+    /// Borrow stable for a borrower whose collaterals were
+    /// deposited and locked through the overseer in the same transaction
+    BorrowStableFromOverseer {
+        borrower: String,
+        borrow_amount: Uint256,
+        to: Option<String>,
+    },
 
     /// Execute epoch operations
     /// 1. send reserve to collector contract
@@ -95,6 +152,155 @@ pub enum ExecuteMsg {
     RepayStable {
         borrower: Option<String>,
     },
+
+    /// Repay the floating rate loan of a borrower, then their fixed rate loans,
+    /// the way a liquidation does. The Dutch auction lots of a liquidation
+    /// repay the loan with this message
+    RepayStableFromAuction {
+        borrower: String,
+    },
+
+    /// Borrow stable asset at a fixed rate for `term` blocks.
+    /// The rate is quoted from the current borrow rate plus the fixed rate premium
+    BorrowFixed {
+        borrow_amount: Uint256,
+        term: u64,
+        to: Option<String>,
+    },
+
+    /// Repay a fixed rate loan, totally or partially
+    /// You can repay a loan on behalf of someone else
+    RepayFixed {
+        loan_id: u64,
+    },
+
+    /// Roll a matured fixed rate loan over a new term
+    /// at the currently quoted fixed rate
+    RolloverFixed {
+        loan_id: u64,
+        term: u64,
+    },
+
+    /// Allow a delegate to borrow up to `amount` against the sender's collaterals
+    ApproveDelegate {
+        delegate: String,
+        amount: Uint256,
+    },
+
+    /// Remove the borrow allowance of a delegate
+    RevokeDelegate {
+        delegate: String,
+    },
+
+    /// Borrow stable asset against the collaterals of a borrower
+    /// who approved the sender as a delegate.
+    /// The debt is recorded on the borrower, the funds are sent to the delegate
+    BorrowStableOnBehalf {
+        borrower: String,
+        amount: Uint256,
+    },
+
+    /// Lend stable asset to the sender contract for the duration of the transaction.
+    /// The sender is called back with a `FlashLoanReceiveMsg` and must send
+    /// back `amount + fee` to the market before the callback returns
+    FlashLoan {
+        amount: Uint256,
+        msg: Binary,
+    },
+
+    /// Claim the borrower rewards accrued in `Claimable` mode
+    ClaimBorrowerRewards {
+        to: Option<String>,
+    },
+
+    /// Cancel a queued redemption, the aTerra left is sent back
+    CancelRedemption {
+        redemption_id: u64,
+    },
+}
+
+/// How the borrow incentives of the bucket are given to the borrowers
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BorrowerRewardsMode {
+    /// The incentives lower the borrow interest of everyone
+    #[default]
+    Subsidize,
+    /// The incentives are kept in the bucket and can be claimed by the borrowers
+    Claimable,
+}
+
+/// How the reserves of the market accrue
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReserveMode {
+    /// The yield above the target deposit rate goes to the reserves,
+    /// which are all sent to the collector at each epoch
+    #[default]
+    TargetDepositRate,
+    /// A share of the borrower interest goes to the reserves,
+    /// which are kept until withdrawn with `WithdrawReserves`
+    ReserveFactor { reserve_factor: Decimal256 },
+}
+
+/// Fee charged on the floating rate loans when they are borrowed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct OriginationFee {
+    pub rate: Decimal256,
+    /// Deduct the fee from the sent amount instead of adding it to the loan
+    pub deduct_from_disbursement: bool,
+    /// The fee goes to the reserves when no receiver is set
+    pub receiver: Option<String>,
+}
+
+/// Callback sent to the flash loan receiver, along with the borrowed funds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FlashLoanReceiveMsg {
+    pub amount: Uint256,
+    pub fee: Uint256,
+    pub msg: Binary,
+}
+
+impl FlashLoanReceiveMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = FlashLoanReceiverExecuteMsg::FlashLoanReceive(self);
+        to_json_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct and the borrowed funds to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(
+        self,
+        contract_addr: T,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds,
+        };
+        Ok(execute.into())
+    }
+}
+
+// This is just a helper to properly serialize the above message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum FlashLoanReceiverExecuteMsg {
+    FlashLoanReceive(FlashLoanReceiveMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FixedRateConfigMsg {
+    /// Per block rate added to the borrow rate when quoting fixed rate loans
+    pub premium_rate: Option<Decimal256>,
+    /// Minimum term of a fixed rate loan, in blocks
+    pub min_term: Option<u64>,
+    /// Maximum term of a fixed rate loan, in blocks
+    pub max_term: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -103,6 +309,21 @@ pub enum Cw20HookMsg {
     /// Return stable coins to a user
     /// according to exchange rate
     RedeemStable {},
+    /// Repay a loan with the value of the sent aTerra
+    /// according to exchange rate
+    RepayStableWithAterra { borrower: Option<String> },
+    /// Deposit the sent CW20 stable
+    DepositStable {},
+    /// Repay a loan with the sent CW20 stable
+    RepayStable { borrower: Option<String> },
+    /// Repay the floating then the fixed rate loans of a borrower
+    /// with the sent CW20 stable, the way a liquidation does
+    RepayStableFromAuction { borrower: String },
+    /// Repay a fixed rate loan with the sent CW20 stable
+    RepayFixed { loan_id: u64 },
+    /// Escrow the sent aTerra until the market has enough stable to redeem it.
+    /// Queued redemptions are filled in order from the following deposits and repayments
+    QueueRedemption {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -123,9 +344,59 @@ pub enum QueryMsg {
         borrower: String,
         block_height: Option<u64>,
     },
+    /// Stored liabilities, or the liabilities with
+    /// the interest accrued up to `block_height`
     BorrowerInfos {
         start_after: Option<String>,
         limit: Option<u32>,
+        block_height: Option<u64>,
+    },
+    /// Borrowers ordered by their stored loan amount, largest first
+    TopBorrowers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        block_height: Option<u64>,
+    },
+    FixedRateQuote {},
+    Allowance {
+        borrower: String,
+        delegate: String,
+    },
+    Allowances {
+        borrower: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    FixedLoan {
+        loan_id: u64,
+        block_height: Option<u64>,
+    },
+    FixedLoans {
+        borrower: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        block_height: Option<u64>,
+    },
+    Redemptions {
+        redeemer: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    Reserves {
+        block_height: Option<u64>,
+    },
+    InterestTiers {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    FeesCollected {},
+    /// Compare the floating rate liabilities with the interest-accrued loans,
+    /// one page of borrowers at a time
+    LiabilitiesDrift {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        block_height: Option<u64>,
+        scanned: Option<LiabilitiesScan>,
     },
 }
 
@@ -141,8 +412,13 @@ pub struct ConfigResponse {
     pub distributor_contract: String,
     pub borrow_reserves_bucket_contract: String,
     pub stable_denom: String,
+    pub stable_asset: AssetInfo,
     pub max_borrow_factor: Decimal256,
     pub max_borrow_subsidy_rate: Decimal256,
+    pub flash_loan_fee_rate: Decimal256,
+    pub borrower_rewards_mode: BorrowerRewardsMode,
+    pub reserve_mode: ReserveMode,
+    pub origination_fee: OriginationFee,
 }
 
 // We define a custom struct for each query response
@@ -158,6 +434,8 @@ pub struct StateResponse {
     pub prev_aterra_supply: Uint256,
     pub prev_exchange_rate: Decimal256,
     pub prev_borrower_incentives: Uint256,
+    pub reserved_borrower_rewards: Uint256,
+    pub global_claimable_reward_index: Decimal256,
 }
 
 // We define a custom struct for each query response
@@ -178,6 +456,14 @@ pub struct BorrowerInfoResponse {
     pub reward_index: Decimal256,
     pub loan_amount: Uint256,
     pub pending_rewards: Decimal256,
+    /// Rewards that can be claimed with `ClaimBorrowerRewards`
+    pub claimable_rewards: Decimal256,
+    /// Amount owed on all the fixed rate loans of the borrower
+    pub fixed_loan_amount: Uint256,
+    /// Interest tier of the borrower, `None` for the default tier
+    pub interest_tier: Option<u64>,
+    /// Per block borrow rate of the borrower, after the tier discount
+    pub borrow_rate: Decimal256,
 }
 
 // We define a custom struct for each query response
@@ -186,6 +472,116 @@ pub struct BorrowerInfosResponse {
     pub borrower_infos: Vec<BorrowerInfoResponse>,
 }
 
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllowanceResponse {
+    pub borrower: String,
+    pub delegate: String,
+    pub allowance: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllowancesResponse {
+    pub allowances: Vec<AllowanceResponse>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FixedRateQuoteResponse {
+    /// Per block rate a fixed rate loan would lock right now
+    pub rate: Decimal256,
+    pub premium_rate: Decimal256,
+    pub min_term: u64,
+    pub max_term: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FixedLoanResponse {
+    pub loan_id: u64,
+    pub borrower: String,
+    pub principal: Uint256,
+    pub rate: Decimal256,
+    pub start_height: u64,
+    pub maturity_height: u64,
+    /// Principal plus the interest accrued at the requested block height
+    pub amount_due: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FixedLoansResponse {
+    pub loans: Vec<FixedLoanResponse>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RedemptionResponse {
+    pub redemption_id: u64,
+    pub redeemer: String,
+    /// aTerra left to redeem
+    pub aterra_amount: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RedemptionsResponse {
+    pub redemptions: Vec<RedemptionResponse>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InterestTierResponse {
+    pub tier_id: u64,
+    pub discount: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InterestTiersResponse {
+    pub tiers: Vec<InterestTierResponse>,
+}
+
+/// Running totals of a `LiabilitiesDrift` scan
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct LiabilitiesScan {
+    /// Floating rate loans of the borrowers scanned so far
+    pub loans: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiabilitiesDriftResponse {
+    /// Total liabilities minus the fixed rate liabilities
+    pub floating_liabilities: Decimal256,
+    pub scanned: LiabilitiesScan,
+    /// Start of the next page, `None` once all the borrowers are scanned
+    pub last_borrower: Option<String>,
+    /// Liabilities above the scanned loans, final once the scan is over
+    pub excess_liabilities: Decimal256,
+    /// Scanned loans above the liabilities, final once the scan is over
+    pub missing_liabilities: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReservesResponse {
+    pub reserve_mode: ReserveMode,
+    /// Reserves held by the market at the requested block height
+    pub total_reserves: Decimal256,
+    /// Reserves sent out, by the epoch operations or `WithdrawReserves`
+    pub total_withdrawn: Uint256,
+    pub total_accrued: Decimal256,
+}
+
+/// Fees collected by the market since the ledger was introduced
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeesCollectedResponse {
+    pub origination: Uint256,
+    pub flash_loan: Uint256,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct MigrateMsg {}
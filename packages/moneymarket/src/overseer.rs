@@ -2,6 +2,7 @@ use cosmwasm_schema::cw_serde;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::astroport_router::AssetInfo;
 use crate::tokens::TokensHuman;
 use cosmwasm_std::{Decimal256, Uint256};
 
@@ -100,6 +101,19 @@ pub enum ExecuteMsg {
         max_ltv: Option<Decimal256>,      // Loan To Value ratio
     },
 
+    /// Register a market borrowers can borrow from against their collaterals.
+    /// The market of the config is always registered
+    RegisterMarket {
+        market_contract: String,
+        liquidation_contract: String,
+        stable_asset: AssetInfo,
+    },
+    /// Update registered market info
+    UpdateMarket {
+        market_contract: String,
+        liquidation_contract: Option<String>,
+    },
+
     /// Claims all staking rewards from the bAsset contracts
     /// and also do a epoch basis updates
     /// 1. Distribute interest buffers to depositors
@@ -120,12 +134,80 @@ pub enum ExecuteMsg {
     UnlockCollateral {
         collaterals: TokensHuman, // <(Collateral Token, Amount)>
     },
+    /// Deposit `amount` of the collateral token with the allowance given to the overseer,
+    /// lock it and borrow from `market`, the market of the config if not set.
+    /// The borrowed stable is sent to `to`, the sender if not set
+    DepositAndBorrow {
+        collateral_token: String,
+        amount: Uint256,
+        borrow_amount: Uint256,
+        market: Option<String>,
+        to: Option<String>,
+    },
+    /// Repay the loan in `market` with the sent stable,
+    /// then unlock and withdraw the collaterals
+    RepayAndWithdraw {
+        collaterals: TokensHuman, // <(Collateral Token, Amount)>
+        market: Option<String>,
+    },
+
+    /// Borrow stable in the market of the config, swap it to the collateral token
+    /// and lock it, round after round until the LTV of the sender reaches `target_ltv`.
+    /// `max_slippage` bounds each swap against the oracle price
+    Leverage {
+        collateral_token: String,
+        target_ltv: Decimal256,
+        max_slippage: Decimal256,
+    },
+    /// Sell locked collateral to repay the loan in the market of the config
+    /// until the LTV of the sender goes down to `target_ltv`
+    Deleverage {
+        collateral_token: String,
+        target_ltv: Decimal256,
+        max_slippage: Decimal256,
+    },
+
+    ////////////////////
+    /// Internal operations
+    ////////////////////
+    /// Lock the deposited collateral once the custody balance is checked, then borrow
+    LockAndBorrow {
+        borrower: String,
+        collateral_token: String,
+        amount: Uint256,
+        prev_balance: Uint256,
+        borrow_amount: Uint256,
+        market: String,
+        to: String,
+    },
+    /// Unlock and withdraw the collaterals after the repayment
+    UnlockAndWithdraw {
+        borrower: String,
+        collaterals: TokensHuman,
+    },
+    /// Lock the collateral bought in a leverage round and start the next one
+    LockLeveraged {
+        borrower: String,
+        collateral_token: String,
+        target_ltv: Decimal256,
+        max_slippage: Decimal256,
+        prev_balance: Uint256,
+        round: u32,
+    },
+    /// Check the borrow limit once the collateral sold by a deleverage repaid the loan
+    CheckDeleverage {
+        borrower: String,
+        collateral_token: String,
+    },
 
     /////////////////////////////
     /// Permissionless operations
     /////////////////////////////
+    /// Liquidate the collaterals of the borrower to repay its loan in `market`,
+    /// the market of the config if not set
     LiquidateCollateral {
         borrower: String,
+        market: Option<String>,
     },
 
     FundReserve {},
@@ -162,11 +244,24 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Borrow limit in the common unit of the oracle,
+    /// or in the stable asset of `market` minus the loans in the other markets
     #[returns(BorrowLimitResponse)]
     BorrowLimit {
         borrower: String,
         block_time: Option<u64>,
+        market: Option<String>,
     },
+    /// Markets registered on top of the market of the config
+    #[returns(MarketsResponse)]
+    Markets {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Fees collected by the protocol on all the markets, by category,
+    /// valued in the stable denom of the config
+    #[returns(FeesCollectedResponse)]
+    FeesCollected {},
 }
 
 // We define a custom struct for each query response
@@ -220,6 +315,26 @@ pub struct AllCollateralsResponse {
     pub all_collaterals: Vec<CollateralsResponse>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketResponseElem {
+    pub market_contract: String,
+    pub liquidation_contract: String,
+    pub stable_asset: AssetInfo,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketsResponse {
+    pub elems: Vec<MarketResponseElem>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeesCollectedResponse {
+    pub origination: Uint256,
+    pub flash_loan: Uint256,
+    pub liquidation: Uint256,
+    pub platform: Uint256,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BorrowLimitResponse {
     pub borrower: String,
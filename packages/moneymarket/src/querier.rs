@@ -7,7 +7,7 @@ use cosmwasm_std::{
     to_json_binary, Addr, AllBalanceResponse, BalanceResponse, BankQuery, Coin, Deps, QueryRequest,
     StdError, StdResult, Uint128, Uint256, WasmQuery,
 };
-use cw20::{Cw20QueryMsg, TokenInfoResponse};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 
 use crate::{
     astroport_router::AssetInfo,
@@ -87,6 +87,7 @@ pub fn query_token_balance(
                 address: account_addr.to_string(),
             })?,
         }))
+        .map(|res: Cw20BalanceResponse| res.balance)
         .unwrap_or_else(|_| Uint128::zero());
 
     Ok(balance.into())